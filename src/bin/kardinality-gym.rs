@@ -0,0 +1,86 @@
+//! JSON-over-stdio server for the training environment in `kardinality::game::env`.
+//!
+//! Each input line is one request and gets exactly one response line:
+//!
+//! ```text
+//! {"cmd":"spec"}                -> {"obs_len":..,"action_count":..,"actions":[..]}
+//! {"cmd":"reset","seed":7}      -> {"obs":[..],"mask":[..]}
+//! {"cmd":"step","action":3}     -> {"obs":[..],"reward":..,"done":..,"info":{..},"mask":[..]}
+//! {"cmd":"close"}               -> {"ok":true}, then exit
+//! ```
+//!
+//! Malformed requests get `{"error":"..."}` and the server keeps running.
+
+use std::io::{self, BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use kardinality::game::config::RunConfig;
+use kardinality::game::env::{self, ACTION_COUNT, Env, OBS_LEN};
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Request {
+    Spec,
+    Reset {
+        #[serde(default)]
+        seed: u64,
+    },
+    Step {
+        action: usize,
+    },
+    Close,
+}
+
+fn handle(env: &mut Env, request: Request) -> Value {
+    match request {
+        Request::Spec => json!({
+            "obs_len": OBS_LEN,
+            "action_count": ACTION_COUNT,
+            "actions": (0..ACTION_COUNT)
+                .map(|i| env::action_for(i).map(|a| format!("{a:?}")))
+                .collect::<Vec<_>>(),
+        }),
+        Request::Reset { seed } => json!({
+            "obs": env.reset(seed).as_slice(),
+            "mask": env.action_mask().as_slice(),
+        }),
+        Request::Step { action } => {
+            let step = env.step(action);
+            json!({
+                "obs": step.obs.as_slice(),
+                "reward": step.reward,
+                "done": step.done,
+                "info": step.info,
+                "mask": env.action_mask().as_slice(),
+            })
+        }
+        Request::Close => json!({ "ok": true }),
+    }
+}
+
+fn main() -> io::Result<()> {
+    let mut env = Env::new(RunConfig::standard());
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (response, close) = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let close = matches!(request, Request::Close);
+                (handle(&mut env, request), close)
+            }
+            Err(e) => (json!({ "error": e.to_string() }), false),
+        };
+        writeln!(stdout, "{response}")?;
+        stdout.flush()?;
+        if close {
+            break;
+        }
+    }
+    Ok(())
+}
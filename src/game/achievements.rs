@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::{TraceEvent, cards, puzzles};
+
+/// An achievement and the rule that unlocks it. Declared in `achievements.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AchievementDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_icon")]
+    pub icon: String,
+    pub rule: Rule,
+}
+
+fn default_icon() -> String {
+    "★".to_string()
+}
+
+/// Condition checked against the trace stream.
+///
+/// "Hand" rules count within one `PlayHand` dispatch, i.e. between two `Action` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// Score gained by the cards of one hand reaches this value.
+    HandScore(i64),
+    /// One hand applies this many effects of a kind (see [`Effect::name`](crate::vm::Effect::name)).
+    HandEffects { effect: String, count: u32 },
+    /// One hand calls this builtin this many times, by long or short name.
+    HandCalls { name: String, count: u32 },
+    /// Clear this Classic level (or a later one).
+    ClearLevel(u32),
+    /// Clear any Classic level with at most this much bankroll left.
+    ClearLevelWithBankrollAtMost(i64),
+    /// Solve every listed puzzle, across sessions; an empty list means every built-in lesson.
+    SolvePuzzles(Vec<String>),
+    /// Solve any puzzle with at least this many stars.
+    SolveWithStars(u8),
+}
+
+static CATALOG: LazyLock<Vec<AchievementDef>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("achievements.json")).expect("valid achievements.json")
+});
+
+pub fn catalog() -> &'static [AchievementDef] {
+    &CATALOG
+}
+
+pub fn get(id: &str) -> Option<&'static AchievementDef> {
+    catalog().iter().find(|a| a.id == id)
+}
+
+/// Running counts for the hand currently in the trace.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct HandTally {
+    score: i64,
+    effects: BTreeMap<&'static str, u32>,
+    calls: BTreeMap<String, u32>,
+}
+
+/// Unlocked achievements plus the cross-session facts rules need; stored locally.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AchievementLog {
+    /// Unlocked ids, oldest first.
+    pub unlocked: Vec<String>,
+    pub solved_puzzles: BTreeSet<String>,
+    #[serde(skip)]
+    hand: HandTally,
+}
+
+impl AchievementLog {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.iter().any(|u| u == id)
+    }
+
+    /// Feed new trace events; returns the achievements they unlocked, in order.
+    pub fn observe<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a TraceEvent>,
+    ) -> Vec<&'static AchievementDef> {
+        let mut fresh = Vec::new();
+        for event in events {
+            match event {
+                TraceEvent::Action { .. } => self.hand = HandTally::default(),
+                TraceEvent::CardEnd { delta, .. } => self.hand.score += delta.score,
+                TraceEvent::EffectApplied { effect } => {
+                    *self.hand.effects.entry(effect.name()).or_default() += 1;
+                }
+                TraceEvent::Call { name, .. } => {
+                    let name = cards::canonical_function(name).to_string();
+                    *self.hand.calls.entry(name).or_default() += 1;
+                }
+                TraceEvent::PuzzleSolved { id, .. } => {
+                    self.solved_puzzles.insert(id.clone());
+                }
+                _ => {}
+            }
+            for def in catalog() {
+                if !self.is_unlocked(&def.id) && self.met(&def.rule, event) {
+                    self.unlocked.push(def.id.clone());
+                    fresh.push(def);
+                }
+            }
+        }
+        fresh
+    }
+
+    fn met(&self, rule: &Rule, event: &TraceEvent) -> bool {
+        match (rule, event) {
+            (Rule::HandScore(n), TraceEvent::CardEnd { .. }) => self.hand.score >= *n,
+            (Rule::HandEffects { effect, count }, TraceEvent::EffectApplied { .. }) => {
+                self.hand.effects.get(effect.as_str()).copied().unwrap_or(0) >= *count
+            }
+            (Rule::HandCalls { name, count }, TraceEvent::Call { .. }) => {
+                let name = cards::canonical_function(name);
+                self.hand.calls.get(name).copied().unwrap_or(0) >= *count
+            }
+            (Rule::ClearLevel(n), TraceEvent::LevelCleared { level, .. }) => level >= n,
+            (Rule::ClearLevelWithBankrollAtMost(n), TraceEvent::LevelCleared { bankroll, .. }) => {
+                bankroll <= n
+            }
+            (Rule::SolvePuzzles(ids), TraceEvent::PuzzleSolved { .. }) if ids.is_empty() => {
+                puzzles::catalog()
+                    .iter()
+                    .all(|p| self.solved_puzzles.contains(p.id))
+            }
+            (Rule::SolvePuzzles(ids), TraceEvent::PuzzleSolved { .. }) => {
+                ids.iter().all(|id| self.solved_puzzles.contains(id))
+            }
+            (Rule::SolveWithStars(n), TraceEvent::PuzzleSolved { stars, .. }) => stars >= n,
+            _ => false,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parse the stored log; unreadable data yields an empty log.
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Action, CardInstance, Engine};
+    use crate::vm::Effect;
+    use crate::vm::Limits;
+
+    #[test]
+    fn catalog_parses_with_unique_ids_and_known_names() {
+        let mut ids = BTreeSet::new();
+        for def in catalog() {
+            assert!(ids.insert(def.id.as_str()), "duplicate {}", def.id);
+            if let Rule::HandEffects { effect, .. } = &def.rule {
+                assert!(Effect::NAMES.contains(&effect.as_str()), "{}", def.id);
+            }
+            if let Rule::SolvePuzzles(list) = &def.rule {
+                assert!(
+                    list.iter().all(|id| puzzles::get(id).is_some()),
+                    "{}",
+                    def.id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn solving_a_lesson_with_three_stars_unlocks_perfectionist_once() {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_score_ping".to_string(),
+            })
+            .unwrap();
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let mut log = AchievementLog::default();
+        let trace = engine.state.trace.iter().cloned().collect::<Vec<_>>();
+        let fresh = log.observe(&trace);
+        assert_eq!(
+            fresh.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(),
+            ["perfectionist"]
+        );
+        assert!(log.solved_puzzles.contains("lesson_score_ping"));
+        assert!(log.observe(&trace).is_empty());
+
+        let restored = AchievementLog::from_json(&log.to_json());
+        assert!(restored.is_unlocked("perfectionist"));
+    }
+
+    #[test]
+    fn hand_calls_count_long_and_short_names_alike() {
+        let mut engine = Engine::with_deck(1, Vec::new(), Limits::default());
+        engine.state.hand = vec![
+            CardInstance::new(10, "tap_score"),
+            CardInstance::new(11, "tap_score"),
+        ];
+        engine.dispatch(Action::PlayHand).unwrap();
+        let trace = engine.state.trace.iter().cloned().collect::<Vec<_>>();
+        let last_call = trace
+            .iter()
+            .rev()
+            .find(|e| matches!(e, TraceEvent::Call { name, .. } if name == "s"))
+            .unwrap();
+
+        let mut log = AchievementLog::default();
+        log.observe(&trace);
+        for name in ["s", "score"] {
+            let rule = Rule::HandCalls {
+                name: name.to_string(),
+                count: 2,
+            };
+            assert!(log.met(&rule, last_call), "{name}");
+        }
+    }
+
+    #[test]
+    fn hand_rules_reset_at_each_action() {
+        let clone = || TraceEvent::EffectApplied {
+            effect: Effect::Clone(1),
+        };
+        let action = || TraceEvent::Action {
+            action: Action::PlayHand,
+        };
+        let mut log = AchievementLog::default();
+        assert!(
+            log.observe(&[action(), clone(), clone(), action(), clone()])
+                .is_empty()
+        );
+        let fresh = log.observe(&[clone(), clone()]);
+        assert_eq!(fresh[0].id, "clone_chain");
+    }
+}
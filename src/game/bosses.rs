@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use crate::kardlang::CostRules;
+use crate::vm::{Limits, VmRules};
+
+/// Default boss interval (see `RunConfig::boss_every`).
+pub const BOSS_EVERY: u32 = 3;
+
+/// Rule change applied for the whole of a boss level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossModifier {
+    /// Digits cost double in `effective_len`.
+    DoubleDigits,
+    /// `max_steps` is halved.
+    HalfSteps,
+    /// `mutate` (and `wild`) are disabled.
+    NoMutate,
+    /// The source is face-down and draws from it yield nothing.
+    FrozenSource,
+    /// Score from `cash` is halved.
+    HalfCash,
+}
+
+impl BossModifier {
+    pub const ALL: [BossModifier; 5] = [
+        BossModifier::DoubleDigits,
+        BossModifier::HalfSteps,
+        BossModifier::NoMutate,
+        BossModifier::FrozenSource,
+        BossModifier::HalfCash,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BossModifier::DoubleDigits => "Inflation",
+            BossModifier::HalfSteps => "Short Circuit",
+            BossModifier::NoMutate => "Stasis",
+            BossModifier::FrozenSource => "Permafrost",
+            BossModifier::HalfCash => "Tax Man",
+        }
+    }
+
+    pub fn doc(self) -> &'static str {
+        match self {
+            BossModifier::DoubleDigits => "Digits cost double in card scripts.",
+            BossModifier::HalfSteps => "max_steps is halved.",
+            BossModifier::NoMutate => "mutate() and wild() are disabled.",
+            BossModifier::FrozenSource => "The source is face-down and frozen: draws find nothing.",
+            BossModifier::HalfCash => "cash() converts only half its amount into score.",
+        }
+    }
+
+    pub fn cost_rules(self, mut rules: CostRules) -> CostRules {
+        if self == BossModifier::DoubleDigits {
+            rules.double_digits = true;
+        }
+        rules
+    }
+
+    pub fn limits(self, mut limits: Limits) -> Limits {
+        if self == BossModifier::HalfSteps {
+            limits.max_steps = (limits.max_steps / 2).max(1);
+        }
+        limits
+    }
+
+    pub fn vm_rules(self) -> VmRules {
+        VmRules {
+            mutate_disabled: self == BossModifier::NoMutate,
+            cash_halved: self == BossModifier::HalfCash,
+        }
+    }
+}
+
+/// Boss modifier for a Classic level when every `every`th level is a boss (0 = never),
+/// cycling through [`BossModifier::ALL`].
+pub fn boss_for_level(level: u32, every: u32) -> Option<BossModifier> {
+    if level == 0 || every == 0 || !level.is_multiple_of(every) {
+        return None;
+    }
+    let round = (level / every - 1) as usize;
+    Some(BossModifier::ALL[round % BossModifier::ALL.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_nth_level_is_a_boss_and_modifiers_cycle() {
+        assert_eq!(boss_for_level(1, BOSS_EVERY), None);
+        assert_eq!(boss_for_level(2, BOSS_EVERY), None);
+        assert_eq!(
+            boss_for_level(3, BOSS_EVERY),
+            Some(BossModifier::DoubleDigits)
+        );
+        assert_eq!(boss_for_level(6, BOSS_EVERY), Some(BossModifier::HalfSteps));
+        let cycle = BOSS_EVERY * BossModifier::ALL.len() as u32;
+        assert_eq!(
+            boss_for_level(3 + cycle, BOSS_EVERY),
+            boss_for_level(3, BOSS_EVERY)
+        );
+        assert_eq!(boss_for_level(3, 0), None);
+    }
+
+    #[test]
+    fn modifiers_only_touch_their_own_rule() {
+        let base = Limits::default();
+        assert_eq!(
+            BossModifier::HalfSteps.limits(base).max_steps,
+            base.max_steps / 2
+        );
+        assert_eq!(BossModifier::HalfCash.limits(base), base);
+        assert!(
+            BossModifier::DoubleDigits
+                .cost_rules(CostRules::default())
+                .double_digits
+        );
+        assert_eq!(
+            BossModifier::NoMutate.cost_rules(CostRules::default()),
+            CostRules::default()
+        );
+        assert!(BossModifier::NoMutate.vm_rules().mutate_disabled);
+        assert_eq!(BossModifier::FrozenSource.vm_rules(), VmRules::default());
+    }
+}
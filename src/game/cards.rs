@@ -1,6 +1,7 @@
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::HashSet;
 
 use crate::kardlang::parse_program;
@@ -40,22 +41,57 @@ pub struct FunctionVisual {
     pub accent: &'static str,
 }
 
+/// Level window and weight for one card in source generation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct GenerationRule {
-    id: &'static str,
-    min_level: u32,
-    max_level: u32,
-    weight: u16,
+pub struct GenerationRule {
+    pub id: &'static str,
+    pub min_level: u32,
+    pub max_level: u32,
+    pub weight: u16,
 }
 
-const ANY_LEVEL: u32 = u32::MAX;
+pub const ANY_LEVEL: u32 = u32::MAX;
+
+/// A catalog and its generation rules.
+pub(crate) type CardSet = (&'static [CardDef], &'static [GenerationRule]);
+
+thread_local! {
+    /// Catalog and rules in effect on this thread; `None` means the built-in set.
+    ///
+    /// Per thread, so parallel tests and engines on other threads keep their own set.
+    static ACTIVE: Cell<Option<CardSet>> = const { Cell::new(None) };
+}
 
 pub fn catalog() -> &'static [CardDef] {
-    &CATALOG
+    active().0
 }
 
 pub fn get(id: &str) -> Option<&'static CardDef> {
-    CATALOG.iter().find(|c| c.id == id)
+    catalog().iter().find(|c| c.id == id)
+}
+
+pub fn generation_rules() -> &'static [GenerationRule] {
+    active().1
+}
+
+/// Cards compiled into the crate, ignoring any installed mod.
+pub fn builtin_catalog() -> &'static [CardDef] {
+    CATALOG
+}
+
+pub fn builtin_generation_rules() -> &'static [GenerationRule] {
+    GENERATION_RULES
+}
+
+fn active() -> CardSet {
+    ACTIVE.get().unwrap_or((CATALOG, GENERATION_RULES))
+}
+
+/// Swap the catalog and rules used by `get`, `catalog` and source generation on this thread.
+///
+/// Only `mods` calls this, after validating the set; `None` restores the built-ins.
+pub(crate) fn set_active(set: Option<CardSet>) {
+    ACTIVE.set(set);
 }
 
 pub fn starter_deck_ids() -> &'static [&'static str] {
@@ -87,6 +123,11 @@ pub fn kind_visual(kind: CardKind) -> KindVisual {
     }
 }
 
+/// Long builtin name for an alias (`c` -> `clone`); unknown names are returned unchanged.
+pub fn canonical_function(name: &str) -> &str {
+    function_visual(name).map_or(name, |f| f.canonical)
+}
+
 pub fn function_visual(name: &str) -> Option<FunctionVisual> {
     let canonical = match name {
         "score" | "s" => "score",
@@ -103,6 +144,10 @@ pub fn function_visual(name: &str) -> Option<FunctionVisual> {
         "cash" | "v" => "cash",
         "hedge" | "h" => "hedge",
         "wild" | "w" => "wild",
+        "skip" | "k" => "skip",
+        "swap" | "z" => "swap",
+        "rev" | "r" => "rev",
+        "pull" | "p" => "pull",
         _ => return None,
     };
 
@@ -205,6 +250,34 @@ pub fn function_visual(name: &str) -> Option<FunctionVisual> {
             label: "Wild",
             accent: "meta",
         },
+        "skip" => FunctionVisual {
+            canonical,
+            icon: "⤼",
+            short: "Skp",
+            label: "Skip",
+            accent: "control",
+        },
+        "swap" => FunctionVisual {
+            canonical,
+            icon: "⇅",
+            short: "Swp",
+            label: "Swap",
+            accent: "control",
+        },
+        "rev" => FunctionVisual {
+            canonical,
+            icon: "⇋",
+            short: "Rev",
+            label: "Reverse",
+            accent: "control",
+        },
+        "pull" => FunctionVisual {
+            canonical,
+            icon: "⤒",
+            short: "Pull",
+            label: "Pull",
+            accent: "control",
+        },
         _ => unreachable!("canonical function mapping must stay exhaustive"),
     })
 }
@@ -217,10 +290,10 @@ pub fn script_function_visuals(script: &str) -> Vec<FunctionVisual> {
     let mut seen = HashSet::new();
     let mut out = Vec::new();
     for call in program.calls {
-        if let Some(fx) = function_visual(&call.name) {
-            if seen.insert(fx.canonical) {
-                out.push(fx);
-            }
+        if let Some(fx) = function_visual(&call.name)
+            && seen.insert(fx.canonical)
+        {
+            out.push(fx);
         }
     }
     out
@@ -256,13 +329,14 @@ pub fn roll_card_id_for_level<R: Rng + ?Sized>(rng: &mut R, level: u32) -> Optio
 }
 
 fn active_rules_for_level(level: u32) -> Vec<&'static GenerationRule> {
-    let mut rules: Vec<&GenerationRule> = GENERATION_RULES
+    let all = generation_rules();
+    let mut rules: Vec<&GenerationRule> = all
         .iter()
         .filter(|r| level >= r.min_level && level <= r.max_level)
         .collect();
 
     if rules.is_empty() {
-        rules = GENERATION_RULES.iter().collect();
+        rules = all.iter().collect();
     }
     rules
 }
@@ -288,7 +362,7 @@ fn pick_weighted_rule_id<R: Rng + ?Sized>(
     rules.last().map(|r| r.id)
 }
 
-static CATALOG: [CardDef; 33] = [
+static CATALOG: &[CardDef] = &[
     CardDef {
         id: "tap_score",
         name: "Tap Score",
@@ -749,11 +823,53 @@ Convert all bankroll into score.
 
 * Script: `v(B)`
 * Effect: `score += bankroll`, then `bankroll = 0`.
+"#,
+    },
+    CardDef {
+        id: "queue_flip",
+        name: "Flip Queue",
+        kind: CardKind::Control,
+        budget: 9,
+        script: "r();s(1)",
+        icon: "⇋",
+        doc: r#"
+Run the rest of the hand backwards.
+
+* Script: `r();s(1)`
+* Effect: reverse the queue, +1 score.
+"#,
+    },
+    CardDef {
+        id: "queue_skip",
+        name: "Skip Ahead",
+        kind: CardKind::Control,
+        budget: 13,
+        script: "k(1);b(11)",
+        icon: "⤼",
+        doc: r#"
+Bin the next card for cash.
+
+* Script: `k(1);b(11)`
+* Effect: the next queued card goes to the pile unplayed, +2 bankroll.
+"#,
+    },
+    CardDef {
+        id: "queue_pull",
+        name: "Pull Last",
+        kind: CardKind::Control,
+        budget: 8,
+        script: "p(U);z()",
+        icon: "⤒",
+        doc: r#"
+Bring the last card forward, then let the old front go first.
+
+* Script: `p(U);z()`
+* Register: `U` = `len_queue`.
 "#,
     },
 ];
 
-static GENERATION_RULES: [GenerationRule; 33] = [
+static GENERATION_RULES: &[GenerationRule] = &[
     GenerationRule {
         id: "tap_score",
         min_level: 1,
@@ -952,12 +1068,48 @@ static GENERATION_RULES: [GenerationRule; 33] = [
         max_level: ANY_LEVEL,
         weight: 3,
     },
+    GenerationRule {
+        id: "queue_flip",
+        min_level: 3,
+        max_level: ANY_LEVEL,
+        weight: 3,
+    },
+    GenerationRule {
+        id: "queue_skip",
+        min_level: 3,
+        max_level: ANY_LEVEL,
+        weight: 3,
+    },
+    GenerationRule {
+        id: "queue_pull",
+        min_level: 4,
+        max_level: ANY_LEVEL,
+        weight: 2,
+    },
 ];
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::kardlang::{effective_len, parse_program};
+    use crate::game::bosses::BossModifier;
+    use crate::kardlang::{CostRules, effective_len, effective_len_with, parse_program};
+
+    #[test]
+    fn every_card_fits_its_budget_under_every_boss() {
+        for boss in BossModifier::ALL {
+            let rules = boss.cost_rules(CostRules::default());
+            for card in catalog() {
+                let cost = effective_len_with(card.script, rules);
+                assert!(
+                    cost <= card.budget,
+                    "card {} over budget under {}: cost={cost}, budget={}",
+                    card.id,
+                    boss.name(),
+                    card.budget
+                );
+            }
+        }
+    }
 
     #[test]
     fn starter_cards_exist_in_catalog() {
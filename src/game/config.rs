@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vm::Limits;
+
+use super::bosses::BOSS_EVERY;
+use super::cards;
+use super::model::{DISCARDS_PER_LEVEL, HANDS_PER_LEVEL};
+
+/// Highest Ascension preset offered in the UI.
+pub const MAX_ASCENSION: u32 = 8;
+
+/// Named difficulty a [`RunConfig`] was built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preset {
+    Casual,
+    Standard,
+    Hard,
+    /// Hard, plus stacking penalties per step (1..=`MAX_ASCENSION`).
+    Ascension(u32),
+}
+
+impl Preset {
+    pub fn label(self) -> String {
+        match self {
+            Preset::Casual => "Casual".to_string(),
+            Preset::Standard => "Standard".to_string(),
+            Preset::Hard => "Hard".to_string(),
+            Preset::Ascension(n) => format!("Ascension {n}"),
+        }
+    }
+
+    /// Every preset, easiest first.
+    pub fn all() -> Vec<Preset> {
+        let mut out = vec![Preset::Casual, Preset::Standard, Preset::Hard];
+        out.extend((1..=MAX_ASCENSION).map(Preset::Ascension));
+        out
+    }
+}
+
+/// Everything that shapes a Classic run: progression curve, economy, limits and starter deck.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunConfig {
+    pub preset: Preset,
+    pub start_bankroll: i64,
+    pub hands_per_level: u32,
+    pub discards_per_level: u32,
+    /// Target for level `L` is `base + linear*(L-1) + curve*(L-1)*(L-2)`.
+    pub target_base: i64,
+    pub target_linear: i64,
+    pub target_curve: i64,
+    /// Shop pack size for level `L` is `booster_base + L/2`, capped at `booster_max`.
+    pub booster_base: usize,
+    pub booster_max: usize,
+    /// Source size for level `L` is `source_base + source_per_level*(L-1)`, capped at `source_max`.
+    pub source_base: usize,
+    pub source_per_level: usize,
+    pub source_max: usize,
+    /// Every Nth level is a boss level; 0 disables bosses.
+    pub boss_every: u32,
+    pub limits: Limits,
+    pub starter_deck: Vec<String>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl RunConfig {
+    pub fn from_preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Casual => Self::casual(),
+            Preset::Standard => Self::standard(),
+            Preset::Hard => Self::hard(),
+            Preset::Ascension(n) => Self::ascension(n),
+        }
+    }
+
+    pub fn standard() -> Self {
+        Self {
+            preset: Preset::Standard,
+            start_bankroll: 10,
+            hands_per_level: HANDS_PER_LEVEL,
+            discards_per_level: DISCARDS_PER_LEVEL,
+            target_base: 10,
+            target_linear: 12,
+            target_curve: 2,
+            booster_base: 3,
+            booster_max: 8,
+            source_base: 56,
+            source_per_level: 8,
+            source_max: 128,
+            boss_every: BOSS_EVERY,
+            limits: Limits::default(),
+            starter_deck: cards::starter_deck_ids()
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn casual() -> Self {
+        let mut config = Self::standard();
+        config.preset = Preset::Casual;
+        config.start_bankroll = 15;
+        config.hands_per_level = HANDS_PER_LEVEL + 1;
+        config.discards_per_level = DISCARDS_PER_LEVEL + 1;
+        config.target_base = 8;
+        config.target_linear = 9;
+        config.target_curve = 1;
+        config.boss_every = 4;
+        config.limits.max_steps = 300;
+        config.starter_deck.push("tap_score".to_string());
+        config
+    }
+
+    pub fn hard() -> Self {
+        let mut config = Self::standard();
+        config.preset = Preset::Hard;
+        config.start_bankroll = 8;
+        config.hands_per_level = HANDS_PER_LEVEL - 1;
+        config.discards_per_level = DISCARDS_PER_LEVEL - 1;
+        config.target_base = 12;
+        config.target_linear = 15;
+        config.target_curve = 3;
+        config.booster_max = 6;
+        config.limits.max_steps = 150;
+        config
+    }
+
+    /// Hard with one more penalty per step; steps stack.
+    pub fn ascension(level: u32) -> Self {
+        let n = level.clamp(1, MAX_ASCENSION);
+        let mut config = Self::hard();
+        config.preset = Preset::Ascension(n);
+        config.target_linear += 2 * n as i64;
+        if n >= 2 {
+            config.start_bankroll -= 2;
+        }
+        if n >= 3 {
+            config.target_curve += 1;
+        }
+        if n >= 4 {
+            config.boss_every = 2;
+        }
+        if n >= 5 {
+            config.discards_per_level -= 1;
+        }
+        if n >= 6 {
+            config.starter_deck.retain(|id| id != "tap_bank");
+        }
+        if n >= 7 {
+            config.limits.max_steps = 100;
+        }
+        if n >= 8 {
+            config.hands_per_level -= 1;
+        }
+        config
+    }
+
+    pub fn target_for_level(&self, level: u32) -> i64 {
+        let steps = level.max(1) as i64 - 1;
+        let curve = steps.saturating_mul((steps - 1).max(0));
+        self.target_base
+            .saturating_add(steps.saturating_mul(self.target_linear))
+            .saturating_add(curve.saturating_mul(self.target_curve))
+    }
+
+    pub fn booster_count_for_level(&self, level: u32) -> usize {
+        (self.booster_base + level.max(1) as usize / 2).min(self.booster_max)
+    }
+
+    pub fn source_count_for_level(&self, level: u32) -> usize {
+        let steps = level.max(1) as usize - 1;
+        (self.source_base + steps * self.source_per_level).min(self.source_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_matches_the_original_curves() {
+        let config = RunConfig::standard();
+        assert_eq!(config.target_for_level(1), 10);
+        assert_eq!(config.target_for_level(2), 22);
+        assert_eq!(config.target_for_level(3), 38);
+        assert_eq!(config.booster_count_for_level(1), 3);
+        assert_eq!(config.booster_count_for_level(20), 8);
+        for level in [1, 4, 12] {
+            assert_eq!(
+                config.source_count_for_level(level),
+                cards::source_count_for_level(level)
+            );
+        }
+    }
+
+    #[test]
+    fn presets_get_strictly_harder() {
+        let presets = Preset::all();
+        for pair in presets.windows(2) {
+            let easier = RunConfig::from_preset(pair[0]);
+            let harder = RunConfig::from_preset(pair[1]);
+            assert!(
+                harder.target_for_level(5) > easier.target_for_level(5),
+                "{} should have a higher level-5 target than {}",
+                pair[1].label(),
+                pair[0].label()
+            );
+            assert!(harder.start_bankroll <= easier.start_bankroll);
+            assert!(harder.hands_per_level >= 1 && harder.discards_per_level >= 1);
+        }
+    }
+
+    #[test]
+    fn vm_limits_come_from_the_run_config() {
+        let mut engine = crate::game::Engine::with_config(1, RunConfig::hard());
+        assert_eq!(engine.state.vm_limits().max_steps, 150);
+        engine.state.config.limits.max_steps = 40;
+        assert_eq!(engine.state.vm_limits().max_steps, 40);
+    }
+
+    #[test]
+    fn starter_decks_only_use_catalog_cards() {
+        for preset in Preset::all() {
+            let config = RunConfig::from_preset(preset);
+            assert!(!config.starter_deck.is_empty());
+            for id in &config.starter_deck {
+                assert!(
+                    cards::get(id).is_some(),
+                    "{}: unknown card {id}",
+                    preset.label()
+                );
+            }
+        }
+    }
+}
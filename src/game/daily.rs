@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GameState, RunMode, rng};
+
+/// Mixed into every daily seed so dates do not map onto seeds players already use.
+pub const DAILY_SALT: u64 = 0x4B41_5244_2D44_4159;
+
+/// Most records kept in the local history; the oldest are dropped first.
+pub const MAX_HISTORY: usize = 100;
+
+/// Seed for the daily challenge on `date` (`YYYY-MM-DD`).
+pub fn daily_seed(date: &str) -> u64 {
+    // A splitmix finaliser over the stable hash so adjacent dates land far apart.
+    let mut z = rng::stable_hash(date) ^ DAILY_SALT;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// `YYYY-MM-DD` (UTC) for a count of days since 1970-01-01.
+pub fn date_from_days(days: i64) -> String {
+    // Civil-from-days (proleptic Gregorian calendar).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Result of one finished Classic run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub seed: u64,
+    /// Challenge date when the run was a daily.
+    pub daily: Option<String>,
+    pub preset: String,
+    pub level: u32,
+    pub score: i64,
+    pub turns: u32,
+}
+
+impl RunRecord {
+    /// Record for a Classic run; `None` for puzzles.
+    pub fn from_state(state: &GameState) -> Option<Self> {
+        (state.mode == RunMode::Classic).then(|| Self {
+            seed: state.seed,
+            daily: state.daily.clone(),
+            preset: state.config.preset.label(),
+            level: state.level,
+            score: state.score,
+            turns: state.turn,
+        })
+    }
+
+    /// Ranking key: deeper level, then higher score, then fewer turns.
+    fn rank(&self) -> (std::cmp::Reverse<u32>, std::cmp::Reverse<i64>, u32) {
+        (
+            std::cmp::Reverse(self.level),
+            std::cmp::Reverse(self.score),
+            self.turns,
+        )
+    }
+}
+
+/// Locally stored run results (most recent last).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunHistory {
+    pub records: Vec<RunRecord>,
+}
+
+impl RunHistory {
+    pub fn push(&mut self, record: RunRecord) {
+        self.records.push(record);
+        if self.records.len() > MAX_HISTORY {
+            let excess = self.records.len() - MAX_HISTORY;
+            self.records.drain(..excess);
+        }
+    }
+
+    /// Personal bests, best first.
+    pub fn best(&self, limit: usize) -> Vec<&RunRecord> {
+        let mut out = self.records.iter().collect::<Vec<_>>();
+        out.sort_by_key(|r| r.rank());
+        out.truncate(limit);
+        out
+    }
+
+    /// Best result for the daily challenge on `date`.
+    pub fn best_daily(&self, date: &str) -> Option<&RunRecord> {
+        self.records
+            .iter()
+            .filter(|r| r.daily.as_deref() == Some(date))
+            .min_by_key(|r| r.rank())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parse stored history; unreadable data yields an empty history.
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: u32, score: i64, turns: u32) -> RunRecord {
+        RunRecord {
+            seed: 1,
+            daily: None,
+            preset: "Standard".to_string(),
+            level,
+            score,
+            turns,
+        }
+    }
+
+    #[test]
+    fn daily_seed_is_stable_per_date_and_differs_across_dates() {
+        assert_eq!(daily_seed("2026-10-18"), daily_seed("2026-10-18"));
+        assert_ne!(daily_seed("2026-10-18"), daily_seed("2026-10-19"));
+        assert_ne!(daily_seed("2026-10-18"), 0);
+    }
+
+    #[test]
+    fn dates_follow_the_gregorian_calendar() {
+        assert_eq!(date_from_days(0), "1970-01-01");
+        assert_eq!(date_from_days(59), "1970-03-01");
+        assert_eq!(date_from_days(11_016), "2000-02-29");
+        assert_eq!(date_from_days(20_744), "2026-10-18");
+    }
+
+    #[test]
+    fn best_ranks_by_level_then_score_then_turns() {
+        let mut history = RunHistory::default();
+        history.push(record(2, 50, 9));
+        history.push(record(3, 10, 12));
+        history.push(record(2, 50, 7));
+
+        let best = history.best(2);
+        assert_eq!((best[0].level, best[0].score), (3, 10));
+        assert_eq!(best[1].turns, 7);
+    }
+
+    #[test]
+    fn history_round_trips_and_is_capped() {
+        let mut history = RunHistory::default();
+        for i in 0..(MAX_HISTORY as i64 + 5) {
+            history.push(record(1, i, 1));
+        }
+        assert_eq!(history.records.len(), MAX_HISTORY);
+        assert_eq!(history.records[0].score, 5);
+        assert_eq!(RunHistory::from_json(&history.to_json()), history);
+        assert_eq!(RunHistory::from_json("not json"), RunHistory::default());
+    }
+}
@@ -0,0 +1,148 @@
+use super::packs::{PACK_FORMAT, PackError, PuzzlePack};
+use super::puzzles::{Par, PuzzleData};
+use super::solver::{self, SolveMode, SolveReport, Solver, SolverLimits};
+use super::{CardInstance, GameError, GameState};
+
+/// Draft puzzle from a live game: its source, collection, hand, bankroll, score and level.
+///
+/// The target starts one point above the current score with a single play, so the
+/// designer only has to adjust what matters. Instance upgrades are not carried over.
+pub fn snapshot(state: &GameState) -> PuzzleData {
+    let ids = |cards: &[CardInstance]| cards.iter().map(|c| c.def_id.clone()).collect();
+    PuzzleData {
+        id: format!("draft_{:x}_{}", state.seed, state.turn),
+        name: "Untitled puzzle".to_string(),
+        blurb: String::new(),
+        hint: String::new(),
+        theme: "terminal".to_string(),
+        start_bankroll: state.bankroll,
+        start_score: state.score,
+        start_level: state.level.max(1),
+        target_score: state.score.saturating_add(1).max(1),
+        goal_bankroll: None,
+        play_limit: Some(1),
+        source_ids: ids(&state.deck),
+        collection_ids: ids(&state.collection),
+        hand_ids: ids(&state.hand),
+        solution: Vec::new(),
+        par: Par::NONE,
+        goals: Vec::new(),
+    }
+}
+
+/// Solver limits for verifying drafts, small enough to run between UI frames.
+pub const VERIFY_LIMITS: SolverLimits = SolverLimits {
+    max_plays: 3,
+    max_hand: 6,
+    max_nodes: 20_000,
+};
+
+/// Run the solver on a draft and store its first minimal line as the draft's solution.
+///
+/// The play and card pars are reset to match that line.
+pub fn verify(draft: &mut PuzzleData) -> Result<SolveReport, GameError> {
+    let engine = solver::puzzle_engine_for(draft)?;
+    let report = Solver::new(SolveMode::Minimal)
+        .with_limits(VERIFY_LIMITS)
+        .solve(&engine);
+    draft.solution = report
+        .solutions
+        .first()
+        .map(|s| s.plays.clone())
+        .unwrap_or_default();
+    draft.par = Par {
+        surplus: draft.par.surplus,
+        ..Par::from_solution(&draft.solution)
+    };
+    Ok(report)
+}
+
+/// One-puzzle pack for a draft, ready to save as a file and load with the pack loader.
+///
+/// Fails with the error the pack loader would report, so a bad draft is never exported.
+pub fn export_pack(draft: &PuzzleData, author: &str) -> Result<PuzzlePack, PackError> {
+    let mut required_cards: Vec<String> = Vec::new();
+    for id in draft.card_ids() {
+        if !required_cards.contains(id) {
+            required_cards.push(id.clone());
+        }
+    }
+    let pack = PuzzlePack {
+        format: PACK_FORMAT,
+        id: draft.id.clone(),
+        title: draft.name.clone(),
+        author: author.to_string(),
+        description: draft.blurb.clone(),
+        required_cards,
+        puzzles: vec![draft.clone()],
+    };
+    pack.validate()?;
+    Ok(pack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::mods::ModFormat;
+    use crate::game::{Action, CardInstance, Engine, share};
+    use crate::vm::Limits;
+
+    fn live_engine() -> Engine {
+        let mut engine = Engine::with_deck(9, Vec::new(), Limits::default());
+        engine.state.bankroll = 6;
+        engine.state.collection = vec![CardInstance::new(101, "tap_bank")];
+        engine.state.hand = vec![
+            CardInstance::new(102, "double_bank"),
+            CardInstance::new(103, "all_in_score"),
+        ];
+        engine
+    }
+
+    #[test]
+    fn snapshot_captures_zones_and_registers() {
+        let draft = snapshot(&live_engine().state);
+        assert_eq!(draft.start_bankroll, 6);
+        assert_eq!(draft.collection_ids, vec!["tap_bank".to_string()]);
+        assert_eq!(draft.hand_ids.len(), 2);
+        assert_eq!(draft.target_score, 1);
+    }
+
+    #[test]
+    fn verified_drafts_carry_a_line_that_solves_them() {
+        let mut draft = snapshot(&live_engine().state);
+        draft.target_score = 12;
+        let report = verify(&mut draft).unwrap();
+        assert!(report.is_solvable());
+        assert!(!draft.solution.is_empty());
+
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::LoadPuzzle {
+                puzzle: Box::new(share::decode(&share::encode(&draft).unwrap()).unwrap()),
+            })
+            .unwrap();
+        assert_eq!(engine.state.puzzle_source.as_deref(), Some(&draft));
+
+        draft.target_score = 1_000;
+        assert!(!verify(&mut draft).unwrap().is_solvable());
+        assert!(draft.solution.is_empty());
+    }
+
+    #[test]
+    fn exported_packs_load_back() {
+        let mut draft = snapshot(&live_engine().state);
+        draft.target_score = 12;
+        verify(&mut draft).unwrap();
+
+        let json = serde_json::to_string_pretty(&export_pack(&draft, "me").unwrap()).unwrap();
+        let pack = PuzzlePack::parse(&json, ModFormat::Json).unwrap();
+        assert_eq!(pack.puzzles, vec![draft.clone()]);
+        assert_eq!(pack.required_cards.len(), 3);
+
+        draft.hand_ids.clear();
+        assert!(matches!(
+            export_pack(&draft, "me"),
+            Err(PackError::InvalidPuzzle { .. })
+        ));
+    }
+}
@@ -1,27 +1,89 @@
-use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
-use rand_chacha::ChaCha8Rng;
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use std::collections::VecDeque;
 
-use crate::kardlang::{effective_len, parse_program};
-use crate::vm::{Effect, Limits, Machine, VmContext, VmError};
-
-use crate::game::{CardInstance, GameState, Phase, RunMode, TraceEvent, cards, puzzles};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::kardlang::{CostRules, effective_len_with, parse_program};
+use crate::vm::{self, Effect, Limits, Machine, VmContext, VmError};
+
+use crate::game::bosses;
+
+use crate::game::cards::CardKind;
+use crate::game::config::RunConfig;
+use crate::game::daily;
+use crate::game::goals::{self, Step};
+use crate::game::observer::{EngineObserver, ObserverId, Observers};
+use crate::game::progress::{self, PuzzleResult};
+use crate::game::puzzles::PuzzleData;
+use crate::game::relics::{self, RelicHook};
+use crate::game::rng::{self, RngStreams};
+use crate::game::shop::{self, ShopState};
+use crate::game::trace::Snapshot;
+use crate::game::upgrades::{self, CardUpgrade, Edition, FOIL_SCORE};
+use crate::game::{CardInstance, GameState, Lifetime, Phase, RunMode, TraceEvent, cards, puzzles};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
-    NewRun { seed: u64 },
-    StartPuzzle { id: String },
-    DrawToCollection { count: usize },
-    MoveCollectionToHand { index: usize },
-    MoveHandToCollection { index: usize },
-    ReorderCollection { from: usize, to: usize },
-    ReorderHand { from: usize, to: usize },
-    SwapCollection { a: usize, b: usize },
-    SwapHand { a: usize, b: usize },
+    /// Start a Classic run; `config: None` keeps the current run's configuration.
+    NewRun {
+        seed: u64,
+        config: Option<Box<RunConfig>>,
+    },
+    /// Start the daily challenge for `date` (`YYYY-MM-DD`) on the Standard preset.
+    NewDailyRun {
+        date: String,
+    },
+    StartPuzzle {
+        id: String,
+    },
+    LoadPuzzle {
+        puzzle: Box<PuzzleData>,
+    },
+    DrawToCollection {
+        count: usize,
+    },
+    MoveCollectionToHand {
+        index: usize,
+    },
+    MoveHandToCollection {
+        index: usize,
+    },
+    ReorderCollection {
+        from: usize,
+        to: usize,
+    },
+    ReorderHand {
+        from: usize,
+        to: usize,
+    },
+    SwapCollection {
+        a: usize,
+        b: usize,
+    },
+    SwapHand {
+        a: usize,
+        b: usize,
+    },
     PlayHand,
+    Discard {
+        indices: Vec<usize>,
+    },
+    BuyCard {
+        index: usize,
+    },
+    RerollShop,
+    BuyPack,
+    RemoveCard {
+        index: usize,
+    },
+    BuyRelic,
+    /// Raise a Code Bank card one upgrade level (see `upgrades::upgrade_price`).
+    UpgradeCard {
+        index: usize,
+    },
+    LeaveShop,
     ClearTrace,
 }
 
@@ -39,6 +101,9 @@ pub enum GameError {
     #[error("unknown puzzle id: {0}")]
     UnknownPuzzle(String),
 
+    #[error("invalid puzzle goal: {0}")]
+    InvalidGoal(String),
+
     #[error("card script cost {cost} exceeds budget {budget}: {name}")]
     CardOverBudget {
         name: String,
@@ -48,64 +113,164 @@ pub enum GameError {
 
     #[error("cannot draw a hand: deck and pile are empty")]
     NoCards,
+
+    #[error("no discards left this level")]
+    NoDiscardsLeft,
+
+    #[error("cannot afford ${cost} with bankroll ${bankroll}")]
+    CannotAfford { cost: i64, bankroll: i64 },
+
+    #[error("{0} is Eternal and cannot be removed")]
+    EternalCard(String),
+
+    #[error("cannot {action} during {phase:?}")]
+    WrongPhase { action: &'static str, phase: Phase },
+
+    #[error("index {index} is out of range for the {zone} ({len} cards)")]
+    IndexOutOfRange {
+        zone: &'static str,
+        index: usize,
+        len: usize,
+    },
+
+    #[error("nothing to do: {0}")]
+    Unavailable(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Engine {
     pub state: GameState,
-    rng: ChaCha8Rng,
     next_id: u64,
+    observers: Observers,
+    /// Reject actions that `check_action` refuses instead of ignoring them.
+    strict: bool,
 }
 
 impl Engine {
     pub fn new(seed: u64) -> Self {
-        // Draw pile ("source") is generated from weighted level-aware rules.
-        let mut draw_pile: Vec<CardInstance> = Vec::new();
-        let mut next_id: u64 = 1;
-        for def_id in cards::generate_source_ids(seed, 1) {
-            draw_pile.push(CardInstance::new(next_id, def_id));
-            next_id += 1;
-        }
+        Self::with_config(seed, RunConfig::standard())
+    }
 
-        let mut engine = Self::with_deck(seed, draw_pile, Limits::default());
-        engine.state.target_score = target_for_level(engine.state.level);
+    pub fn with_config(seed: u64, config: RunConfig) -> Self {
+        // Draw pile ("source") is generated from weighted level-aware rules.
+        let draw_pile: Vec<CardInstance> =
+            cards::generate_source_ids_with_count(seed, 1, config.source_count_for_level(1))
+                .into_iter()
+                .enumerate()
+                .map(|(i, def_id)| CardInstance::new((i + 1) as u64, def_id))
+                .collect();
+
+        let mut engine = Self::with_deck(seed, draw_pile, config.limits);
+        engine.state.bankroll = config.start_bankroll;
+        engine.state.hands_left = config.hands_per_level;
+        engine.state.discards_left = config.discards_per_level;
+        engine.state.target_score = config.target_for_level(engine.state.level);
 
         // Player starts with a small starter deck.
-        engine.state.collection = cards::starter_deck_ids()
+        engine.state.collection = config
+            .starter_deck
             .iter()
-            .map(|id| engine.new_card(*id))
+            .map(|id| engine.new_card(id))
             .collect();
+        engine.state.config = config;
 
         engine
     }
 
     pub fn with_deck(seed: u64, mut deck: Vec<CardInstance>, limits: Limits) -> Self {
-        let mut rng = ChaCha8Rng::seed_from_u64(seed);
-        deck.shuffle(&mut rng);
+        let mut rng = RngStreams::new(seed);
+        deck.shuffle(&mut rng.shuffle);
         let next_id = deck
             .iter()
             .map(|c| c.id)
             .max()
             .unwrap_or(0)
             .saturating_add(1);
+        let mut state = GameState::new(deck, limits);
+        state.seed = seed;
+        state.rng = rng;
         Self {
-            state: GameState::new(deck, limits),
-            rng,
+            state,
             next_id,
+            observers: Observers::default(),
+            strict: false,
         }
     }
 
+    /// In strict mode, `dispatch` returns an error for any action `legal_actions` would not
+    /// allow; otherwise invalid indices and out-of-phase actions are silently ignored.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Registers an observer that is called for every event from now on.
+    ///
+    /// Observers survive new runs and puzzle loads but are not copied by `clone`.
+    pub fn subscribe(&mut self, observer: impl EngineObserver + 'static) -> ObserverId {
+        self.observers.subscribe(Box::new(observer))
+    }
+
+    pub fn unsubscribe(&mut self, id: ObserverId) -> Option<Box<dyn EngineObserver>> {
+        self.observers.unsubscribe(id)
+    }
+
+    pub fn observer_count(&self) -> usize {
+        self.observers.len()
+    }
+
+    /// Records `event` in the trace and hands it to the observers.
+    fn emit(&mut self, event: TraceEvent) {
+        self.observers.event(&event);
+        self.state.trace.push(event);
+    }
+
+    /// Swaps in a freshly built engine, carrying the observers and strict mode across.
+    fn replace_with(&mut self, mut next: Engine) {
+        next.observers = std::mem::take(&mut self.observers);
+        next.strict = self.strict;
+        *self = next;
+    }
+
     pub fn dispatch(&mut self, action: Action) -> Result<(), GameError> {
-        self.state.trace.push(TraceEvent::Action {
-            action: format!("{action:?}"),
+        if self.strict {
+            self.check_action(&action)?;
+        }
+        let turn = match action {
+            Action::PlayHand => self.state.turn + 1,
+            _ => self.state.turn,
+        };
+        self.observers.event(&TraceEvent::Action {
+            action: action.clone(),
         });
+        self.state
+            .trace
+            .push_action(action.clone(), self.state.level, turn);
+        let policy = self.state.trace.policy();
 
-        match action {
-            Action::NewRun { seed } => {
-                *self = Self::new(seed);
+        let result = match action {
+            Action::NewRun { seed, config } => {
+                let config = config.map_or_else(|| self.state.config.clone(), |c| *c);
+                self.replace_with(Self::with_config(seed, config));
+                Ok(())
+            }
+            Action::NewDailyRun { date } => {
+                self.replace_with(Self::with_config(
+                    daily::daily_seed(&date),
+                    RunConfig::standard(),
+                ));
+                self.emit(TraceEvent::Info(format!(
+                    "Daily challenge {date} (seed {})",
+                    self.state.seed
+                )));
+                self.state.daily = Some(date);
                 Ok(())
             }
             Action::StartPuzzle { id } => self.start_puzzle(&id),
+            Action::LoadPuzzle { puzzle } => self.load_puzzle(&puzzle),
             Action::DrawToCollection { count } => self.draw_to_collection(count),
             Action::MoveCollectionToHand { index } => {
                 if let Some(card) = take_at(&mut self.state.collection, index) {
@@ -136,11 +301,25 @@ impl Engine {
                 Ok(())
             }
             Action::PlayHand => self.play_hand(),
+            Action::Discard { indices } => self.discard(indices),
+            Action::BuyCard { index } => self.buy_card(index),
+            Action::RerollShop => self.reroll_shop(),
+            Action::BuyPack => self.buy_pack(),
+            Action::RemoveCard { index } => self.remove_card(index),
+            Action::BuyRelic => self.buy_relic(),
+            Action::UpgradeCard { index } => self.upgrade_card(index),
+            Action::LeaveShop => {
+                self.leave_shop();
+                Ok(())
+            }
             Action::ClearTrace => {
                 self.state.trace.clear();
                 Ok(())
             }
-        }
+        };
+        // New runs and puzzles replace the state; keep the player's retention choice.
+        self.state.trace.set_policy(policy);
+        result
     }
 
     fn new_card(&mut self, def_id: &str) -> CardInstance {
@@ -168,6 +347,9 @@ impl Engine {
     }
 
     fn draw_one(&mut self, allow_recycle: bool) -> Result<Option<CardInstance>, GameError> {
+        if self.state.source_frozen() {
+            return Ok(None);
+        }
         if self.state.deck.is_empty() {
             if !allow_recycle {
                 return Ok(None);
@@ -176,36 +358,44 @@ impl Engine {
                 return Err(GameError::NoCards);
             }
 
-            self.state.deck.append(&mut self.state.pile);
-            self.state.deck.shuffle(&mut self.rng);
+            // Ephemeral copies already ran; they never return to the source.
+            let pile = std::mem::take(&mut self.state.pile);
+            self.state
+                .deck
+                .extend(pile.into_iter().filter(|c| !c.is_ephemeral()));
+            self.state.deck.shuffle(&mut self.state.rng.shuffle);
         }
 
-        Ok(self.state.deck.pop())
+        let card = self.state.deck.pop();
+        if let Some(card) = &card {
+            self.observers.card_drawn(card);
+        }
+        Ok(card)
     }
 
     fn start_puzzle(&mut self, id: &str) -> Result<(), GameError> {
         let puzzle = puzzles::get(id).ok_or_else(|| GameError::UnknownPuzzle(id.to_string()))?;
+        self.load_puzzle(&PuzzleData::from(puzzle))
+    }
 
-        for card_id in puzzle
-            .source_ids
-            .iter()
-            .chain(puzzle.collection_ids.iter())
-            .chain(puzzle.hand_ids.iter())
-        {
+    fn load_puzzle(&mut self, puzzle: &PuzzleData) -> Result<(), GameError> {
+        for card_id in puzzle.card_ids() {
             if cards::get(card_id).is_none() {
-                return Err(GameError::UnknownCardDef((*card_id).to_string()));
+                return Err(GameError::UnknownCardDef(card_id.clone()));
             }
         }
+        goals::check(&puzzle.goals).map_err(GameError::InvalidGoal)?;
 
-        let seed = stable_seed_from_id(puzzle.id);
+        let seed = rng::stable_hash(&puzzle.id);
         let source = puzzle
             .source_ids
             .iter()
             .enumerate()
-            .map(|(i, id)| CardInstance::new((i + 1) as u64, *id))
+            .map(|(i, id)| CardInstance::new((i + 1) as u64, id.as_str()))
             .collect::<Vec<_>>();
 
-        let mut next = Self::with_deck(seed, source, self.state.limits);
+        // Puzzles are fixed scenarios: they ignore the run's difficulty settings.
+        let mut next = Self::with_deck(seed, source, Limits::default());
         next.state.mode = RunMode::Puzzle;
         next.state.level = puzzle.start_level.max(1);
         next.state.bankroll = puzzle.start_bankroll;
@@ -214,49 +404,36 @@ impl Engine {
         next.state.target_score = puzzle.target_score.max(1);
         next.state.phase = Phase::InLevel;
         next.state.turn = 0;
-        next.state.puzzle_id = Some(puzzle.id.to_string());
-        next.state.puzzle_title = Some(puzzle.name.to_string());
-        next.state.puzzle_blurb = Some(puzzle.blurb.to_string());
-        next.state.puzzle_hint = Some(puzzle.hint.to_string());
-        next.state.puzzle_theme = Some(puzzle.theme.to_string());
+        next.state.puzzle_id = Some(puzzle.id.clone());
+        next.state.puzzle_title = Some(puzzle.name.clone());
+        next.state.puzzle_blurb = Some(puzzle.blurb.clone());
+        next.state.puzzle_hint = Some(puzzle.hint.clone());
+        next.state.puzzle_theme = Some(puzzle.theme.clone());
         next.state.puzzle_play_limit = puzzle.play_limit;
         next.state.puzzle_bankroll_goal = puzzle.goal_bankroll;
         next.state.puzzle_solved = false;
         next.state.puzzle_failed = false;
         next.state.puzzle_message = Some(format!("{}: {}", puzzle.name, puzzle.blurb));
+        next.state.puzzle_source = Some(Box::new(puzzle.clone()));
+        next.state.puzzle_goals = puzzle.goals.clone();
 
         next.state.collection = puzzle
             .collection_ids
             .iter()
-            .map(|id| next.new_card(*id))
-            .collect();
-        next.state.hand = puzzle
-            .hand_ids
-            .iter()
-            .map(|id| next.new_card(*id))
+            .map(|id| next.new_card(id))
             .collect();
-
-        next.state
-            .trace
-            .push(TraceEvent::Info(format!("Puzzle loaded: {}", puzzle.name)));
-        next.state
-            .trace
-            .push(TraceEvent::Info(format!("Hint: {}", puzzle.hint)));
-        next.state.trace.push(TraceEvent::Info(format!(
-            "Goal: score >= {}{}",
-            next.state.target_score,
-            next.state
-                .puzzle_bankroll_goal
-                .map(|g| format!(", bankroll >= {g}"))
-                .unwrap_or_default()
+        next.state.hand = puzzle.hand_ids.iter().map(|id| next.new_card(id)).collect();
+        self.replace_with(next);
+
+        self.emit(TraceEvent::Info(format!("Puzzle loaded: {}", puzzle.name)));
+        self.emit(TraceEvent::Info(format!("Hint: {}", puzzle.hint)));
+        self.emit(TraceEvent::Info(format!(
+            "Goal: {}",
+            goals::describe(&goals::active(&self.state))
         )));
-        if let Some(limit) = next.state.puzzle_play_limit {
-            next.state
-                .trace
-                .push(TraceEvent::Info(format!("Play limit: {limit}")));
+        if let Some(limit) = self.state.puzzle_play_limit {
+            self.emit(TraceEvent::Info(format!("Play limit: {limit}")));
         }
-
-        *self = next;
         Ok(())
     }
 
@@ -267,68 +444,163 @@ impl Engine {
 
         self.state.turn = self.state.turn.saturating_add(1);
 
-        let mut vm = Machine::new(self.state.limits);
+        let mut vm = Machine::with_rules(self.state.vm_limits(), self.state.vm_rules());
+        let cost_rules = self.cost_rules();
+        let mut fired_this_hand: Vec<&'static str> = Vec::new();
 
         let mut queue: VecDeque<CardInstance> = std::mem::take(&mut self.state.hand).into();
         let mut exec_index: usize = 0;
+        if self.state.mode == RunMode::Puzzle {
+            self.state.puzzle_cards_used = self
+                .state
+                .puzzle_cards_used
+                .saturating_add(queue.len() as u32);
+        }
+        // First broken step constraint of a puzzle goal; stops the hand.
+        let mut violation = self.puzzle_violation(Step::HandStart);
+        if violation.is_some() {
+            self.state.pile.extend(queue.drain(..));
+        }
 
         while let Some(card) = queue.pop_front() {
             let def = card
-                .def()
+                .effective_def()
                 .ok_or_else(|| GameError::UnknownCardDef(card.def_id.clone()))?;
 
-            let before = Snapshot::capture(&self.state);
+            let before = Snapshot::capture(&self.state, queue.iter().map(|c| c.id));
 
-            let cost = effective_len(def.script);
-            self.state.trace.push(TraceEvent::CardStart {
+            let cost = effective_len_with(&def.script, cost_rules);
+            self.emit(TraceEvent::CardStart {
                 index: exec_index,
-                name: def.name.to_string(),
-                script: def.script.to_string(),
+                card_id: card.id,
+                def_id: card.def_id.clone(),
+                name: def.name.clone(),
+                script: def.script.clone(),
                 budget: def.budget,
                 cost,
+                before: before.clone(),
             });
 
-            if cost > def.budget {
-                return Err(GameError::CardOverBudget {
-                    name: def.name.to_string(),
-                    cost,
-                    budget: def.budget,
-                });
-            }
-
-            let program = parse_program(def.script)?;
+            let program = parse_program(&def.script)?;
             let mut post_queue: Vec<CardInstance> = Vec::new();
 
-            for call in &program.calls {
-                let args = call.args.iter().map(expr_to_string).collect::<Vec<_>>();
+            'run: {
+                // A boss cost rule can push a legal card over budget: it fizzles like a
+                // disabled builtin and still goes to the pile.
+                if cost > def.budget && !def.ignores_budget() {
+                    let error = GameError::CardOverBudget {
+                        name: def.name.clone(),
+                        cost,
+                        budget: def.budget,
+                    };
+                    self.emit(TraceEvent::Error(error.to_string()));
+                    break 'run;
+                }
 
-                self.state.trace.push(TraceEvent::Call {
-                    name: call.name.clone(),
-                    args,
-                });
+                if let Some(reason) = self.fire_relics(
+                    RelicHook::CardStart,
+                    Some(def.kind),
+                    None,
+                    &mut fired_this_hand,
+                    &mut post_queue,
+                ) {
+                    violation = Some(reason);
+                    break 'run;
+                }
+
+                for call in &program.calls {
+                    let args = call.args.iter().map(expr_to_string).collect::<Vec<_>>();
+                    let queued = queue.len() + post_queue.len();
+                    let values = vm::eval_args(
+                        call,
+                        &GameCtx {
+                            state: &self.state,
+                            queued,
+                        },
+                    );
+
+                    self.emit(TraceEvent::Call {
+                        step: vm.steps(),
+                        card_id: card.id,
+                        name: call.name.clone(),
+                        span: call.span,
+                        args,
+                        values,
+                    });
+                    if let Some(reason) = self.puzzle_violation(Step::Call(&call.name)) {
+                        violation = Some(reason);
+                        break 'run;
+                    }
+
+                    let ctx = GameCtx {
+                        state: &self.state,
+                        queued,
+                    };
+                    let effects = match vm.eval_call(call, &ctx) {
+                        Ok(effects) => effects,
+                        // Boss-disabled builtins fizzle instead of aborting the hand.
+                        Err(e @ VmError::Disabled(_)) => {
+                            self.emit(TraceEvent::Error(e.to_string()));
+                            continue;
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    for effect in effects {
+                        if effect.is_queue_op() {
+                            self.apply_queue_effect(&effect, &mut queue, &mut post_queue);
+                        } else {
+                            self.apply_effect_for_hand(&effect, &mut post_queue);
+                        }
+                        self.emit(TraceEvent::EffectApplied {
+                            effect: effect.clone(),
+                        });
+                        let hooks: &[RelicHook] = match effect {
+                            Effect::Draw(_) => &[RelicHook::Effect, RelicHook::Draw],
+                            _ => &[RelicHook::Effect],
+                        };
+                        for &hook in hooks {
+                            if let Some(reason) = self.fire_relics(
+                                hook,
+                                None,
+                                Some(&effect),
+                                &mut fired_this_hand,
+                                &mut post_queue,
+                            ) {
+                                violation = Some(reason);
+                                break 'run;
+                            }
+                        }
+                        if let Some(reason) = self.puzzle_violation(Step::Effect) {
+                            violation = Some(reason);
+                            break 'run;
+                        }
+                    }
+                }
 
-                let ctx = GameCtx { state: &self.state };
-                let effects = vm.eval_call(call, &ctx)?;
-                for effect in effects {
+                if def.edition == Some(Edition::Foil) {
+                    let effect = Effect::AddScore(FOIL_SCORE);
                     self.apply_effect_for_hand(&effect, &mut post_queue);
-                    self.state.trace.push(TraceEvent::EffectApplied { effect });
+                    self.emit(TraceEvent::EffectApplied { effect });
+                    if let Some(reason) = self.puzzle_violation(Step::Effect) {
+                        violation = Some(reason);
+                        break 'run;
+                    }
                 }
             }
 
             // After execution, cards go to the pile (discard).
             self.state.pile.push(card.clone());
+            if violation.is_some() {
+                // A broken step constraint stops the hand; the cards left in it go to the pile.
+                self.state.pile.extend(queue.drain(..));
+                break;
+            }
 
             // Track full history for cards like clone/again/mutate.
             self.state.history.push(crate::game::HistoryEntry {
                 card_id: card.id,
                 def_id: card.def_id.clone(),
-            });
-
-            let after = Snapshot::capture(&self.state);
-            self.state.trace.push(TraceEvent::CardEnd {
-                index: exec_index,
-                name: def.name.to_string(),
-                delta: after.delta_from(before),
+                clone_of: card.provenance.clone_of,
             });
 
             // Queue any extra executions to run immediately after this card.
@@ -336,35 +608,144 @@ impl Engine {
                 queue.push_front(c);
             }
 
+            let after = Snapshot::capture(&self.state, queue.iter().map(|c| c.id));
+            self.emit(TraceEvent::CardEnd {
+                index: exec_index,
+                card_id: card.id,
+                name: def.name,
+                delta: after.regs.delta_from(&before.regs),
+                after,
+            });
+
             exec_index = exec_index.saturating_add(1);
         }
 
+        // Copies made this hand have run; they leave play now.
+        let vanished = self.state.pile.iter().filter(|c| c.is_ephemeral()).count();
+        self.state.pile.retain(|c| !c.is_ephemeral());
+        if vanished > 0 {
+            self.emit(TraceEvent::Info(format!(
+                "{vanished} ephemeral cop{} vanished",
+                if vanished == 1 { "y" } else { "ies" }
+            )));
+        }
+
         // Always draw 1 card after playing a hand (soft reward / pacing).
         {
             let effect = Effect::Draw(1);
             // Source-only (do not consume the pile).
             let _ = self.draw_to_collection_source_only(1);
-            self.state.trace.push(TraceEvent::EffectApplied { effect });
+            self.emit(TraceEvent::EffectApplied { effect });
+        }
+
+        // Charge the hand only once it has run, so a failed play costs nothing.
+        if self.state.mode == RunMode::Classic {
+            self.state.hands_left = self.state.hands_left.saturating_sub(1);
         }
 
         if self.state.mode == RunMode::Puzzle {
-            self.update_puzzle_outcome();
+            self.update_puzzle_outcome(violation);
         } else if self.state.score >= self.state.target_score {
             self.advance_classic_level();
+        } else if self.state.hands_left == 0 {
+            self.state.phase = Phase::GameOver;
+            self.emit(TraceEvent::Info(format!(
+                "Out of hands on level {}: score {}/{}. Run over.",
+                self.state.level, self.state.score, self.state.target_score
+            )));
         }
 
         Ok(())
     }
 
+    /// Cycle collection cards to the pile and draw the same number of replacements.
+    fn discard(&mut self, mut indices: Vec<usize>) -> Result<(), GameError> {
+        if self.state.phase != Phase::InLevel || self.state.mode != RunMode::Classic {
+            return Ok(());
+        }
+        if indices.is_empty() {
+            return Err(GameError::Unavailable("discarding no cards".to_string()));
+        }
+        let len = self.state.collection.len();
+        if let Some(&index) = indices.iter().find(|&&i| i >= len) {
+            return Err(GameError::IndexOutOfRange {
+                zone: "Code Bank",
+                index,
+                len,
+            });
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        if self.state.discards_left == 0 {
+            return Err(GameError::NoDiscardsLeft);
+        }
+        self.state.discards_left -= 1;
+
+        for &index in indices.iter().rev() {
+            let card = self.state.collection.remove(index);
+            self.state.pile.push(card);
+        }
+        self.emit(TraceEvent::Info(format!(
+            "Discarded {} card(s); {} discard(s) left",
+            indices.len(),
+            self.state.discards_left
+        )));
+        self.draw_to_collection(indices.len())
+    }
+
+    /// Rearranges the cards still to run: copies queued by this card first, then the hand.
+    fn apply_queue_effect(
+        &mut self,
+        effect: &Effect,
+        queue: &mut VecDeque<CardInstance>,
+        post_queue: &mut Vec<CardInstance>,
+    ) {
+        for c in post_queue.drain(..).rev() {
+            queue.push_front(c);
+        }
+        let mut skipped = Vec::new();
+        match effect {
+            Effect::Skip(n) => {
+                let count = (*n).clamp(0, queue.len() as i64) as usize;
+                for card in queue.drain(..count) {
+                    skipped.push(card.id);
+                    self.state.pile.push(card);
+                }
+            }
+            Effect::SwapNext => {
+                if queue.len() >= 2 {
+                    queue.swap(0, 1);
+                }
+            }
+            Effect::Reverse => queue.make_contiguous().reverse(),
+            Effect::Pull(n) => {
+                let index = usize::try_from(*n).unwrap_or(0);
+                if (1..=queue.len()).contains(&index)
+                    && let Some(card) = queue.remove(index - 1)
+                {
+                    queue.push_front(card);
+                }
+            }
+            _ => return,
+        }
+        self.emit(TraceEvent::QueueChanged {
+            op: effect.name().to_string(),
+            queue: queue.iter().map(|c| c.id).collect(),
+            skipped,
+        });
+    }
+
     fn apply_effect_for_hand(&mut self, effect: &Effect, post_queue: &mut Vec<CardInstance>) {
         match effect {
             Effect::AddScore(n) => self.state.score += *n,
             Effect::AddBankroll(n) => self.state.bankroll += *n,
             Effect::MulBankroll(n) => self.state.bankroll *= *n,
+            // Only cards reach the queue (`apply_queue_effect`); relic scripts can't.
+            Effect::Skip(_) | Effect::SwapNext | Effect::Reverse | Effect::Pull(_) => {}
             Effect::Draw(n) => {
                 let count: usize = (*n).clamp(0, 25) as usize;
                 if let Err(e) = self.draw_to_collection(count) {
-                    self.state.trace.push(TraceEvent::Error(e.to_string()));
+                    self.emit(TraceEvent::Error(e.to_string()));
                 }
             }
             Effect::SetAcc(v) => self.state.acc = *v,
@@ -375,31 +756,35 @@ impl Engine {
                 }
 
                 let Some(last) = self.state.history.last() else {
-                    self.state.trace.push(TraceEvent::Info(
+                    self.emit(TraceEvent::Info(
                         "clone/again: no last played card".to_string(),
                     ));
                     return;
                 };
 
                 // If the last played card was mutated, clone its current def_id from the pile/deck.
-                let def_id = self
+                // Clones inherit the source instance's upgrades and last only for this hand.
+                let source_id = last.card_id;
+                let (def_id, upgrade) = self
                     .state
                     .pile
                     .iter()
                     .find(|c| c.id == last.card_id)
                     .or_else(|| self.state.collection.iter().find(|c| c.id == last.card_id))
-                    .map(|c| c.def_id.clone())
-                    .unwrap_or_else(|| last.def_id.clone());
+                    .map(|c| (c.def_id.clone(), c.upgrade.clone()))
+                    .unwrap_or_else(|| (last.def_id.clone(), CardUpgrade::default()));
 
                 for _ in 0..count {
-                    post_queue.push(self.new_card(&def_id));
+                    let mut clone = self.new_card(&def_id);
+                    clone.upgrade = upgrade.clone();
+                    clone.provenance.clone_of = Some(source_id);
+                    clone.lifetime = Lifetime::EphemeralThisHand;
+                    post_queue.push(clone);
                 }
             }
             Effect::Mutate => {
                 let Some(last) = self.state.history.last() else {
-                    self.state
-                        .trace
-                        .push(TraceEvent::Info("mutate: no last played card".to_string()));
+                    self.emit(TraceEvent::Info("mutate: no last played card".to_string()));
                     return;
                 };
 
@@ -415,67 +800,463 @@ impl Engine {
                             .find(|c| c.id == last.card_id)
                     });
                 let Some(target) = target else {
-                    self.state.trace.push(TraceEvent::Info(
+                    self.emit(TraceEvent::Info(
                         "mutate: last played card not in pile/deck".to_string(),
                     ));
                     return;
                 };
 
-                if let Some(new_id) = cards::roll_card_id_for_level(&mut self.rng, self.state.level)
+                if target.upgrade.edition == Some(Edition::Eternal) {
+                    let msg = format!("mutate: {} is Eternal", target.def_id);
+                    self.emit(TraceEvent::Info(msg));
+                    return;
+                }
+
+                if let Some(new_id) =
+                    cards::roll_card_id_for_level(&mut self.state.rng.mutate, self.state.level)
                 {
-                    let old = target.def_id.clone();
-                    target.def_id = new_id.to_string();
-                    self.state.trace.push(TraceEvent::Info(format!(
-                        "mutate: {} → {}",
-                        old, target.def_id
-                    )));
+                    let from = std::mem::replace(&mut target.def_id, new_id.to_string());
+                    target.provenance.mutated_from.push(from.clone());
+                    target.retier();
+                    let card_id = target.id;
+                    self.emit(TraceEvent::Mutated {
+                        card_id,
+                        from,
+                        to: new_id.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Cost model for card scripts after static relic and boss modifiers.
+    fn cost_rules(&self) -> CostRules {
+        let rules = CostRules {
+            digit_discount: self
+                .state
+                .relics
+                .iter()
+                .filter_map(|id| relics::get(id))
+                .map(|r| r.digit_discount)
+                .sum(),
+            ..CostRules::default()
+        };
+        self.state.boss.map_or(rules, |b| b.cost_rules(rules))
+    }
+
+    /// Run the trigger script of every owned relic listening to `hook`.
+    ///
+    /// Relic effects are applied like card effects but never re-trigger relics, and a failing
+    /// relic script is traced instead of aborting the hand. Returns the first puzzle step
+    /// constraint a relic effect breaks; no further relics fire after that.
+    fn fire_relics(
+        &mut self,
+        hook: RelicHook,
+        kind: Option<CardKind>,
+        effect: Option<&Effect>,
+        fired_this_hand: &mut Vec<&'static str>,
+        post_queue: &mut Vec<CardInstance>,
+    ) -> Option<String> {
+        let listening = self
+            .state
+            .relics
+            .iter()
+            .filter_map(|id| relics::get(id))
+            .filter(|r| r.matches(hook, kind, effect))
+            .collect::<Vec<_>>();
+
+        for relic in listening {
+            if relic.once_per_hand {
+                if fired_this_hand.contains(&relic.id) {
+                    continue;
+                }
+                fired_this_hand.push(relic.id);
+            }
+            self.emit(TraceEvent::RelicTriggered {
+                name: relic.name.to_string(),
+                hook: hook.label().to_string(),
+            });
+
+            let program = match parse_program(relic.script) {
+                Ok(program) => program,
+                Err(e) => {
+                    self.emit(TraceEvent::Error(e.to_string()));
+                    continue;
+                }
+            };
+            let mut vm = Machine::with_rules(self.state.vm_limits(), self.state.vm_rules());
+            for call in &program.calls {
+                let ctx = GameCtx {
+                    state: &self.state,
+                    queued: 0,
+                };
+                match vm.eval_call(call, &ctx) {
+                    Ok(effects) => {
+                        for effect in effects {
+                            self.apply_effect_for_hand(&effect, post_queue);
+                            self.emit(TraceEvent::EffectApplied { effect });
+                            if let Some(reason) = self.puzzle_violation(Step::Effect) {
+                                return Some(reason);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        self.emit(TraceEvent::Error(e.to_string()));
+                        break;
+                    }
                 }
             }
         }
+        None
     }
 
     fn advance_classic_level(&mut self) {
+        // Record the clear before any payout, so the event carries the bankroll it was won with.
+        self.emit(TraceEvent::LevelCleared {
+            level: self.state.level,
+            score: self.state.score,
+            target: self.state.target_score,
+            bankroll: self.state.bankroll,
+            turn: self.state.turn,
+            hands_left: self.state.hands_left,
+        });
+
+        // Level-clear relics see the level that was just cleared. Classic runs have no step
+        // constraints, so there is no violation to handle.
+        self.fire_relics(
+            RelicHook::LevelClear,
+            None,
+            None,
+            &mut Vec::new(),
+            &mut Vec::new(),
+        );
+
+        if self.state.boss.is_some() {
+            self.grant_boss_reward();
+        }
+
         let previous_level = self.state.level;
         self.state.level = self.state.level.saturating_add(1);
-        self.state.target_score = target_for_level(self.state.level);
+        self.state.target_score = self.state.config.target_for_level(self.state.level);
+        self.state.boss = bosses::boss_for_level(self.state.level, self.state.config.boss_every);
+
+        // Stock the shop; boosters are now bought there instead of granted for free.
+        let offers = cards::generate_source_ids_with_count(
+            self.state.rng.shop.random::<u64>(),
+            self.state.level,
+            shop::OFFER_COUNT,
+        );
+        let relic_id = (self.state.relics.len() < relics::MAX_RELICS)
+            .then(|| {
+                let unowned = relics::catalog()
+                    .iter()
+                    .filter(|r| !self.state.relics.iter().any(|id| id == r.id))
+                    .collect::<Vec<_>>();
+                unowned.choose(&mut self.state.rng.shop).map(|r| r.id)
+            })
+            .flatten();
+        self.state.shop = Some(ShopState::new(
+            self.state.level,
+            &offers,
+            self.state.config.booster_count_for_level(self.state.level),
+            relic_id,
+        ));
+        self.state.phase = Phase::Shop;
+
+        self.emit(TraceEvent::Info(format!(
+            "Level {previous_level} cleared! Next target: {}. Shop open.",
+            self.state.target_score
+        )));
+        if let Some(boss) = self.state.boss {
+            self.emit(TraceEvent::Info(format!(
+                "Next level is a boss: {}. {}",
+                boss.name(),
+                boss.doc()
+            )));
+        }
+    }
+
+    /// Clearing a boss level stamps an edition onto a random plain Code Bank card.
+    fn grant_boss_reward(&mut self) {
+        let candidates = (0..self.state.collection.len())
+            .filter(|&i| self.state.collection[i].upgrade.edition.is_none())
+            .collect::<Vec<_>>();
+        let (Some(&index), Some(&edition)) = (
+            candidates.choose(&mut self.state.rng.shop),
+            Edition::ALL.choose(&mut self.state.rng.shop),
+        ) else {
+            return;
+        };
+        let card = &mut self.state.collection[index];
+        card.upgrade.edition = Some(edition);
+        let name = card.name();
+        let msg = format!("Boss reward: {name} is now {}", edition.label());
+        self.emit(TraceEvent::Info(msg));
+    }
+
+    fn spend(&mut self, cost: i64) -> Result<(), GameError> {
+        if self.state.bankroll < cost {
+            return Err(GameError::CannotAfford {
+                cost,
+                bankroll: self.state.bankroll,
+            });
+        }
+        self.state.bankroll -= cost;
+        Ok(())
+    }
+
+    fn buy_card(&mut self, index: usize) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop {
+            return Ok(());
+        }
+        let Some(offer) = self
+            .state
+            .shop
+            .as_ref()
+            .and_then(|shop| shop.offers.get(index))
+            .filter(|offer| !offer.sold)
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        self.spend(offer.price)?;
+        let card = self.new_card(&offer.def_id);
+        self.state.collection.push(card);
+        if let Some(slot) = self
+            .state
+            .shop
+            .as_mut()
+            .and_then(|shop| shop.offers.get_mut(index))
+        {
+            slot.sold = true;
+        }
+
+        let name = cards::get(&offer.def_id).map_or(offer.def_id.as_str(), |d| d.name);
+        self.emit(TraceEvent::Info(format!(
+            "Shop: bought {name} for ${}",
+            offer.price
+        )));
+        Ok(())
+    }
+
+    fn reroll_shop(&mut self) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop {
+            return Ok(());
+        }
+        let Some(cost) = self.state.shop.as_ref().map(|shop| shop.reroll_cost) else {
+            return Ok(());
+        };
+
+        self.spend(cost)?;
+        let offers = cards::generate_source_ids_with_count(
+            self.state.rng.shop.random::<u64>(),
+            self.state.level,
+            shop::OFFER_COUNT,
+        );
+        if let Some(shop) = self.state.shop.as_mut() {
+            shop.restock(&offers);
+            shop.reroll_cost += 1;
+        }
+
+        self.emit(TraceEvent::Info(format!("Shop: rerolled for ${cost}")));
+        Ok(())
+    }
+
+    fn buy_pack(&mut self) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop {
+            return Ok(());
+        }
+        let Some((cost, size)) = self
+            .state
+            .shop
+            .as_ref()
+            .filter(|shop| !shop.pack_bought)
+            .map(|shop| (shop.pack_cost, shop.pack_size))
+        else {
+            return Ok(());
+        };
 
-        // Add a small "booster" of newly generated cards every clear.
-        let mut generated = 0usize;
+        self.spend(cost)?;
         let booster = cards::generate_source_ids_with_count(
-            self.rng.random::<u64>(),
+            self.state.rng.booster.random(),
             self.state.level,
-            booster_count_for_level(self.state.level),
+            size,
         );
+        let generated = booster.len();
         for id in booster {
             let card = self.new_card(id);
             self.state.deck.push(card);
-            generated += 1;
         }
-        self.state.deck.shuffle(&mut self.rng);
+        self.state.deck.shuffle(&mut self.state.rng.shuffle);
+        if let Some(shop) = self.state.shop.as_mut() {
+            shop.pack_bought = true;
+        }
 
-        self.state.trace.push(TraceEvent::Info(format!(
-            "Level {previous_level} cleared! Next target: {} (+{generated} source cards)",
-            self.state.target_score
+        self.emit(TraceEvent::Info(format!(
+            "Shop: pack opened for ${cost} (+{generated} source cards)"
+        )));
+        Ok(())
+    }
+
+    fn remove_card(&mut self, index: usize) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop || index >= self.state.collection.len() {
+            return Ok(());
+        }
+        let Some(cost) = self.state.shop.as_ref().map(|shop| shop.removal_cost) else {
+            return Ok(());
+        };
+
+        if self.state.collection[index].upgrade.edition == Some(Edition::Eternal) {
+            let name = self.state.collection[index].name();
+            return Err(GameError::EternalCard(name));
+        }
+
+        self.spend(cost)?;
+        let card = self.state.collection.remove(index);
+        if let Some(shop) = self.state.shop.as_mut() {
+            shop.removal_cost += 1;
+        }
+
+        let name = card.name();
+        self.emit(TraceEvent::Info(format!(
+            "Shop: removed {name} for ${cost}"
         )));
+        Ok(())
     }
 
-    fn update_puzzle_outcome(&mut self) {
-        let score_ok = self.state.score >= self.state.target_score;
-        let bankroll_ok = self
+    fn buy_relic(&mut self) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop {
+            return Ok(());
+        }
+        let Some(offer) = self
+            .state
+            .shop
+            .as_ref()
+            .and_then(|shop| shop.relic.as_ref())
+            .filter(|offer| !offer.sold)
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        self.spend(offer.price)?;
+        self.state.relics.push(offer.relic_id.clone());
+        if let Some(slot) = self
             .state
-            .puzzle_bankroll_goal
-            .is_none_or(|goal| self.state.bankroll >= goal);
+            .shop
+            .as_mut()
+            .and_then(|shop| shop.relic.as_mut())
+        {
+            slot.sold = true;
+        }
+
+        let name = relics::get(&offer.relic_id).map_or(offer.relic_id.as_str(), |r| r.name);
+        self.emit(TraceEvent::Info(format!(
+            "Shop: bought relic {name} for ${}",
+            offer.price
+        )));
+        Ok(())
+    }
 
-        if score_ok && bankroll_ok {
+    fn upgrade_card(&mut self, index: usize) -> Result<(), GameError> {
+        if self.state.phase != Phase::Shop {
+            return Ok(());
+        }
+        let Some(card) = self.state.collection.get(index) else {
+            return Ok(());
+        };
+        if card.upgrade.level >= upgrades::MAX_UPGRADE_LEVEL {
+            return Ok(());
+        }
+
+        let cost = upgrades::upgrade_price(card.upgrade.level);
+        self.spend(cost)?;
+        let card = &mut self.state.collection[index];
+        card.level_up();
+
+        let name = card
+            .effective_def()
+            .map_or_else(|| card.def_id.clone(), |d| d.name);
+        self.emit(TraceEvent::Info(format!(
+            "Shop: upgraded to {name} for ${cost}"
+        )));
+        Ok(())
+    }
+
+    fn leave_shop(&mut self) {
+        if self.state.phase != Phase::Shop {
+            return;
+        }
+        self.state.shop = None;
+        self.state.phase = Phase::InLevel;
+        self.state.hands_left = self.state.config.hands_per_level;
+        self.state.discards_left = self.state.config.discards_per_level;
+        self.emit(TraceEvent::Info(format!(
+            "Level {} begins. Target: {}",
+            self.state.level, self.state.target_score
+        )));
+        if let Some(boss) = self.state.boss {
+            self.emit(TraceEvent::Info(format!(
+                "Boss level: {}. {}",
+                boss.name(),
+                boss.doc()
+            )));
+        }
+    }
+
+    /// First puzzle goal broken by `step`; always `None` outside puzzles.
+    fn puzzle_violation(&self, step: Step<'_>) -> Option<String> {
+        if self.state.mode != RunMode::Puzzle {
+            return None;
+        }
+        self.state
+            .puzzle_goals
+            .iter()
+            .find_map(|goal| goal.violation(&self.state, step))
+    }
+
+    fn update_puzzle_outcome(&mut self, violation: Option<String>) {
+        if let Some(reason) = violation {
+            self.state.puzzle_failed = true;
+            self.state.phase = Phase::GameOver;
+            let msg = format!("Puzzle failed: {reason}.");
+            self.state.puzzle_message = Some(msg.clone());
+            self.emit(TraceEvent::Info(msg));
+            return;
+        }
+
+        let goals = goals::active(&self.state);
+        if goals.iter().all(|goal| goal.is_met(&self.state)) {
             if !self.state.puzzle_solved {
                 self.state.puzzle_solved = true;
                 self.state.phase = Phase::Reward;
+                let par = self
+                    .state
+                    .puzzle_source
+                    .as_deref()
+                    .map(|p| p.par)
+                    .unwrap_or_default();
+                let surplus = self.state.bankroll - self.state.puzzle_bankroll_goal.unwrap_or(0);
+                let result = PuzzleResult::rate(
+                    &par,
+                    self.state.turn,
+                    self.state.puzzle_cards_used,
+                    surplus,
+                );
+                self.state.puzzle_result = Some(result);
+                self.emit(TraceEvent::PuzzleSolved {
+                    id: self.state.puzzle_id.clone().unwrap_or_default(),
+                    stars: result.stars,
+                });
                 let msg = format!(
-                    "Puzzle solved in {} play(s). Great sequencing.",
-                    self.state.turn
+                    "Puzzle solved in {} play(s) with {} card(s): {}",
+                    self.state.turn,
+                    self.state.puzzle_cards_used,
+                    progress::star_text(result.stars)
                 );
                 self.state.puzzle_message = Some(msg.clone());
-                self.state.trace.push(TraceEvent::Info(msg));
+                self.emit(TraceEvent::Info(msg));
             }
             return;
         }
@@ -493,7 +1274,7 @@ impl Engine {
                 .unwrap_or("Try a different sequence.");
             let msg = format!("Puzzle failed: out of plays ({limit}). Hint: {hint}");
             self.state.puzzle_message = Some(msg.clone());
-            self.state.trace.push(TraceEvent::Info(msg));
+            self.emit(TraceEvent::Info(msg));
             return;
         }
 
@@ -512,40 +1293,6 @@ impl Engine {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Snapshot {
-    score: i64,
-    bankroll: i64,
-    acc: i64,
-    len_source: i64,
-    len_deck: i64,
-    len_hand: i64,
-}
-
-impl Snapshot {
-    fn capture(state: &GameState) -> Self {
-        Self {
-            score: state.score,
-            bankroll: state.bankroll,
-            acc: state.acc,
-            len_source: state.deck.len() as i64,
-            len_deck: state.collection.len() as i64,
-            len_hand: state.hand.len() as i64,
-        }
-    }
-
-    fn delta_from(self, before: Snapshot) -> crate::game::trace::StateDelta {
-        crate::game::trace::StateDelta {
-            score: self.score - before.score,
-            bankroll: self.bankroll - before.bankroll,
-            acc: self.acc - before.acc,
-            len_source: self.len_source - before.len_source,
-            len_deck: self.len_deck - before.len_deck,
-            len_hand: self.len_hand - before.len_hand,
-        }
-    }
-}
-
 fn take_at<T>(v: &mut Vec<T>, index: usize) -> Option<T> {
     if index >= v.len() {
         return None;
@@ -563,40 +1310,17 @@ fn move_within<T>(v: &mut Vec<T>, from: usize, to: usize) {
     v.insert(to, item);
 }
 
-fn swap_within<T>(v: &mut Vec<T>, a: usize, b: usize) {
+fn swap_within<T>(v: &mut [T], a: usize, b: usize) {
     if a >= v.len() || b >= v.len() || a == b {
         return;
     }
     v.swap(a, b);
 }
 
-fn target_for_level(level: u32) -> i64 {
-    let level = level.max(1) as i64;
-    let base = 10i64;
-    let linear = level.saturating_sub(1).saturating_mul(12);
-    let curve = level
-        .saturating_sub(1)
-        .saturating_mul(level.saturating_sub(2))
-        .saturating_mul(2);
-    base.saturating_add(linear).saturating_add(curve)
-}
-
-fn booster_count_for_level(level: u32) -> usize {
-    (3 + (level.max(1) as usize / 2)).min(8)
-}
-
-fn stable_seed_from_id(id: &str) -> u64 {
-    // Stable FNV-1a hash so puzzle seeds are reproducible across targets.
-    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
-    for b in id.as_bytes() {
-        h ^= *b as u64;
-        h = h.wrapping_mul(0x1000_0000_01b3);
-    }
-    h
-}
-
 struct GameCtx<'a> {
     state: &'a GameState,
+    /// Cards still waiting to run this hand (`len_queue`).
+    queued: usize,
 }
 
 impl VmContext for GameCtx<'_> {
@@ -611,6 +1335,7 @@ impl VmContext for GameCtx<'_> {
             "len_source" | "len_draw" | "S" => Some(self.state.deck.len() as i64),
             "len_hand" | "H" => Some(self.state.hand.len() as i64),
             "len_pile" | "len_discard" | "P" => Some(self.state.pile.len() as i64),
+            "len_queue" | "U" => Some(self.queued as i64),
             "deck" => Some(self.state.collection.len() as i64),
             "hand" => Some(self.state.hand.len() as i64),
             "lvl" | "level" | "L" => Some(self.state.level as i64),
@@ -618,13 +1343,36 @@ impl VmContext for GameCtx<'_> {
             "bankroll" | "money" | "B" => Some(self.state.bankroll),
             "score" | "Q" => Some(self.state.score),
             "target" | "T" => Some(self.state.target_score),
-            "max_step" | "max_steps" => Some(self.state.limits.max_steps as i64),
-            "max_loop" | "max_loop_iters" => Some(self.state.limits.max_loop_iters as i64),
+            "max_step" | "max_steps" => Some(self.state.vm_limits().max_steps as i64),
+            "max_loop" | "max_loop_iters" => Some(self.state.vm_limits().max_loop_iters as i64),
             _ => None,
         }
     }
 }
 
+/// Static check for a card script: it parses, and every call names a builtin with the right
+/// arity and reads only known registers. Runtime failures such as overflow are not reported.
+pub fn check_script(script: &str) -> Result<(), GameError> {
+    let program = parse_program(script)?;
+    let state = GameState::new(Vec::new(), Limits::default());
+    let ctx = GameCtx {
+        state: &state,
+        queued: 0,
+    };
+    for call in &program.calls {
+        let mut vm = Machine::new(Limits::default());
+        if let Err(
+            e @ (VmError::UnknownFunction(_)
+            | VmError::UnknownRegister(_)
+            | VmError::WrongArity { .. }),
+        ) = vm.eval_call(call, &ctx)
+        {
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
 fn expr_to_string(expr: &crate::kardlang::Expr) -> String {
     use crate::kardlang::{BinOp, Expr};
     match expr {
@@ -644,6 +1392,8 @@ fn expr_to_string(expr: &crate::kardlang::Expr) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::goals::Goal;
+    use crate::game::{DISCARDS_PER_LEVEL, HANDS_PER_LEVEL};
 
     #[test]
     fn draw_then_play_applies_effects_and_moves_cards_to_pile() {
@@ -709,12 +1459,39 @@ mod tests {
 
         let replayed_score_cards = engine
             .state
-            .pile
+            .history
             .iter()
-            .filter(|c| c.def_id == "tap_score")
+            .filter(|h| h.def_id == "tap_score")
             .count();
         assert_eq!(engine.state.score, 4);
         assert_eq!(replayed_score_cards, 2);
+        // The copy was ephemeral: only the two hand cards reach the pile.
+        assert_eq!(engine.state.pile.len(), 2);
+    }
+
+    #[test]
+    fn clones_are_ephemeral_and_remember_their_source() {
+        let mut engine = Engine::with_deck(2, Vec::new(), Limits::default());
+        engine.state.pile = vec![CardInstance::new(1, "tap_score")];
+        engine.state.history.push(crate::game::HistoryEntry {
+            card_id: 1,
+            def_id: "tap_score".to_string(),
+            clone_of: None,
+        });
+        let mut queued = Vec::new();
+        engine.apply_effect_for_hand(&Effect::Clone(2), &mut queued);
+
+        assert_eq!(queued.len(), 2);
+        for copy in &queued {
+            assert_eq!(copy.provenance.clone_of, Some(1));
+            assert!(copy.is_ephemeral());
+        }
+
+        // Mutating the last played card records what it used to be.
+        engine.apply_effect_for_hand(&Effect::Mutate, &mut queued);
+        let card = &engine.state.pile[0];
+        assert_eq!(card.provenance.mutated_from, ["tap_score"]);
+        assert!(!card.provenance.is_original());
     }
 
     #[test]
@@ -729,7 +1506,23 @@ mod tests {
     }
 
     #[test]
-    fn clearing_a_level_scales_target_and_generates_more_source_cards() {
+    fn daily_runs_share_a_seed_per_date() {
+        let mut a = Engine::new(0);
+        let mut b = Engine::new(99);
+        let date = "2026-10-18".to_string();
+        a.dispatch(Action::NewDailyRun { date: date.clone() })
+            .unwrap();
+        b.dispatch(Action::NewDailyRun { date: date.clone() })
+            .unwrap();
+
+        assert_eq!(a.state.seed, daily::daily_seed(&date));
+        assert_eq!(a.state.daily.as_deref(), Some("2026-10-18"));
+        assert_eq!(a.state.deck, b.state.deck);
+        assert_eq!(a.state.config, RunConfig::standard());
+    }
+
+    #[test]
+    fn clearing_a_level_scales_target_and_opens_the_shop() {
         let mut engine = Engine::with_deck(4, Vec::new(), Limits::default());
         engine.state.score = engine.state.target_score;
         engine.state.hand = vec![CardInstance::new(1, "tap_bank")];
@@ -738,13 +1531,428 @@ mod tests {
         engine.dispatch(Action::PlayHand).unwrap();
 
         assert_eq!(engine.state.level, 2);
-        assert_eq!(engine.state.target_score, target_for_level(2));
+        assert_eq!(
+            engine.state.target_score,
+            engine.state.config.target_for_level(2)
+        );
+        assert_eq!(engine.state.phase, Phase::Shop);
+        let shop = engine.state.shop.as_ref().expect("shop should be stocked");
+        assert_eq!(shop.offers.len(), shop::OFFER_COUNT);
+
+        engine.dispatch(Action::LeaveShop).unwrap();
+        assert_eq!(engine.state.phase, Phase::InLevel);
+        assert!(engine.state.shop.is_none());
+    }
+
+    fn engine_in_shop() -> Engine {
+        let mut engine = Engine::with_deck(5, Vec::new(), Limits::default());
+        engine.state.score = engine.state.target_score;
+        engine.state.bankroll = 40;
+        engine.dispatch(Action::PlayHand).unwrap();
+        assert_eq!(engine.state.phase, Phase::Shop);
+        engine
+    }
+
+    #[test]
+    fn buying_an_offer_spends_bankroll_and_adds_the_card() {
+        let mut engine = engine_in_shop();
+        let offer = engine.state.shop.as_ref().unwrap().offers[0].clone();
+
+        engine.dispatch(Action::BuyCard { index: 0 }).unwrap();
+
+        assert_eq!(engine.state.bankroll, 40 - offer.price);
+        assert_eq!(engine.state.collection.len(), 1);
+        assert_eq!(engine.state.collection[0].def_id, offer.def_id);
+        assert!(engine.state.shop.as_ref().unwrap().offers[0].sold);
+
+        // Sold offers cannot be bought twice.
+        engine.dispatch(Action::BuyCard { index: 0 }).unwrap();
+        assert_eq!(engine.state.collection.len(), 1);
+    }
+
+    #[test]
+    fn shop_rejects_purchases_the_bankroll_cannot_cover() {
+        let mut engine = engine_in_shop();
+        engine.state.bankroll = 0;
+
+        let err = engine.dispatch(Action::BuyCard { index: 0 }).unwrap_err();
+        assert!(matches!(err, GameError::CannotAfford { bankroll: 0, .. }));
+        assert!(engine.state.collection.is_empty());
+    }
+
+    #[test]
+    fn shop_sells_one_relic_that_joins_the_run() {
+        let mut engine = engine_in_shop();
+        let offer = engine.state.shop.as_ref().unwrap().relic.clone().unwrap();
+
+        engine.dispatch(Action::BuyRelic).unwrap();
+        engine.dispatch(Action::BuyRelic).unwrap();
+
+        assert_eq!(engine.state.relics, vec![offer.relic_id]);
+        assert_eq!(engine.state.bankroll, 40 - offer.price);
+    }
+
+    #[test]
+    fn shop_upgrades_raise_level_script_and_price() {
+        let mut engine = engine_in_shop();
+        engine.state.collection = vec![CardInstance::new(101, "tap_score")];
+
+        engine.dispatch(Action::UpgradeCard { index: 0 }).unwrap();
+        engine.dispatch(Action::UpgradeCard { index: 0 }).unwrap();
+
+        let def = engine.state.collection[0].effective_def().unwrap();
+        assert_eq!(def.level, 2);
+        assert_eq!(def.name, "Tap Score +2");
+        assert_eq!(def.budget, def.base.budget + 4);
+        assert_eq!(
+            engine.state.bankroll,
+            40 - upgrades::upgrade_price(0) - upgrades::upgrade_price(1)
+        );
+    }
+
+    #[test]
+    fn clones_inherit_upgrades_and_foil_adds_score() {
+        let mut engine = Engine::with_deck(2, Vec::new(), Limits::default());
+        let mut tap = CardInstance::new(101, "tap_score");
+        tap.level_up();
+        tap.upgrade.edition = Some(Edition::Foil);
+        engine.state.hand = vec![tap, CardInstance::new(102, "clone_one")];
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        // Upgraded tap scores 3, plus 2 for Foil, twice.
+        assert_eq!(engine.state.score, 10);
+        assert!(engine.state.pile.iter().all(|c| !c.is_ephemeral()));
+    }
+
+    #[test]
+    fn eternal_cards_cannot_be_removed() {
+        let mut engine = engine_in_shop();
+        let mut card = CardInstance::new(101, "tap_score");
+        card.upgrade.edition = Some(Edition::Eternal);
+        assert!(card.level_up());
+        engine.state.collection = vec![card];
+
+        let err = engine
+            .dispatch(Action::RemoveCard { index: 0 })
+            .unwrap_err();
+        // Errors name the upgraded card.
+        assert!(matches!(err, GameError::EternalCard(name) if name == "Tap Score +1"));
+        assert_eq!(engine.state.collection.len(), 1);
+        assert_eq!(engine.state.bankroll, 40);
+    }
+
+    #[test]
+    fn clearing_a_boss_level_grants_an_edition() {
+        let mut engine = Engine::with_deck(5, Vec::new(), Limits::default());
+        engine.state.collection = vec![CardInstance::new(101, "tap_score")];
+        engine.state.boss = Some(crate::game::bosses::BossModifier::HalfCash);
+        engine.state.score = engine.state.target_score;
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        assert!(engine.state.collection[0].upgrade.edition.is_some());
+    }
+
+    #[test]
+    fn score_chip_adds_score_per_score_card() {
+        let mut engine = Engine::with_deck(2, Vec::new(), Limits::default());
+        engine.state.relics = vec!["score_chip".to_string()];
+        engine.state.hand = vec![
+            CardInstance::new(1, "tap_score"),
+            CardInstance::new(2, "tap_bank"),
+            CardInstance::new(3, "tap_score"),
+        ];
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        assert_eq!(engine.state.score, 2 + 2 + 2);
+        let triggers = engine
+            .state
+            .trace
+            .iter()
+            .filter(|e| matches!(e, TraceEvent::RelicTriggered { .. }))
+            .count();
+        assert_eq!(triggers, 2);
+    }
+
+    #[test]
+    fn echo_chamber_adds_one_copy_to_the_first_clone_only() {
+        let mut engine = Engine::with_deck(2, Vec::new(), Limits::default());
+        engine.state.relics = vec!["echo_chamber".to_string()];
+        // Ids above the engine's counter so clones never collide with hand cards.
+        engine.state.hand = vec![
+            CardInstance::new(101, "tap_score"),
+            CardInstance::new(102, "clone_one"),
+            CardInstance::new(103, "clone_one"),
+        ];
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        // Base line: tap, clone (+1 tap), clone (+1 clone of the replayed tap).
+        // The relic adds one extra tap on the first clone only.
+        let taps = engine
+            .state
+            .history
+            .iter()
+            .filter(|h| h.def_id == "tap_score")
+            .count();
+        assert_eq!(taps, 4);
+    }
+
+    #[test]
+    fn dividend_pays_the_cleared_level_on_clear() {
+        let mut engine = Engine::with_deck(5, Vec::new(), Limits::default());
+        engine.state.relics = vec!["dividend".to_string()];
+        engine.state.score = engine.state.target_score;
+        let bankroll = engine.state.bankroll;
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        assert_eq!(engine.state.phase, Phase::Shop);
+        assert_eq!(engine.state.bankroll, bankroll + 1);
+        // The clear is recorded with the bankroll before the payout.
+        assert!(engine.state.trace.iter().any(|e| matches!(
+            e,
+            TraceEvent::LevelCleared { bankroll: b, .. } if *b == bankroll
+        )));
+    }
+
+    #[test]
+    fn reroll_pack_and_removal_costs_escalate() {
+        let mut engine = engine_in_shop();
+        engine.state.collection = vec![
+            CardInstance::new(90, "tap_score"),
+            CardInstance::new(91, "tap_bank"),
+        ];
+
+        engine.dispatch(Action::RerollShop).unwrap();
+        assert_eq!(engine.state.bankroll, 40 - shop::BASE_REROLL_COST);
+        assert_eq!(
+            engine.state.shop.as_ref().unwrap().reroll_cost,
+            shop::BASE_REROLL_COST + 1
+        );
+
+        let before = engine.state.bankroll;
+        engine.dispatch(Action::RemoveCard { index: 0 }).unwrap();
+        assert_eq!(engine.state.bankroll, before - shop::BASE_REMOVAL_COST);
+        assert_eq!(engine.state.collection.len(), 1);
+        assert_eq!(engine.state.collection[0].def_id, "tap_bank");
+
+        let deck_before = engine.state.deck.len();
+        engine.dispatch(Action::BuyPack).unwrap();
+        assert_eq!(
+            engine.state.deck.len(),
+            deck_before + engine.state.config.booster_count_for_level(2)
+        );
+        // Only one pack per visit.
+        engine.dispatch(Action::BuyPack).unwrap();
+        assert_eq!(
+            engine.state.deck.len(),
+            deck_before + engine.state.config.booster_count_for_level(2)
+        );
+    }
+
+    #[test]
+    fn classic_run_ends_when_hands_run_out_below_target() {
+        let mut engine = Engine::with_deck(6, Vec::new(), Limits::default());
+        for played in 1..=HANDS_PER_LEVEL {
+            assert_eq!(engine.state.phase, Phase::InLevel);
+            engine.state.hand = vec![CardInstance::new(played as u64, "tap_score")];
+            engine.dispatch(Action::PlayHand).unwrap();
+            assert_eq!(engine.state.hands_left, HANDS_PER_LEVEL - played);
+        }
+
+        assert!(engine.state.score < engine.state.target_score);
+        assert_eq!(engine.state.phase, Phase::GameOver);
+
+        // Further plays are ignored once the run is over.
+        engine.state.hand = vec![CardInstance::new(99, "tap_score")];
+        engine.dispatch(Action::PlayHand).unwrap();
+        assert_eq!(engine.state.hand.len(), 1);
+    }
+
+    #[test]
+    fn clearing_on_the_last_hand_still_advances() {
+        let mut engine = Engine::with_deck(7, Vec::new(), Limits::default());
+        engine.state.hands_left = 1;
+        engine.state.score = engine.state.target_score - 2;
+        engine.state.hand = vec![CardInstance::new(1, "tap_score")];
+
+        engine.dispatch(Action::PlayHand).unwrap();
+        assert_eq!(engine.state.phase, Phase::Shop);
+
+        engine.dispatch(Action::LeaveShop).unwrap();
+        assert_eq!(engine.state.hands_left, HANDS_PER_LEVEL);
+        assert_eq!(engine.state.discards_left, DISCARDS_PER_LEVEL);
+    }
+
+    #[test]
+    fn advancing_into_a_boss_level_announces_the_modifier() {
+        let mut engine = Engine::with_deck(5, Vec::new(), Limits::default());
+        engine.state.level = engine.state.config.boss_every - 1;
+        engine.state.score = engine.state.target_score;
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let boss = engine.state.boss.expect("level should be a boss");
+        engine.dispatch(Action::LeaveShop).unwrap();
+        let announced = engine.state.trace.iter().any(|e| {
+            matches!(e, TraceEvent::Info(msg) if msg.starts_with("Boss level") && msg.contains(boss.name()))
+        });
+        assert!(announced);
+    }
+
+    #[test]
+    fn over_budget_cards_fizzle_and_keep_the_hand_in_play() {
+        let mut engine = Engine::with_deck(9, Vec::new(), Limits::default());
+        let mut bloated = CardInstance::new(102, "tap_score");
+        bloated.upgrade.script = Some("s(1111111111)".to_string());
+        engine.state.hand = vec![
+            CardInstance::new(101, "tap_score"),
+            bloated,
+            CardInstance::new(103, "tap_score"),
+        ];
+
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        // Only the two cards within budget scored; all three reached the pile.
+        assert_eq!(engine.state.score, 4);
+        assert_eq!(engine.state.pile.len(), 3);
+        assert!(
+            engine
+                .state
+                .trace
+                .iter()
+                .any(|e| matches!(e, TraceEvent::Error(msg) if msg.contains("exceeds budget")))
+        );
+    }
+
+    #[test]
+    fn boss_rules_apply_to_cost_vm_and_draws() {
+        use crate::game::bosses::BossModifier;
+
+        let play = |boss: BossModifier, hand: &[&str]| {
+            let deck = vec![
+                CardInstance::new(1, "tap_bank"),
+                CardInstance::new(2, "tap_bank"),
+            ];
+            let mut engine = Engine::with_deck(9, deck, Limits::default());
+            engine.state.boss = Some(boss);
+            engine.state.hand = hand
+                .iter()
+                .enumerate()
+                .map(|(i, id)| CardInstance::new(101 + i as u64, *id))
+                .collect();
+            engine.dispatch(Action::PlayHand).unwrap();
+            engine
+        };
+
+        // `s(11)` costs 5 normally; each 1 now costs 2.
+        let engine = play(BossModifier::DoubleDigits, &["tap_score"]);
         assert!(
-            engine.state.deck.len() >= booster_count_for_level(2),
-            "expected level clear to generate booster cards"
+            engine
+                .state
+                .trace
+                .iter()
+                .any(|e| matches!(e, TraceEvent::CardStart { cost: 7, .. }))
+        );
+
+        // Cash still spends 2 but only scores 1.
+        let engine = play(BossModifier::HalfCash, &["cash_two"]);
+        assert_eq!((engine.state.score, engine.state.bankroll), (1, 8));
+
+        // Mutate fizzles with a traced error; the rest of the hand still runs.
+        let engine = play(
+            BossModifier::NoMutate,
+            &["tap_score", "mutator", "tap_score"],
+        );
+        assert_eq!(engine.state.score, 4);
+        assert!(
+            engine
+                .state
+                .trace
+                .iter()
+                .any(|e| matches!(e, TraceEvent::Error(msg) if msg.contains("disabled")))
+        );
+
+        // Frozen source: neither the draw effect nor the post-hand draw finds anything.
+        let engine = play(BossModifier::FrozenSource, &["spark_draw"]);
+        assert!(engine.state.collection.is_empty());
+        assert_eq!(engine.state.deck.len(), 2);
+
+        let engine = play(BossModifier::HalfSteps, &["tap_score"]);
+        assert_eq!(
+            engine.state.vm_limits().max_steps,
+            Limits::default().max_steps / 2
         );
     }
 
+    #[test]
+    fn discard_cycles_cards_to_the_pile_and_draws_replacements() {
+        let deck = vec![
+            CardInstance::new(1, "tap_bank"),
+            CardInstance::new(2, "tap_bank"),
+        ];
+        let mut engine = Engine::with_deck(8, deck, Limits::default());
+        engine.state.collection = vec![
+            CardInstance::new(10, "tap_score"),
+            CardInstance::new(11, "spark_draw"),
+            CardInstance::new(12, "tap_score"),
+        ];
+
+        engine
+            .dispatch(Action::Discard {
+                indices: vec![2, 0, 2],
+            })
+            .unwrap();
+
+        assert_eq!(engine.state.discards_left, DISCARDS_PER_LEVEL - 1);
+        assert_eq!(engine.state.pile.len(), 2);
+        assert_eq!(engine.state.collection.len(), 3);
+        assert_eq!(engine.state.collection[0].def_id, "spark_draw");
+        assert!(engine.state.deck.is_empty());
+    }
+
+    #[test]
+    fn discard_fails_once_the_budget_is_spent() {
+        let mut engine = Engine::with_deck(9, Vec::new(), Limits::default());
+        engine.state.collection = vec![CardInstance::new(10, "tap_score")];
+        engine.state.discards_left = 0;
+
+        let err = engine
+            .dispatch(Action::Discard { indices: vec![0] })
+            .unwrap_err();
+        assert!(matches!(err, GameError::NoDiscardsLeft));
+        assert_eq!(engine.state.collection.len(), 1);
+
+        // Bad selections are rejected too, without spending a discard.
+        engine.state.discards_left = 1;
+        let err = engine
+            .dispatch(Action::Discard {
+                indices: vec![0, 3],
+            })
+            .unwrap_err();
+        assert!(matches!(err, GameError::IndexOutOfRange { index: 3, .. }));
+        let err = engine
+            .dispatch(Action::Discard {
+                indices: Vec::new(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, GameError::Unavailable(_)));
+        assert_eq!(engine.state.discards_left, 1);
+    }
+
+    #[test]
+    fn failed_plays_do_not_spend_a_hand() {
+        let mut engine = Engine::with_deck(9, Vec::new(), Limits::default());
+        engine.state.hand = vec![CardInstance::new(10, "no_such_card")];
+        let hands = engine.state.hands_left;
+
+        let err = engine.dispatch(Action::PlayHand).unwrap_err();
+        assert!(matches!(err, GameError::UnknownCardDef(_)));
+        assert_eq!(engine.state.hands_left, hands);
+    }
+
     #[test]
     fn start_puzzle_sets_mode_hint_and_goal_state() {
         let mut engine = Engine::new(0);
@@ -761,6 +1969,8 @@ mod tests {
         assert_eq!(engine.state.puzzle_id.as_deref(), Some("lesson_score_ping"));
         assert_eq!(engine.state.hand.len(), 1);
         assert!(engine.state.puzzle_hint.is_some());
+        let source = engine.state.puzzle_source.as_deref().unwrap();
+        assert_eq!(source.hand_ids, vec!["tap_score".to_string()]);
     }
 
     #[test]
@@ -832,6 +2042,220 @@ mod tests {
         assert!(engine.state.bankroll >= 12);
     }
 
+    fn meta_clone_with(goals: Vec<Goal>) -> Engine {
+        let mut puzzle = PuzzleData::from(puzzles::get("lesson_meta_clone").unwrap());
+        puzzle.goals = goals;
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::LoadPuzzle {
+                puzzle: Box::new(puzzle),
+            })
+            .unwrap();
+        engine
+    }
+
+    #[test]
+    fn forbidden_function_fails_the_puzzle_mid_hand() {
+        let mut engine = meta_clone_with(vec![Goal::ForbidFunction("c".to_string())]);
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        assert!(engine.state.puzzle_failed);
+        assert!(!engine.state.puzzle_solved);
+        // The clone never ran, so only the first scorer counted.
+        assert_eq!(engine.state.score, 2);
+        // The card that broke the rule and the rest of the hand end up in the pile.
+        assert!(engine.state.pile.iter().any(|c| c.def_id == "clone_pair"));
+        assert!(
+            engine
+                .state
+                .puzzle_message
+                .as_deref()
+                .is_some_and(|m| m.contains("forbidden function `c`"))
+        );
+
+        // Forbidding the long name catches the short alias too.
+        let mut long = meta_clone_with(vec![Goal::ForbidFunction("clone".to_string())]);
+        long.dispatch(Action::PlayHand).unwrap();
+        assert!(long.state.puzzle_failed);
+        assert_eq!(long.state.score, 2);
+    }
+
+    #[test]
+    fn step_constraints_inside_any_of_are_rejected_on_load() {
+        let mut puzzle = PuzzleData::from(puzzles::get("lesson_meta_clone").unwrap());
+        puzzle.goals = vec![Goal::AnyOf(vec![
+            Goal::ExactScore(6),
+            Goal::ForbidFunction("c".to_string()),
+        ])];
+        let err = Engine::new(0)
+            .dispatch(Action::LoadPuzzle {
+                puzzle: Box::new(puzzle),
+            })
+            .unwrap_err();
+        assert!(matches!(err, GameError::InvalidGoal(_)));
+    }
+
+    #[test]
+    fn extra_goals_must_all_hold_to_solve() {
+        let mut exact = meta_clone_with(vec![Goal::ExactScore(6), Goal::MinPileSize(2)]);
+        exact.dispatch(Action::PlayHand).unwrap();
+        assert!(exact.state.puzzle_solved);
+
+        let mut missed = meta_clone_with(vec![Goal::ExactScore(5)]);
+        missed.dispatch(Action::PlayHand).unwrap();
+        assert!(!missed.state.puzzle_solved);
+        assert!(missed.state.puzzle_failed);
+
+        let mut capped = meta_clone_with(vec![Goal::MaxCards(1)]);
+        let hand = capped.state.hand.len();
+        capped.dispatch(Action::PlayHand).unwrap();
+        assert!(capped.state.puzzle_failed);
+        assert_eq!(capped.state.score, 0);
+        // The hand never ran, but its cards stay visible in the pile.
+        assert_eq!(capped.state.pile.len(), hand);
+    }
+
+    #[derive(Default)]
+    struct Recorder(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl EngineObserver for Recorder {
+        fn on_card_started(&mut self, card_id: u64, def_id: &str, _index: usize) {
+            self.0
+                .borrow_mut()
+                .push(format!("start {card_id} {def_id}"));
+        }
+        fn on_effect_applied(&mut self, effect: &Effect) {
+            self.0.borrow_mut().push(format!("effect {effect:?}"));
+        }
+        fn on_card_drawn(&mut self, card: &CardInstance) {
+            self.0.borrow_mut().push(format!("drawn {}", card.def_id));
+        }
+        fn on_level_cleared(&mut self, level: u32, _score: i64) {
+            self.0.borrow_mut().push(format!("cleared {level}"));
+        }
+        fn on_puzzle_solved(&mut self, id: &str, stars: u8) {
+            self.0.borrow_mut().push(format!("solved {id} {stars}"));
+        }
+    }
+
+    #[test]
+    fn observers_see_typed_events_in_order_and_survive_puzzle_loads() {
+        let mut engine = Engine::new(0);
+        let recorder = Recorder::default();
+        let log = recorder.0.clone();
+        let id = engine.subscribe(recorder);
+        let events = std::rc::Rc::new(std::cell::Cell::new(0));
+        let seen = events.clone();
+        let counter = engine.subscribe(move |_: &TraceEvent| seen.set(seen.get() + 1));
+        assert_eq!(engine.clone().observer_count(), 0);
+
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_meta_clone".to_string(),
+            })
+            .unwrap();
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let log = log.borrow().clone();
+        assert!(log.first().unwrap().starts_with("start "), "{log:?}");
+        assert!(log.iter().any(|l| l.starts_with("effect AddScore")));
+        assert_eq!(log.last().unwrap(), "solved lesson_meta_clone 3");
+        // The StartPuzzle action went to the trace that the puzzle replaced.
+        assert_eq!(events.get(), engine.state.trace.len() + 1);
+        assert_eq!(engine.observer_count(), 2);
+        assert!(engine.unsubscribe(id).is_some());
+        assert!(engine.unsubscribe(id).is_none());
+        assert!(engine.unsubscribe(counter).is_some());
+    }
+
+    #[test]
+    fn observers_hear_draws_and_level_clears() {
+        let deck = vec![CardInstance::new(1, "tap_score")];
+        let mut engine = Engine::with_deck(4, deck, Limits::default());
+        let recorder = Recorder::default();
+        let log = recorder.0.clone();
+        engine.subscribe(recorder);
+
+        engine
+            .dispatch(Action::DrawToCollection { count: 1 })
+            .unwrap();
+        engine.state.score = engine.state.target_score;
+        engine.state.hand = vec![CardInstance::new(2, "tap_bank")];
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let log = log.borrow();
+        assert_eq!(log.first().unwrap(), "drawn tap_score");
+        assert_eq!(log.last().unwrap(), "cleared 1");
+    }
+
+    #[test]
+    fn mutating_a_card_leaves_later_shuffles_unchanged() {
+        let reshuffled = |second: &str| {
+            let mut engine = Engine::with_deck(9, Vec::new(), Limits::default());
+            engine.state.hand = vec![
+                CardInstance::new(100, "tap_score"),
+                CardInstance::new(101, second),
+            ];
+            engine.dispatch(Action::PlayHand).unwrap();
+            engine.state.pile = (1..=8)
+                .map(|id| CardInstance::new(id, "tap_bank"))
+                .collect();
+            engine
+                .dispatch(Action::DrawToCollection { count: 8 })
+                .unwrap();
+            engine
+                .state
+                .collection
+                .iter()
+                .map(|c| c.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(reshuffled("mutator"), reshuffled("tap_bank"));
+    }
+
+    fn played_order(hand: &[&str]) -> (Vec<String>, Engine) {
+        let mut engine = Engine::with_deck(2, Vec::new(), Limits::default());
+        engine.state.target_score = 1_000;
+        engine.state.hand = hand
+            .iter()
+            .enumerate()
+            .map(|(i, id)| CardInstance::new(i as u64 + 1, *id))
+            .collect();
+        engine.dispatch(Action::PlayHand).unwrap();
+        let order = engine
+            .state
+            .history
+            .iter()
+            .map(|h| h.def_id.clone())
+            .collect();
+        (order, engine)
+    }
+
+    #[test]
+    fn queue_builtins_reorder_and_skip_the_rest_of_the_hand() {
+        let (order, _) = played_order(&["queue_flip", "tap_score", "tap_bank", "pulse_score"]);
+        assert_eq!(
+            order,
+            ["queue_flip", "pulse_score", "tap_bank", "tap_score"]
+        );
+
+        // p(U) pulls the last of three queued cards forward, then z() swaps the front pair.
+        let (order, _) = played_order(&["queue_pull", "tap_score", "tap_bank", "pulse_score"]);
+        assert_eq!(
+            order,
+            ["queue_pull", "tap_score", "pulse_score", "tap_bank"]
+        );
+
+        let (order, engine) = played_order(&["queue_skip", "tap_score", "tap_bank"]);
+        assert_eq!(order, ["queue_skip", "tap_bank"]);
+        assert!(engine.state.pile.iter().any(|c| c.id == 2));
+        assert!(engine.state.trace.iter().any(|e| matches!(
+            e,
+            TraceEvent::QueueChanged { op, queue, skipped }
+                if op == "skip" && queue == &[3] && skipped == &[2]
+        )));
+    }
+
     #[test]
     fn puzzle_draw_math_reaches_expected_score() {
         let mut engine = Engine::new(0);
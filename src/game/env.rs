@@ -0,0 +1,371 @@
+//! A Gym-style training environment around `Engine`.
+//!
+//! Actions are integers in `0..ACTION_COUNT` and observations are fixed-size `f32` arrays, so
+//! an agent never needs to know about `Action` or `GameState`. The engine runs in strict mode:
+//! an illegal action leaves the game untouched and costs `ILLEGAL_PENALTY`.
+
+use serde::Serialize;
+
+use crate::game::cards::CardKind;
+use crate::game::config::RunConfig;
+use crate::game::shop;
+use crate::game::trace::TracePolicy;
+use crate::game::{Action, CardInstance, Engine, GameState, Phase};
+
+/// Hand and Code Bank slots that can be observed and addressed.
+pub const MAX_SLOTS: usize = 16;
+
+/// Reward for an action the engine rejects.
+pub const ILLEGAL_PENALTY: f64 = -0.1;
+
+/// Steps after which an episode is cut off, even if the run goes on.
+pub const DEFAULT_MAX_STEPS: u32 = 2_000;
+
+const REGISTERS: usize = 8;
+const PHASES: usize = 4;
+const KINDS: usize = 4;
+/// Per zone: total cards, then one count per card kind.
+const ZONE_FEATURES: usize = 1 + KINDS;
+/// Per slot: kind code (0 when empty) and upgrade level.
+const SLOT_FEATURES: usize = 2;
+/// Per offer: kind code, price, sold.
+const OFFER_FEATURES: usize = 3;
+const SHOP_FEATURES: usize = shop::OFFER_COUNT * OFFER_FEATURES + 5;
+
+/// Length of every observation.
+///
+/// Layout: registers (score, bankroll, acc, level, target, hands left, discards left, turn),
+/// a one-hot phase, zone counts for the draw pile, hand, Code Bank and discard pile, hand and
+/// Code Bank slots, then the shop (offers, reroll, pack, pack bought, relic, removal prices).
+pub const OBS_LEN: usize =
+    REGISTERS + PHASES + 4 * ZONE_FEATURES + 2 * MAX_SLOTS * SLOT_FEATURES + SHOP_FEATURES;
+
+pub type Observation = [f32; OBS_LEN];
+
+const MOVE_TO_HAND: usize = 2;
+const MOVE_TO_BANK: usize = MOVE_TO_HAND + MAX_SLOTS;
+const DISCARD: usize = MOVE_TO_BANK + MAX_SLOTS;
+const BUY_CARD: usize = DISCARD + MAX_SLOTS;
+const REROLL: usize = BUY_CARD + shop::OFFER_COUNT;
+const BUY_PACK: usize = REROLL + 1;
+const BUY_RELIC: usize = BUY_PACK + 1;
+const REMOVE: usize = BUY_RELIC + 1;
+const UPGRADE: usize = REMOVE + MAX_SLOTS;
+const LEAVE_SHOP: usize = UPGRADE + MAX_SLOTS;
+
+/// Size of the discrete action space.
+///
+/// Hand order is set by the order cards are moved in, so there are no reorder actions.
+pub const ACTION_COUNT: usize = LEAVE_SHOP + 1;
+
+/// The `Action` behind a discrete action index.
+pub fn action_for(index: usize) -> Option<Action> {
+    let slot = |base: usize| index - base;
+    Some(match index {
+        0 => Action::PlayHand,
+        1 => Action::DrawToCollection { count: 1 },
+        i if i < MOVE_TO_BANK => Action::MoveCollectionToHand {
+            index: slot(MOVE_TO_HAND),
+        },
+        i if i < DISCARD => Action::MoveHandToCollection {
+            index: slot(MOVE_TO_BANK),
+        },
+        i if i < BUY_CARD => Action::Discard {
+            indices: vec![slot(DISCARD)],
+        },
+        i if i < REROLL => Action::BuyCard {
+            index: slot(BUY_CARD),
+        },
+        REROLL => Action::RerollShop,
+        BUY_PACK => Action::BuyPack,
+        BUY_RELIC => Action::BuyRelic,
+        i if i < UPGRADE => Action::RemoveCard {
+            index: slot(REMOVE),
+        },
+        i if i < LEAVE_SHOP => Action::UpgradeCard {
+            index: slot(UPGRADE),
+        },
+        LEAVE_SHOP => Action::LeaveShop,
+        _ => return None,
+    })
+}
+
+/// The discrete index of `action`, if the action space covers it.
+pub fn index_of(action: &Action) -> Option<usize> {
+    let slot = |base: usize, i: usize| (i < MAX_SLOTS).then_some(base + i);
+    match action {
+        Action::PlayHand => Some(0),
+        Action::DrawToCollection { count: 1 } => Some(1),
+        Action::MoveCollectionToHand { index } => slot(MOVE_TO_HAND, *index),
+        Action::MoveHandToCollection { index } => slot(MOVE_TO_BANK, *index),
+        Action::Discard { indices } if indices.len() == 1 => slot(DISCARD, indices[0]),
+        Action::BuyCard { index } if *index < shop::OFFER_COUNT => Some(BUY_CARD + index),
+        Action::RerollShop => Some(REROLL),
+        Action::BuyPack => Some(BUY_PACK),
+        Action::BuyRelic => Some(BUY_RELIC),
+        Action::RemoveCard { index } => slot(REMOVE, *index),
+        Action::UpgradeCard { index } => slot(UPGRADE, *index),
+        Action::LeaveShop => Some(LEAVE_SHOP),
+        _ => None,
+    }
+}
+
+/// Extra facts about a step, for logging rather than learning.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepInfo {
+    pub legal: bool,
+    pub error: Option<String>,
+    pub level: u32,
+    pub score: i64,
+    pub target: i64,
+    pub bankroll: i64,
+    pub phase: Phase,
+    pub steps: u32,
+    /// The episode hit `max_steps` rather than ending the run.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub obs: Observation,
+    pub reward: f64,
+    pub done: bool,
+    pub info: StepInfo,
+}
+
+/// One Classic run at a time, driven by discrete actions.
+#[derive(Debug, Clone)]
+pub struct Env {
+    engine: Engine,
+    config: RunConfig,
+    steps: u32,
+    max_steps: u32,
+}
+
+impl Env {
+    pub fn new(config: RunConfig) -> Self {
+        let mut env = Self {
+            engine: Engine::new(0),
+            config,
+            steps: 0,
+            max_steps: DEFAULT_MAX_STEPS,
+        };
+        env.reset(0);
+        env
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Starts a fresh run; the same seed always gives the same episode.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.engine = Engine::with_config(seed, self.config.clone());
+        self.engine.set_strict(true);
+        // Agents never read the trace; keep only the current hand.
+        self.engine
+            .state
+            .trace
+            .set_policy(TracePolicy::LastHands(1));
+        self.steps = 0;
+        self.observe()
+    }
+
+    pub fn step(&mut self, index: usize) -> StepResult {
+        let before = progress(&self.engine.state);
+        let outcome = match action_for(index) {
+            Some(action) => self.engine.dispatch(action).map_err(|e| e.to_string()),
+            None => Err(format!("action {index} is outside 0..{ACTION_COUNT}")),
+        };
+        self.steps = self.steps.saturating_add(1);
+
+        let state = &self.engine.state;
+        let reward = match outcome {
+            Ok(()) => progress(state) - before,
+            Err(_) => ILLEGAL_PENALTY,
+        };
+        let over = state.phase == Phase::GameOver;
+        let truncated = !over && self.steps >= self.max_steps;
+        StepResult {
+            obs: self.observe(),
+            reward,
+            done: over || truncated,
+            info: StepInfo {
+                legal: outcome.is_ok(),
+                error: outcome.err(),
+                level: state.level,
+                score: state.score,
+                target: state.target_score,
+                bankroll: state.bankroll,
+                phase: state.phase,
+                steps: self.steps,
+                truncated,
+            },
+        }
+    }
+
+    /// Which action indices the engine would accept right now.
+    pub fn action_mask(&self) -> [bool; ACTION_COUNT] {
+        let mut mask = [false; ACTION_COUNT];
+        for (index, allowed) in mask.iter_mut().enumerate() {
+            *allowed = action_for(index).is_some_and(|a| self.engine.is_legal(&a));
+        }
+        mask
+    }
+
+    pub fn observe(&self) -> Observation {
+        let mut obs = [0.0; OBS_LEN];
+        encode(&self.engine.state, &mut obs);
+        obs
+    }
+}
+
+/// Levels cleared plus progress towards the current target; rewards are its change.
+fn progress(state: &GameState) -> f64 {
+    let target = state.target_score.max(1) as f64;
+    let toward = (state.score.max(0) as f64 / target).min(1.0);
+    f64::from(state.level.saturating_sub(1)) + toward
+}
+
+fn encode(state: &GameState, obs: &mut Observation) {
+    let mut out = obs.iter_mut();
+    let mut put = |value: f32| {
+        if let Some(slot) = out.next() {
+            *slot = value;
+        }
+    };
+
+    for value in [
+        state.score,
+        state.bankroll,
+        state.acc,
+        i64::from(state.level),
+        state.target_score,
+        i64::from(state.hands_left),
+        i64::from(state.discards_left),
+        i64::from(state.turn),
+    ] {
+        put(value as f32);
+    }
+    for phase in [Phase::InLevel, Phase::Shop, Phase::Reward, Phase::GameOver] {
+        put(f32::from(u8::from(state.phase == phase)));
+    }
+    for zone in [&state.deck, &state.hand, &state.collection, &state.pile] {
+        let mut counts = [0u32; KINDS];
+        for card in zone.iter() {
+            if let Some(code) = kind_code(card) {
+                counts[code - 1] += 1;
+            }
+        }
+        put(zone.len() as f32);
+        for count in counts {
+            put(count as f32);
+        }
+    }
+    for zone in [&state.hand, &state.collection] {
+        for slot in 0..MAX_SLOTS {
+            let card = zone.get(slot);
+            put(card.and_then(kind_code).unwrap_or(0) as f32);
+            put(card.map_or(0, |c| c.upgrade.level) as f32);
+        }
+    }
+
+    let shop = state.shop.as_ref();
+    for slot in 0..shop::OFFER_COUNT {
+        let offer = shop.and_then(|s| s.offers.get(slot));
+        let code = offer
+            .and_then(|o| crate::game::cards::get(&o.def_id))
+            .map_or(0, |d| kind_index(d.kind) + 1);
+        put(code as f32);
+        put(offer.map_or(0, |o| o.price) as f32);
+        put(f32::from(u8::from(offer.is_some_and(|o| o.sold))));
+    }
+    let relic_price = shop
+        .and_then(|s| s.relic.as_ref())
+        .filter(|r| !r.sold)
+        .map_or(0, |r| r.price);
+    for value in [
+        shop.map_or(0, |s| s.reroll_cost),
+        shop.map_or(0, |s| s.pack_cost),
+        i64::from(shop.is_some_and(|s| s.pack_bought)),
+        relic_price,
+        shop.map_or(0, |s| s.removal_cost),
+    ] {
+        put(value as f32);
+    }
+}
+
+/// 1-based card kind, so 0 can mean "no card".
+fn kind_code(card: &CardInstance) -> Option<usize> {
+    card.effective_def().map(|d| kind_index(d.kind) + 1)
+}
+
+fn kind_index(kind: CardKind) -> usize {
+    match kind {
+        CardKind::Economy => 0,
+        CardKind::Score => 1,
+        CardKind::Control => 2,
+        CardKind::Meta => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_indices_round_trip() {
+        for index in 0..ACTION_COUNT {
+            let action = action_for(index).expect("every index maps to an action");
+            assert_eq!(index_of(&action), Some(index), "{action:?}");
+        }
+        assert_eq!(action_for(ACTION_COUNT), None);
+        assert_eq!(index_of(&Action::ClearTrace), None);
+    }
+
+    #[test]
+    fn episodes_are_deterministic_and_mask_matches_legality() {
+        let play = |seed| {
+            let mut env = Env::new(RunConfig::standard());
+            let mut obs = vec![env.reset(seed)];
+            let mut rewards = Vec::new();
+            for _ in 0..200 {
+                let mask = env.action_mask();
+                // Always take the highest legal index: shop purchases before playing.
+                let index = (0..ACTION_COUNT).rev().find(|&i| mask[i]).unwrap_or(0);
+                let step = env.step(index);
+                assert!(step.info.legal, "{:?}", step.info.error);
+                obs.push(step.obs);
+                rewards.push(step.reward);
+                if step.done {
+                    break;
+                }
+            }
+            (obs, rewards)
+        };
+        assert_eq!(play(7), play(7));
+        assert_ne!(play(7).0, play(8).0);
+    }
+
+    #[test]
+    fn illegal_actions_are_penalised_and_leave_the_game_alone() {
+        let mut env = Env::new(RunConfig::standard()).with_max_steps(2);
+        let obs = env.reset(3);
+        assert_eq!(obs[REGISTERS], 1.0, "a run starts in level");
+
+        let step = env.step(LEAVE_SHOP);
+        assert!(!step.info.legal);
+        assert_eq!(step.reward, ILLEGAL_PENALTY);
+        assert_eq!(step.obs, obs);
+        assert!(!step.done);
+
+        let step = env.step(ACTION_COUNT + 5);
+        assert!(step.info.error.is_some());
+        assert!(step.done && step.info.truncated);
+    }
+}
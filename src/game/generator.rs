@@ -0,0 +1,337 @@
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::game::cards;
+use crate::game::puzzles::{Par, PuzzleData};
+use crate::game::solver::{self, SolveMode, Solver, SolverLimits};
+
+/// Core mechanics a generated puzzle can be forced to exercise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mechanic {
+    Clone,
+    Again,
+    Mutate,
+    Hedge,
+    Cash,
+    Double,
+    Draw,
+    Tri,
+    Fibo,
+}
+
+impl Mechanic {
+    /// Canonical function name (see `cards::function_visual`).
+    pub fn canonical(self) -> &'static str {
+        match self {
+            Mechanic::Clone => "clone",
+            Mechanic::Again => "again",
+            Mechanic::Mutate => "mutate",
+            Mechanic::Hedge => "hedge",
+            Mechanic::Cash => "cash",
+            Mechanic::Double => "dbl",
+            Mechanic::Draw => "draw",
+            Mechanic::Tri => "tri",
+            Mechanic::Fibo => "fibo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    /// Level the puzzle starts at (drives generation rules and the `L` register).
+    pub level: u32,
+    /// Cards dealt across hand and collection.
+    pub card_count: usize,
+    /// Cards placed in the source pile for draws.
+    pub source_count: usize,
+    pub play_limit: u32,
+    /// Every listed mechanic must appear in the unique solution.
+    pub required: Vec<Mechanic>,
+    /// Also require a bankroll goal alongside the score target.
+    pub bankroll_goal: bool,
+    /// Setups sampled before giving up.
+    pub max_attempts: usize,
+    /// Per-attempt search cap; attempts that would need more are rejected.
+    pub max_nodes: usize,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            level: 1,
+            card_count: 3,
+            source_count: 4,
+            play_limit: 1,
+            required: Vec::new(),
+            bankroll_goal: false,
+            max_attempts: 200,
+            max_nodes: 20_000,
+        }
+    }
+}
+
+/// Sample setups until one has exactly one minimal solution, then package it as a puzzle.
+///
+/// Returns `None` if no attempt within `max_attempts` qualifies.
+pub fn generate(config: &GeneratorConfig) -> Option<PuzzleData> {
+    let mut rng = ChaCha8Rng::seed_from_u64(config.seed);
+    for attempt in 0..config.max_attempts {
+        let draft = sample_setup(config, &mut rng, attempt);
+        if let Some(puzzle) = finalize(config, draft) {
+            return Some(puzzle);
+        }
+    }
+    None
+}
+
+fn sample_setup(config: &GeneratorConfig, rng: &mut ChaCha8Rng, attempt: usize) -> PuzzleData {
+    let level = config.level.max(1);
+    let card_count = config.card_count.max(config.required.len()).max(1);
+
+    let mut dealt: Vec<String> = config
+        .required
+        .iter()
+        .filter_map(|m| {
+            let options = cards::catalog()
+                .iter()
+                .filter(|c| {
+                    cards::script_function_visuals(c.script)
+                        .iter()
+                        .any(|fx| fx.canonical == m.canonical())
+                })
+                .collect::<Vec<_>>();
+            options.choose(rng).map(|c| c.id.to_string())
+        })
+        .collect();
+    while dealt.len() < card_count {
+        match cards::roll_card_id_for_level(rng, level) {
+            Some(id) => dealt.push(id.to_string()),
+            None => break,
+        }
+    }
+    // Shuffle so required cards are not always first.
+    for i in (1..dealt.len()).rev() {
+        dealt.swap(i, rng.random_range(0..=i));
+    }
+
+    let hand_len = rng.random_range(1..=dealt.len().max(1));
+    let collection_ids = dealt.split_off(hand_len.min(dealt.len()));
+    let source_ids = (0..config.source_count)
+        .filter_map(|_| cards::roll_card_id_for_level(rng, level).map(str::to_string))
+        .collect();
+
+    let theme = ["terminal", "crt", "magic"]
+        .choose(rng)
+        .copied()
+        .unwrap_or("terminal");
+
+    PuzzleData {
+        id: format!("gen_{:016x}_{attempt}", config.seed),
+        name: String::new(),
+        blurb: String::new(),
+        hint: String::new(),
+        theme: theme.to_string(),
+        start_bankroll: rng.random_range(4..=12),
+        start_score: 0,
+        start_level: level,
+        // Unreachable while exploring; replaced with a real target below.
+        target_score: i64::MAX,
+        goal_bankroll: None,
+        play_limit: None,
+        source_ids,
+        collection_ids,
+        hand_ids: dealt,
+        solution: Vec::new(),
+        par: Par::NONE,
+        goals: Vec::new(),
+    }
+}
+
+fn finalize(config: &GeneratorConfig, mut draft: PuzzleData) -> Option<PuzzleData> {
+    // Search hands as large as what was dealt: uniqueness must hold for every hand the player
+    // can build.
+    let dealt = draft.hand_ids.len() + draft.collection_ids.len();
+    let solver = Solver::new(SolveMode::Minimal).with_limits(SolverLimits {
+        max_plays: config.play_limit.max(1),
+        max_hand: dealt.max(1),
+        max_nodes: config.max_nodes,
+    });
+
+    // Explore every line with an unreachable target and aim at the best outcome.
+    let probe = solver::puzzle_engine_for(&draft).ok()?;
+    let mut best: Option<(i64, i64)> = None;
+    let truncated = solver.explore(&probe, |_, end| {
+        let outcome = (end.state.score, end.state.bankroll);
+        if best.is_none_or(|b| outcome > b) {
+            best = Some(outcome);
+        }
+    });
+    let (score, bankroll) = best?;
+    if truncated || score <= draft.start_score {
+        return None;
+    }
+
+    draft.target_score = score;
+    draft.goal_bankroll = config.bankroll_goal.then_some(bankroll);
+    draft.play_limit = Some(config.play_limit.max(1));
+
+    let report = solver.solve(&solver::puzzle_engine_for(&draft).ok()?);
+    if report.truncated || report.solutions.len() != 1 {
+        return None;
+    }
+    let line = report.solutions.into_iter().next()?.plays;
+
+    let used = line
+        .iter()
+        .flatten()
+        .filter_map(|id| cards::get(id))
+        .flat_map(|c| cards::script_function_visuals(c.script))
+        .map(|fx| fx.canonical)
+        .collect::<Vec<_>>();
+    if !config
+        .required
+        .iter()
+        .all(|m| used.contains(&m.canonical()))
+    {
+        return None;
+    }
+
+    draft.name = format!("Generated {:04}", config.seed % 10_000);
+    draft.blurb = blurb_for(config, &draft);
+    draft.hint = hint_for(&line);
+    draft.par = Par::from_solution(&line);
+    draft.solution = line;
+    Some(draft)
+}
+
+fn blurb_for(config: &GeneratorConfig, puzzle: &PuzzleData) -> String {
+    let mut blurb = format!("Reach {} score", puzzle.target_score);
+    if let Some(goal) = puzzle.goal_bankroll {
+        blurb.push_str(&format!(" with at least ${goal} left"));
+    }
+    let plays = config.play_limit.max(1);
+    blurb.push_str(&format!(" in {plays} play(s)."));
+    if !config.required.is_empty() {
+        let names = config
+            .required
+            .iter()
+            .map(|m| m.canonical())
+            .collect::<Vec<_>>()
+            .join(", ");
+        blurb.push_str(&format!(" Uses: {names}."));
+    }
+    blurb
+}
+
+/// Human-readable line in the same style as the hand-written hints.
+fn hint_for(line: &[Vec<String>]) -> String {
+    let play_text = |play: &Vec<String>| {
+        play.iter()
+            .map(|id| cards::get(id).map(|c| c.name).unwrap_or(id.as_str()))
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    };
+    match line {
+        [only] => format!("The intended line is `{}`.", play_text(only)),
+        _ => {
+            let plays = line
+                .iter()
+                .enumerate()
+                .map(|(i, play)| format!("play {}: `{}`", i + 1, play_text(play)))
+                .collect::<Vec<_>>()
+                .join("; ");
+            format!("The intended line is {plays}.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn as_refs(line: &[Vec<String>]) -> Vec<Vec<&str>> {
+        line.iter()
+            .map(|play| play.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn generated_puzzle_has_exactly_one_minimal_solution() {
+        let puzzle = generate(&GeneratorConfig::default()).expect("generator should succeed");
+        let report =
+            Solver::new(SolveMode::Minimal).solve(&solver::puzzle_engine_for(&puzzle).unwrap());
+        assert_eq!(report.solutions.len(), 1);
+        assert_eq!(report.solutions[0].plays, puzzle.solution);
+    }
+
+    #[test]
+    fn generation_is_deterministic_per_seed() {
+        let config = GeneratorConfig {
+            seed: 42,
+            ..GeneratorConfig::default()
+        };
+        assert_eq!(generate(&config), generate(&config));
+    }
+
+    #[test]
+    fn generated_hint_line_solves_the_puzzle() {
+        let puzzle = generate(&GeneratorConfig {
+            seed: 7,
+            ..GeneratorConfig::default()
+        })
+        .unwrap();
+        let line = as_refs(&puzzle.solution);
+        let plays = line.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let engine = solver::puzzle_engine_for(&puzzle).unwrap();
+        assert!(solver::replay_line(engine, &plays).unwrap());
+
+        let first = cards::get(&puzzle.solution[0][0]).unwrap();
+        assert!(puzzle.hint.contains(first.name));
+    }
+
+    #[test]
+    fn required_mechanics_appear_in_the_solution() {
+        let puzzle = generate(&GeneratorConfig {
+            seed: 3,
+            required: vec![Mechanic::Clone],
+            ..GeneratorConfig::default()
+        })
+        .expect("clone puzzle should be generated");
+        let uses_clone = puzzle.solution.iter().flatten().any(|id| {
+            cards::get(id).is_some_and(|c| {
+                cards::script_function_visuals(c.script)
+                    .iter()
+                    .any(|fx| fx.canonical == "clone")
+            })
+        });
+        assert!(uses_clone);
+    }
+
+    #[test]
+    fn required_mechanics_can_deal_more_than_card_count() {
+        let puzzle = generate(&GeneratorConfig {
+            seed: 5,
+            card_count: 1,
+            required: vec![Mechanic::Draw, Mechanic::Tri],
+            ..GeneratorConfig::default()
+        })
+        .expect("two-mechanic puzzle should be generated");
+        assert_eq!(puzzle.hand_ids.len() + puzzle.collection_ids.len(), 2);
+        assert_eq!(puzzle.solution.concat().len(), 2);
+    }
+
+    #[test]
+    fn bankroll_goal_is_set_when_requested() {
+        let puzzle = generate(&GeneratorConfig {
+            seed: 11,
+            bankroll_goal: true,
+            ..GeneratorConfig::default()
+        })
+        .unwrap();
+        assert!(puzzle.goal_bankroll.is_some());
+        assert_eq!(puzzle.play_limit, Some(1));
+    }
+}
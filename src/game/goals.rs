@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GameState, cards};
+
+/// One condition of a puzzle goal.
+///
+/// Final-state goals are checked after every play and must all hold for the puzzle to be
+/// solved. Step constraints (`MaxCards`, `ForbidFunction`, `SolventEveryStep`) are checked
+/// while the hand runs and fail the puzzle as soon as they are broken, so they cannot be
+/// alternatives inside `AnyOf` (see [`check`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    ScoreAtLeast(i64),
+    ExactScore(i64),
+    BankrollAtLeast(i64),
+    AccEquals(i64),
+    MinPileSize(usize),
+    EmptyCollection,
+    /// At most this many cards played from the hand over the whole puzzle.
+    MaxCards(u32),
+    /// Never call this builtin, by long or short name (`"clone"` and `"c"` are the same).
+    ForbidFunction(String),
+    /// Bankroll never drops below zero after any effect.
+    SolventEveryStep,
+    /// Met when any listed final-state goal is met.
+    AnyOf(Vec<Goal>),
+}
+
+/// What just happened inside a hand, for step constraints.
+#[derive(Debug, Clone, Copy)]
+pub enum Step<'a> {
+    /// A hand is about to run; cards-used counts are already updated.
+    HandStart,
+    /// A builtin is about to be called.
+    Call(&'a str),
+    /// An effect was just applied.
+    Effect,
+}
+
+impl Goal {
+    pub fn is_step_constraint(&self) -> bool {
+        matches!(
+            self,
+            Goal::MaxCards(_) | Goal::ForbidFunction(_) | Goal::SolventEveryStep
+        )
+    }
+
+    /// Whether the final-state part of this goal holds. Step constraints are always met here;
+    /// breaking them is reported by [`Goal::violation`] instead.
+    pub fn is_met(&self, state: &GameState) -> bool {
+        match self {
+            Goal::ScoreAtLeast(n) => state.score >= *n,
+            Goal::ExactScore(n) => state.score == *n,
+            Goal::BankrollAtLeast(n) => state.bankroll >= *n,
+            Goal::AccEquals(n) => state.acc == *n,
+            Goal::MinPileSize(n) => state.pile.len() >= *n,
+            Goal::EmptyCollection => state.collection.is_empty(),
+            Goal::MaxCards(_) | Goal::ForbidFunction(_) | Goal::SolventEveryStep => true,
+            Goal::AnyOf(goals) => goals.iter().any(|g| g.is_met(state)),
+        }
+    }
+
+    /// Reason this goal is broken by `step`, if it is.
+    pub fn violation(&self, state: &GameState, step: Step<'_>) -> Option<String> {
+        match (self, step) {
+            (Goal::MaxCards(n), Step::HandStart) if state.puzzle_cards_used > *n => Some(format!(
+                "used {} cards (at most {n})",
+                state.puzzle_cards_used
+            )),
+            (Goal::ForbidFunction(name), Step::Call(call))
+                if cards::canonical_function(call) == cards::canonical_function(name) =>
+            {
+                Some(format!("called forbidden function `{call}`"))
+            }
+            (Goal::SolventEveryStep, Step::Effect) if state.bankroll < 0 => {
+                Some(format!("bankroll dropped to {}", state.bankroll))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        match self {
+            Goal::ScoreAtLeast(n) => format!("score >= {n}"),
+            Goal::ExactScore(n) => format!("score exactly {n}"),
+            Goal::BankrollAtLeast(n) => format!("bank >= {n}"),
+            Goal::AccEquals(n) => format!("acc = {n}"),
+            Goal::MinPileSize(n) => format!("pile has {n}+ cards"),
+            Goal::EmptyCollection => "empty Code Bank".to_string(),
+            Goal::MaxCards(n) => format!("use at most {n} cards"),
+            Goal::ForbidFunction(name) => format!("never call {name}()"),
+            Goal::SolventEveryStep => "bank never below 0".to_string(),
+            Goal::AnyOf(goals) => format!(
+                "one of ({})",
+                goals.iter().map(Goal::text).collect::<Vec<_>>().join(" | ")
+            ),
+        }
+    }
+}
+
+/// Reject goal lists the checker cannot honour: step constraints nested in `AnyOf`.
+pub fn check(goals: &[Goal]) -> Result<(), String> {
+    for goal in goals {
+        if let Goal::AnyOf(inner) = goal {
+            if let Some(step) = inner.iter().find(|g| g.is_step_constraint()) {
+                return Err(format!(
+                    "`{}` is checked while the hand runs and cannot be an alternative in any_of",
+                    step.text()
+                ));
+            }
+            check(inner)?;
+        }
+    }
+    Ok(())
+}
+
+/// Readable summary of a goal list, e.g. `score >= 10 and never call c()`.
+pub fn describe(goals: &[Goal]) -> String {
+    goals
+        .iter()
+        .map(Goal::text)
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Every goal the active puzzle checks: the score target, the bankroll goal and any extras.
+pub fn active(state: &GameState) -> Vec<Goal> {
+    let mut goals = vec![Goal::ScoreAtLeast(state.target_score)];
+    goals.extend(state.puzzle_bankroll_goal.map(Goal::BankrollAtLeast));
+    goals.extend(state.puzzle_goals.iter().cloned());
+    goals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Limits;
+
+    fn state() -> GameState {
+        GameState::new(Vec::new(), Limits::default())
+    }
+
+    #[test]
+    fn final_goals_read_the_state() {
+        let mut s = state();
+        s.score = 7;
+        s.acc = 3;
+        assert!(Goal::ExactScore(7).is_met(&s));
+        assert!(!Goal::ExactScore(6).is_met(&s));
+        assert!(Goal::AccEquals(3).is_met(&s));
+        assert!(!Goal::MinPileSize(1).is_met(&s));
+        assert!(Goal::AnyOf(vec![Goal::MinPileSize(1), Goal::AccEquals(3)]).is_met(&s));
+        assert!(Goal::ForbidFunction("c".to_string()).is_met(&s));
+    }
+
+    #[test]
+    fn step_constraints_report_violations() {
+        let mut s = state();
+        let forbid = Goal::ForbidFunction("c".to_string());
+        assert!(forbid.violation(&s, Step::Call("s")).is_none());
+        assert!(forbid.violation(&s, Step::Call("c")).is_some());
+        // Aliases match either way round.
+        assert!(forbid.violation(&s, Step::Call("clone")).is_some());
+        let forbid_long = Goal::ForbidFunction("clone".to_string());
+        assert!(forbid_long.violation(&s, Step::Call("c")).is_some());
+        assert!(forbid_long.violation(&s, Step::Call("again")).is_none());
+
+        s.bankroll = -1;
+        assert!(Goal::SolventEveryStep.violation(&s, Step::Effect).is_some());
+        assert!(
+            Goal::SolventEveryStep
+                .violation(&s, Step::HandStart)
+                .is_none()
+        );
+
+        s.puzzle_cards_used = 3;
+        assert!(Goal::MaxCards(2).violation(&s, Step::HandStart).is_some());
+    }
+
+    #[test]
+    fn step_constraints_cannot_be_alternatives() {
+        let nested = Goal::AnyOf(vec![
+            Goal::ExactScore(3),
+            Goal::AnyOf(vec![Goal::MaxCards(2)]),
+        ]);
+        assert!(check(&[nested]).unwrap_err().contains("at most 2 cards"));
+        assert!(check(&[Goal::MaxCards(2), Goal::AnyOf(vec![Goal::EmptyCollection])]).is_ok());
+    }
+
+    #[test]
+    fn goals_read_as_text_and_parse_from_data() {
+        let goals: Vec<Goal> = serde_json::from_str(
+            r#"[{"exact_score": 12}, {"forbid_function": "c"}, "empty_collection"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            describe(&goals),
+            "score exactly 12 and never call c() and empty Code Bank"
+        );
+    }
+}
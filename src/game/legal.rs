@@ -0,0 +1,296 @@
+use crate::game::shop::ShopState;
+use crate::game::upgrades::{self, Edition};
+use crate::game::{Action, CardInstance, Engine, GameError, Phase, RunMode};
+
+impl Engine {
+    /// Every concrete action that would do something right now.
+    ///
+    /// Run, puzzle and trace actions are always allowed and are not listed. `DrawToCollection`
+    /// is listed with a count of 1 and `Discard` once per single card; larger counts and index
+    /// sets are legal too whenever `check_action` accepts them.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let state = &self.state;
+        let hand = state.hand.len();
+        let collection = state.collection.len();
+        let offers = state.shop.as_ref().map_or(0, |shop| shop.offers.len());
+
+        let mut candidates = vec![Action::PlayHand, Action::DrawToCollection { count: 1 }];
+        candidates.extend((0..collection).map(|index| Action::MoveCollectionToHand { index }));
+        candidates.extend((0..hand).map(|index| Action::MoveHandToCollection { index }));
+        for from in 0..hand {
+            for to in 0..hand {
+                candidates.push(Action::ReorderHand { from, to });
+                if from < to {
+                    candidates.push(Action::SwapHand { a: from, b: to });
+                }
+            }
+        }
+        for from in 0..collection {
+            for to in 0..collection {
+                candidates.push(Action::ReorderCollection { from, to });
+                if from < to {
+                    candidates.push(Action::SwapCollection { a: from, b: to });
+                }
+            }
+        }
+        candidates.extend((0..collection).map(|i| Action::Discard { indices: vec![i] }));
+        candidates.extend((0..offers).map(|index| Action::BuyCard { index }));
+        candidates.extend([Action::RerollShop, Action::BuyPack, Action::BuyRelic]);
+        candidates.extend((0..collection).map(|index| Action::RemoveCard { index }));
+        candidates.extend((0..collection).map(|index| Action::UpgradeCard { index }));
+        candidates.push(Action::LeaveShop);
+
+        candidates.retain(|action| self.check_action(action).is_ok());
+        candidates
+    }
+
+    pub fn is_legal(&self, action: &Action) -> bool {
+        self.check_action(action).is_ok()
+    }
+
+    /// Why `action` would be rejected in strict mode, if it would be.
+    pub fn check_action(&self, action: &Action) -> Result<(), GameError> {
+        let state = &self.state;
+        match action {
+            Action::NewRun { .. }
+            | Action::NewDailyRun { .. }
+            | Action::StartPuzzle { .. }
+            | Action::LoadPuzzle { .. }
+            | Action::ClearTrace => Ok(()),
+            Action::DrawToCollection { count } => {
+                self.require_phase("draw", &[Phase::InLevel])?;
+                if *count == 0 {
+                    return Err(GameError::Unavailable("drawing zero cards".to_string()));
+                }
+                if state.source_frozen() {
+                    return Err(GameError::Unavailable(
+                        "the source is frozen this level".to_string(),
+                    ));
+                }
+                if state.deck.is_empty() && state.pile.is_empty() {
+                    return Err(GameError::NoCards);
+                }
+                Ok(())
+            }
+            Action::MoveCollectionToHand { index } => {
+                self.require_phase("move a card", ARRANGE)?;
+                in_range("Code Bank", *index, &state.collection)
+            }
+            Action::MoveHandToCollection { index } => {
+                self.require_phase("move a card", ARRANGE)?;
+                in_range("hand", *index, &state.hand)
+            }
+            Action::ReorderCollection { from, to } | Action::SwapCollection { a: from, b: to } => {
+                self.require_phase("reorder cards", ARRANGE)?;
+                distinct(*from, *to)?;
+                in_range("Code Bank", *from, &state.collection)?;
+                in_range("Code Bank", *to, &state.collection)
+            }
+            Action::ReorderHand { from, to } | Action::SwapHand { a: from, b: to } => {
+                self.require_phase("reorder cards", ARRANGE)?;
+                distinct(*from, *to)?;
+                in_range("hand", *from, &state.hand)?;
+                in_range("hand", *to, &state.hand)
+            }
+            Action::PlayHand => self.require_phase("play a hand", &[Phase::InLevel]),
+            Action::Discard { indices } => {
+                self.require_phase("discard", &[Phase::InLevel])?;
+                if state.mode != RunMode::Classic {
+                    return Err(GameError::Unavailable(
+                        "discards are for Classic runs only".to_string(),
+                    ));
+                }
+                if indices.is_empty() {
+                    return Err(GameError::Unavailable("discarding no cards".to_string()));
+                }
+                for &index in indices {
+                    in_range("Code Bank", index, &state.collection)?;
+                }
+                if state.discards_left == 0 {
+                    return Err(GameError::NoDiscardsLeft);
+                }
+                Ok(())
+            }
+            Action::BuyCard { index } => {
+                let shop = self.open_shop("buy a card")?;
+                let Some(offer) = shop.offers.get(*index) else {
+                    return Err(GameError::IndexOutOfRange {
+                        zone: "shop offers",
+                        index: *index,
+                        len: shop.offers.len(),
+                    });
+                };
+                if offer.sold {
+                    return Err(GameError::Unavailable(format!("offer {index} is sold")));
+                }
+                self.afford(offer.price)
+            }
+            Action::RerollShop => {
+                let shop = self.open_shop("reroll")?;
+                self.afford(shop.reroll_cost)
+            }
+            Action::BuyPack => {
+                let shop = self.open_shop("buy a pack")?;
+                if shop.pack_bought {
+                    return Err(GameError::Unavailable(
+                        "the pack was already opened".to_string(),
+                    ));
+                }
+                self.afford(shop.pack_cost)
+            }
+            Action::RemoveCard { index } => {
+                let shop = self.open_shop("remove a card")?;
+                in_range("Code Bank", *index, &state.collection)?;
+                let card = &state.collection[*index];
+                if card.upgrade.edition == Some(Edition::Eternal) {
+                    return Err(GameError::EternalCard(card.name()));
+                }
+                self.afford(shop.removal_cost)
+            }
+            Action::BuyRelic => {
+                let shop = self.open_shop("buy a relic")?;
+                match &shop.relic {
+                    Some(offer) if !offer.sold => self.afford(offer.price),
+                    Some(_) => Err(GameError::Unavailable("the relic is sold".to_string())),
+                    None => Err(GameError::Unavailable("no relic on offer".to_string())),
+                }
+            }
+            Action::UpgradeCard { index } => {
+                self.open_shop("upgrade a card")?;
+                in_range("Code Bank", *index, &state.collection)?;
+                let level = state.collection[*index].upgrade.level;
+                if level >= upgrades::MAX_UPGRADE_LEVEL {
+                    return Err(GameError::Unavailable(format!(
+                        "card {index} is fully upgraded"
+                    )));
+                }
+                self.afford(upgrades::upgrade_price(level))
+            }
+            Action::LeaveShop => self.open_shop("leave the shop").map(|_| ()),
+        }
+    }
+
+    fn require_phase(&self, action: &'static str, allowed: &[Phase]) -> Result<(), GameError> {
+        if allowed.contains(&self.state.phase) {
+            Ok(())
+        } else {
+            Err(GameError::WrongPhase {
+                action,
+                phase: self.state.phase,
+            })
+        }
+    }
+
+    fn open_shop(&self, action: &'static str) -> Result<&ShopState, GameError> {
+        self.require_phase(action, &[Phase::Shop])?;
+        self.state.shop.as_ref().ok_or(GameError::WrongPhase {
+            action,
+            phase: self.state.phase,
+        })
+    }
+
+    fn afford(&self, cost: i64) -> Result<(), GameError> {
+        if self.state.bankroll < cost {
+            return Err(GameError::CannotAfford {
+                cost,
+                bankroll: self.state.bankroll,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Phases in which the hand and Code Bank can be rearranged.
+const ARRANGE: &[Phase] = &[Phase::InLevel, Phase::Shop];
+
+fn in_range(zone: &'static str, index: usize, cards: &[CardInstance]) -> Result<(), GameError> {
+    if index < cards.len() {
+        return Ok(());
+    }
+    Err(GameError::IndexOutOfRange {
+        zone,
+        index,
+        len: cards.len(),
+    })
+}
+
+fn distinct(a: usize, b: usize) -> Result<(), GameError> {
+    if a == b {
+        return Err(GameError::Unavailable(
+            "moving a card onto itself".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Limits;
+
+    fn puzzle() -> Engine {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_meta_clone".to_string(),
+            })
+            .unwrap();
+        engine
+    }
+
+    #[test]
+    fn strict_mode_rejects_what_legal_actions_leaves_out() {
+        let mut engine = puzzle();
+        let legal = engine.legal_actions();
+        assert!(legal.contains(&Action::PlayHand));
+        assert!(legal.contains(&Action::MoveHandToCollection { index: 0 }));
+        assert!(!legal.contains(&Action::LeaveShop));
+        assert!(!legal.iter().any(|a| matches!(a, Action::Discard { .. })));
+
+        let bad = Action::MoveHandToCollection { index: 99 };
+        engine.dispatch(bad.clone()).unwrap();
+        engine.set_strict(true);
+        assert!(matches!(
+            engine.dispatch(bad),
+            Err(GameError::IndexOutOfRange { zone: "hand", .. })
+        ));
+        assert!(matches!(
+            engine.dispatch(Action::LeaveShop),
+            Err(GameError::WrongPhase {
+                phase: Phase::InLevel,
+                ..
+            })
+        ));
+
+        engine.dispatch(Action::PlayHand).unwrap();
+        assert!(engine.is_strict());
+        assert_eq!(engine.state.phase, Phase::Reward);
+        assert!(engine.legal_actions().is_empty());
+        assert!(engine.dispatch(Action::PlayHand).is_err());
+    }
+
+    #[test]
+    fn every_listed_shop_action_succeeds_in_strict_mode() {
+        let mut engine = Engine::with_deck(5, Vec::new(), Limits::default());
+        engine.state.collection = vec![CardInstance::new(1, "tap_score")];
+        engine.state.score = engine.state.target_score;
+        engine.state.bankroll = 40;
+        engine.dispatch(Action::PlayHand).unwrap();
+        engine.set_strict(true);
+
+        let legal = engine.legal_actions();
+        assert!(legal.contains(&Action::LeaveShop));
+        assert!(legal.contains(&Action::RerollShop));
+        for action in legal {
+            let mut copy = engine.clone();
+            copy.set_strict(true);
+            copy.dispatch(action.clone())
+                .unwrap_or_else(|e| panic!("{action:?}: {e}"));
+        }
+        engine.state.bankroll = 0;
+        assert!(matches!(
+            engine.dispatch(Action::RerollShop),
+            Err(GameError::CannotAfford { bankroll: 0, .. })
+        ));
+    }
+}
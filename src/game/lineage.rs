@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::game::{CardInstance, GameState, HistoryEntry, TraceEvent};
+
+/// Longest copy chain followed; real chains are a few links at most.
+const MAX_DEPTH: usize = 32;
+
+/// Most expired copies listed; a hand rarely makes more.
+const MAX_SPENT_COPIES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    Source,
+    CodeBank,
+    Hand,
+    Pile,
+}
+
+impl Zone {
+    pub fn label(self) -> &'static str {
+        match self {
+            Zone::Source => "Source",
+            Zone::CodeBank => "Code Bank",
+            Zone::Hand => "Hand",
+            Zone::Pile => "Pile",
+        }
+    }
+}
+
+/// Every card in play with the zone it sits in.
+pub fn cards_in_play(state: &GameState) -> impl Iterator<Item = (Zone, &CardInstance)> {
+    let hand = state.hand.iter().map(|c| (Zone::Hand, c));
+    let bank = state.collection.iter().map(|c| (Zone::CodeBank, c));
+    let source = state.deck.iter().map(|c| (Zone::Source, c));
+    let pile = state.pile.iter().map(|c| (Zone::Pile, c));
+    hand.chain(bank).chain(source).chain(pile)
+}
+
+pub fn find(state: &GameState, card_id: u64) -> Option<(Zone, &CardInstance)> {
+    cards_in_play(state).find(|(_, c)| c.id == card_id)
+}
+
+/// Latest run of `card_id` in the execution history.
+pub fn last_run(state: &GameState, card_id: u64) -> Option<&HistoryEntry> {
+    state.history.iter().rev().find(|h| h.card_id == card_id)
+}
+
+/// The latest copies that ran and have since left play (they expire at the end of their
+/// hand), newest first, with their display names. At most `MAX_SPENT_COPIES` are returned.
+pub fn spent_copies(state: &GameState) -> Vec<(&HistoryEntry, String)> {
+    let in_play = cards_in_play(state)
+        .map(|(_, c)| c.id)
+        .collect::<HashSet<_>>();
+    let mut listed = HashSet::new();
+    let copies = state
+        .history
+        .iter()
+        .rev()
+        .filter(|e| e.clone_of.is_some() && !in_play.contains(&e.card_id))
+        .filter(|e| listed.insert(e.card_id))
+        .take(MAX_SPENT_COPIES)
+        .collect::<Vec<_>>();
+
+    // One pass over the trace names every listed copy.
+    let mut names = HashMap::new();
+    for event in state.trace.iter() {
+        if let TraceEvent::CardStart { card_id, name, .. } = event
+            && listed.contains(card_id)
+        {
+            names.insert(*card_id, name.clone());
+        }
+    }
+    copies
+        .into_iter()
+        .map(|entry| {
+            let name = names
+                .remove(&entry.card_id)
+                .unwrap_or_else(|| fallback_name(entry));
+            (entry, name)
+        })
+        .collect()
+}
+
+/// Display name of a card that ran, as recorded when it started (upgrades included).
+pub fn run_name(state: &GameState, entry: &HistoryEntry) -> String {
+    state
+        .trace
+        .iter()
+        .rev()
+        .find_map(|event| match event {
+            TraceEvent::CardStart { card_id, name, .. } if *card_id == entry.card_id => {
+                Some(name.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| fallback_name(entry))
+}
+
+fn fallback_name(entry: &HistoryEntry) -> String {
+    CardInstance::new(entry.card_id, &entry.def_id).name()
+}
+
+/// One instance in a card's copy chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineageLink<'a> {
+    pub card_id: u64,
+    /// Where the card sits, while it is in play.
+    pub card: Option<(Zone, &'a CardInstance)>,
+    /// Its latest run, once it has left play.
+    pub ran: Option<&'a HistoryEntry>,
+}
+
+/// The card followed by the instances it was copied from, nearest first.
+///
+/// Cards that left play are followed through the history, so an expired copy still leads
+/// back to its source. The chain ends at an original card or at an ancestor with no trace.
+pub fn lineage(state: &GameState, card_id: u64) -> Vec<LineageLink<'_>> {
+    let mut links = Vec::new();
+    let mut next = Some(card_id);
+    while let Some(id) = next {
+        if links.len() >= MAX_DEPTH || links.iter().any(|l: &LineageLink| l.card_id == id) {
+            break;
+        }
+        let card = find(state, id);
+        let ran = card.is_none().then(|| last_run(state, id)).flatten();
+        next = match (card, ran) {
+            (Some((_, c)), _) => c.provenance.clone_of,
+            (None, Some(entry)) => entry.clone_of,
+            (None, None) => None,
+        };
+        links.push(LineageLink {
+            card_id: id,
+            card,
+            ran,
+        });
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Action, Engine, Lifetime};
+    use crate::vm::Limits;
+
+    #[test]
+    fn lineage_follows_copies_until_an_ancestor_leaves_play() {
+        let mut state = GameState::new(Vec::new(), Limits::default());
+        let mut original = CardInstance::new(1, "tap_score");
+        original
+            .provenance
+            .mutated_from
+            .push("tap_bank".to_string());
+        let mut copy = CardInstance::new(5, "tap_score");
+        copy.provenance.clone_of = Some(1);
+        copy.lifetime = Lifetime::EphemeralThisHand;
+        let mut copy_of_copy = CardInstance::new(6, "tap_score");
+        copy_of_copy.provenance.clone_of = Some(5);
+        state.pile = vec![original, copy];
+        state.hand = vec![copy_of_copy];
+
+        let chain = lineage(&state, 6);
+        let ids = chain.iter().map(|l| l.card_id).collect::<Vec<_>>();
+        assert_eq!(ids, [6, 5, 1]);
+        assert_eq!(chain[0].card.map(|(zone, _)| zone), Some(Zone::Hand));
+        assert_eq!(
+            chain[2].card.unwrap().1.provenance.mutated_from,
+            ["tap_bank"]
+        );
+
+        state.pile.remove(0);
+        let chain = lineage(&state, 6);
+        assert_eq!(chain.len(), 3);
+        assert!(chain[2].card.is_none());
+    }
+
+    #[test]
+    fn expired_copies_stay_traceable_after_the_hand() {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_meta_clone".to_string(),
+            })
+            .unwrap();
+        engine.dispatch(Action::PlayHand).unwrap();
+        let state = &engine.state;
+
+        let copies = spent_copies(state);
+        assert!(!copies.is_empty());
+        let (copy, name) = &copies[0];
+        let copy = *copy;
+        let chain = lineage(state, copy.card_id);
+        assert!(chain[0].card.is_none());
+        assert_eq!(chain[0].ran, Some(copy));
+        assert_eq!(chain[1].card_id, copy.clone_of.unwrap());
+        assert!(chain[1].card.is_some(), "the source stays in the pile");
+        assert_eq!(*name, chain[1].card.unwrap().1.name());
+        assert_eq!(run_name(state, copy), *name);
+    }
+}
@@ -1,9 +1,35 @@
+pub mod achievements;
+pub mod bosses;
 pub mod cards;
+pub mod config;
+pub mod daily;
+pub mod editor;
 mod engine;
+pub mod env;
+pub mod generator;
+pub mod goals;
+mod legal;
+pub mod lineage;
 mod model;
+pub mod mods;
+pub mod observer;
+pub mod packs;
+pub mod progress;
 pub mod puzzles;
-mod trace;
+pub mod relics;
+pub mod replay;
+pub mod rng;
+pub mod share;
+pub mod shop;
+pub mod solver;
+pub mod timeline;
+pub mod trace;
+pub mod upgrades;
 
-pub use engine::{Action, Engine, GameError};
-pub use model::{CardInstance, GameState, HistoryEntry, Phase, RunMode};
+pub use engine::{Action, Engine, GameError, check_script};
+pub use model::{
+    CardInstance, DISCARDS_PER_LEVEL, GameState, HANDS_PER_LEVEL, HistoryEntry, Lifetime, Phase,
+    Provenance, RunMode,
+};
+pub use observer::{EngineObserver, ObserverId};
 pub use trace::TraceEvent;
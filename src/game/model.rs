@@ -1,12 +1,29 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::vm::Limits;
+use crate::vm::{Limits, VmRules};
 
+use super::bosses::BossModifier;
 use super::cards::{self, CardDef};
+use super::config::RunConfig;
+use super::goals::Goal;
+use super::progress::PuzzleResult;
+use super::puzzles::PuzzleData;
+use super::rng::RngStreams;
+use super::shop::ShopState;
+use super::trace::Trace;
+use super::upgrades::{self, BUDGET_PER_LEVEL, CardUpgrade, EffectiveDef, MAX_UPGRADE_LEVEL};
+
+/// Hands a Classic level allows before the run is lost.
+pub const HANDS_PER_LEVEL: u32 = 4;
+
+/// Discards a Classic level allows.
+pub const DISCARDS_PER_LEVEL: u32 = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Phase {
     InLevel,
+    /// Between Classic levels: spend bankroll before the next level starts.
+    Shop,
     Reward,
     GameOver,
 }
@@ -21,12 +38,53 @@ pub enum RunMode {
 pub struct HistoryEntry {
     pub card_id: u64,
     pub def_id: String,
+    /// Instance the card was copied from, so copies stay traceable after they expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_of: Option<u64>,
+}
+
+/// Where a card instance came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Instance this card was copied from by `clone()` or `again()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clone_of: Option<u64>,
+    /// Definitions the card had before each `mutate()`, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mutated_from: Vec<String>,
+}
+
+impl Provenance {
+    pub fn is_original(&self) -> bool {
+        self.clone_of.is_none() && self.mutated_from.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Lifetime {
+    #[default]
+    Permanent,
+    /// Removed from play once the hand that created it finishes.
+    EphemeralThisHand,
+}
+
+impl Lifetime {
+    pub fn is_permanent(&self) -> bool {
+        *self == Lifetime::Permanent
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CardInstance {
     pub id: u64,
     pub def_id: String,
+    /// Per-instance upgrades; plain cards match their catalog definition exactly.
+    #[serde(default)]
+    pub upgrade: CardUpgrade,
+    #[serde(default, skip_serializing_if = "Provenance::is_original")]
+    pub provenance: Provenance,
+    #[serde(default, skip_serializing_if = "Lifetime::is_permanent")]
+    pub lifetime: Lifetime,
 }
 
 impl CardInstance {
@@ -34,16 +92,60 @@ impl CardInstance {
         Self {
             id,
             def_id: def_id.into(),
+            upgrade: CardUpgrade::default(),
+            provenance: Provenance::default(),
+            lifetime: Lifetime::Permanent,
         }
     }
 
+    pub fn is_ephemeral(&self) -> bool {
+        self.lifetime == Lifetime::EphemeralThisHand
+    }
+
     pub fn def(&self) -> Option<&'static CardDef> {
         cards::get(&self.def_id)
     }
+
+    /// Catalog definition with this instance's upgrades applied.
+    pub fn effective_def(&self) -> Option<EffectiveDef> {
+        self.def()
+            .map(|def| EffectiveDef::resolve(def, &self.upgrade))
+    }
+
+    /// Display name with upgrades applied; the raw id for unknown cards.
+    pub fn name(&self) -> String {
+        self.effective_def()
+            .map_or_else(|| self.def_id.clone(), |def| def.name)
+    }
+
+    /// Raise the upgrade level by one; `false` at `MAX_UPGRADE_LEVEL` or for unknown cards.
+    pub fn level_up(&mut self) -> bool {
+        if self.upgrade.level >= MAX_UPGRADE_LEVEL || self.def().is_none() {
+            return false;
+        }
+        self.upgrade.level += 1;
+        self.upgrade.bonus_budget += BUDGET_PER_LEVEL;
+        self.retier();
+        true
+    }
+
+    /// Recompute the tiered script after the level or definition changed.
+    pub fn retier(&mut self) {
+        self.upgrade.script = self
+            .def()
+            .and_then(|def| upgrades::tier_script(def.script, self.upgrade.level));
+    }
 }
 
+/// Serialized through `remote = "Self"` so loading can fix up fields that depend on each
+/// other (see the `Deserialize` impl).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct GameState {
+    /// Seed the run (or puzzle) was created from.
+    pub seed: u64,
+    /// Challenge date (`YYYY-MM-DD`) when this is a daily run.
+    pub daily: Option<String>,
     pub bankroll: i64,
     pub score: i64,
     pub acc: i64,
@@ -52,6 +154,12 @@ pub struct GameState {
     pub phase: Phase,
     pub mode: RunMode,
     pub turn: u32,
+    /// Hands left this level (Classic). Reaching zero below target ends the run.
+    pub hands_left: u32,
+    /// Discards left this level (Classic).
+    pub discards_left: u32,
+    /// Rule modifier for the current (or, while shopping, the upcoming) boss level.
+    pub boss: Option<BossModifier>,
     pub puzzle_id: Option<String>,
     pub puzzle_title: Option<String>,
     pub puzzle_blurb: Option<String>,
@@ -62,6 +170,18 @@ pub struct GameState {
     pub puzzle_solved: bool,
     pub puzzle_failed: bool,
     pub puzzle_message: Option<String>,
+    /// Definition the active puzzle was loaded from, for retries and share codes.
+    #[serde(default)]
+    pub puzzle_source: Option<Box<PuzzleData>>,
+    /// Puzzle goals beyond the score target and bankroll goal.
+    #[serde(default)]
+    pub puzzle_goals: Vec<Goal>,
+    /// Cards played from the hand across this puzzle's plays (clones excluded).
+    #[serde(default)]
+    pub puzzle_cards_used: u32,
+    /// Par rating of the solve, set once the puzzle is solved.
+    #[serde(default)]
+    pub puzzle_result: Option<PuzzleResult>,
 
     /// Draw pile. Hidden-ish, but still deterministic and reorderable for debug later.
     pub deck: Vec<CardInstance>,
@@ -75,17 +195,48 @@ pub struct GameState {
     /// Executed card pile (discard). Cards played from Hand are placed here.
     pub pile: Vec<CardInstance>,
 
+    /// Owned relic ids (see `relics::catalog`), in acquisition order.
+    pub relics: Vec<String>,
+
     /// Full execution history (oldest first). Used for cards like `clone()`/`again()`/`mutate()`.
     pub history: Vec<HistoryEntry>,
 
-    pub trace: Vec<crate::game::TraceEvent>,
+    pub trace: Trace,
+
+    /// Random streams for shuffles, mutations, boosters and the shop. Saves from before the
+    /// streams existed get fresh ones seeded from `seed`.
+    #[serde(default)]
+    pub rng: RngStreams,
 
-    pub limits: Limits,
+    /// Current shop stock while `phase == Phase::Shop`.
+    pub shop: Option<ShopState>,
+
+    /// Difficulty and progression settings this run was started with.
+    pub config: RunConfig,
+}
+
+impl Serialize for GameState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameState::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut state = GameState::deserialize(deserializer)?;
+        // Streams are always seeded from the run seed; a mismatch means the save had none.
+        if state.rng.seed() != state.seed {
+            state.rng = RngStreams::new(state.seed);
+        }
+        Ok(state)
+    }
 }
 
 impl GameState {
     pub fn new(deck: Vec<CardInstance>, limits: Limits) -> Self {
         Self {
+            seed: 0,
+            daily: None,
             bankroll: 10,
             score: 0,
             acc: 0,
@@ -94,6 +245,9 @@ impl GameState {
             phase: Phase::InLevel,
             mode: RunMode::Classic,
             turn: 0,
+            hands_left: HANDS_PER_LEVEL,
+            discards_left: DISCARDS_PER_LEVEL,
+            boss: None,
             puzzle_id: None,
             puzzle_title: None,
             puzzle_blurb: None,
@@ -104,13 +258,38 @@ impl GameState {
             puzzle_solved: false,
             puzzle_failed: false,
             puzzle_message: None,
+            puzzle_source: None,
+            puzzle_goals: Vec::new(),
+            puzzle_cards_used: 0,
+            puzzle_result: None,
             deck,
             collection: Vec::new(),
             hand: Vec::new(),
             pile: Vec::new(),
+            relics: Vec::new(),
             history: Vec::new(),
-            trace: Vec::new(),
-            limits,
+            trace: Trace::default(),
+            rng: RngStreams::default(),
+            shop: None,
+            config: RunConfig {
+                limits,
+                ..RunConfig::standard()
+            },
         }
     }
+
+    /// Execution limits after the boss modifier, if any.
+    pub fn vm_limits(&self) -> Limits {
+        let limits = self.config.limits;
+        self.boss.map_or(limits, |b| b.limits(limits))
+    }
+
+    pub fn vm_rules(&self) -> VmRules {
+        self.boss.map(|b| b.vm_rules()).unwrap_or_default()
+    }
+
+    /// Whether draws from the source are blocked (and its contents hidden).
+    pub fn source_frozen(&self) -> bool {
+        self.boss == Some(BossModifier::FrozenSource)
+    }
 }
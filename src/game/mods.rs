@@ -0,0 +1,464 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::kardlang::effective_len;
+
+use super::cards::{self, ANY_LEVEL, CardDef, CardKind, CardSet, GenerationRule};
+use super::check_script;
+
+/// Serialization format of a mod file (also used by puzzle packs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModFormat {
+    Json,
+    Ron,
+    Toml,
+}
+
+impl ModFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            ModFormat::Json => "JSON",
+            ModFormat::Ron => "RON",
+            ModFormat::Toml => "TOML",
+        }
+    }
+
+    /// Format for a file name, by extension.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let ext = name.rsplit_once('.')?.1.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(ModFormat::Json),
+            "ron" => Some(ModFormat::Ron),
+            "toml" => Some(ModFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Deserialize `text`; the error is the parser's message.
+    pub fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T, String> {
+        match self {
+            ModFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            ModFormat::Ron => ron::from_str(text).map_err(|e| e.to_string()),
+            ModFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ModError {
+    #[error("unsupported mod file: {0} (expected .json, .ron or .toml)")]
+    UnknownFormat(String),
+
+    #[error("{format} syntax error: {message}")]
+    Syntax {
+        format: &'static str,
+        message: String,
+    },
+
+    #[error("card {id}: {reason}")]
+    InvalidCard { id: String, reason: String },
+
+    #[error("generation rule for unknown card: {0}")]
+    UnknownRuleCard(String),
+
+    #[error("id listed twice in one mod: {0}")]
+    DuplicateId(String),
+
+    #[error("mod leaves no generation rule with a positive weight")]
+    EmptyPool,
+}
+
+/// A card as written in a mod file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardSpec {
+    pub id: String,
+    pub name: String,
+    pub kind: CardKind,
+    pub budget: usize,
+    pub script: String,
+    #[serde(default = "default_icon")]
+    pub icon: String,
+    #[serde(default)]
+    pub doc: String,
+}
+
+/// A generation rule as written in a mod file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleSpec {
+    pub id: String,
+    #[serde(default = "default_min_level")]
+    pub min_level: u32,
+    /// `None` keeps the card in the pool at every later level.
+    #[serde(default)]
+    pub max_level: Option<u32>,
+    pub weight: u16,
+}
+
+fn default_icon() -> String {
+    "◆".to_string()
+}
+
+fn default_min_level() -> u32 {
+    1
+}
+
+/// Cards and rules to merge over the built-in set; entries with a built-in id replace it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModFile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub cards: Vec<CardSpec>,
+    #[serde(default)]
+    pub rules: Vec<RuleSpec>,
+}
+
+impl ModFile {
+    pub fn parse(text: &str, format: ModFormat) -> Result<Self, ModError> {
+        format.parse(text).map_err(|message| ModError::Syntax {
+            format: format.label(),
+            message,
+        })
+    }
+
+    /// Parse a file, picking the format from its extension.
+    pub fn from_file(file_name: &str, text: &str) -> Result<Self, ModError> {
+        let format = ModFormat::from_file_name(file_name)
+            .ok_or_else(|| ModError::UnknownFormat(file_name.to_string()))?;
+        let mut file = Self::parse(text, format)?;
+        if file.name.is_empty() {
+            file.name = file_name.to_string();
+        }
+        Ok(file)
+    }
+
+    /// Merge over the built-in catalog and rules, validating every modded card.
+    pub fn merge(&self) -> Result<ModCatalog, ModError> {
+        let mut out = ModCatalog {
+            name: self.name.clone(),
+            cards: cards::builtin_catalog().to_vec(),
+            rules: cards::builtin_generation_rules().to_vec(),
+            added: 0,
+            replaced: 0,
+        };
+
+        let mut seen: Vec<&str> = Vec::new();
+        for spec in &self.cards {
+            if seen.contains(&spec.id.as_str()) {
+                return Err(ModError::DuplicateId(spec.id.clone()));
+            }
+            seen.push(&spec.id);
+
+            let def = spec.to_def();
+            validate_card(&def)?;
+            match out.cards.iter_mut().find(|c| c.id == def.id) {
+                Some(slot) => {
+                    *slot = def;
+                    out.replaced += 1;
+                }
+                None => {
+                    out.cards.push(def);
+                    out.added += 1;
+                }
+            }
+        }
+
+        seen.clear();
+        for spec in &self.rules {
+            if seen.contains(&spec.id.as_str()) {
+                return Err(ModError::DuplicateId(spec.id.clone()));
+            }
+            seen.push(&spec.id);
+
+            let Some(card) = out.cards.iter().find(|c| c.id == spec.id) else {
+                return Err(ModError::UnknownRuleCard(spec.id.clone()));
+            };
+            let rule = GenerationRule {
+                id: card.id,
+                min_level: spec.min_level,
+                max_level: spec.max_level.unwrap_or(ANY_LEVEL),
+                weight: spec.weight,
+            };
+            match out.rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(slot) => *slot = rule,
+                None => out.rules.push(rule),
+            }
+        }
+
+        if out.rules.iter().all(|r| r.weight == 0) {
+            return Err(ModError::EmptyPool);
+        }
+        Ok(out)
+    }
+}
+
+/// Strings leaked for modded cards, reused so reloading a mod does not leak them again.
+static STRINGS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+/// Card sets leaked by `install`, reused when the same set is installed again.
+static SETS: Mutex<Vec<CardSet>> = Mutex::new(Vec::new());
+
+fn intern(s: &str) -> &'static str {
+    let mut strings = STRINGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = strings.get(s) {
+        return interned;
+    }
+    let leaked: &'static str = Box::leak(s.to_string().into_boxed_str());
+    strings.insert(leaked);
+    leaked
+}
+
+impl CardSpec {
+    /// Strings are interned so modded cards share `CardDef`'s `&'static str` fields;
+    /// each distinct string is leaked once per process.
+    fn to_def(&self) -> CardDef {
+        CardDef {
+            id: intern(&self.id),
+            name: intern(&self.name),
+            kind: self.kind,
+            budget: self.budget,
+            script: intern(&self.script),
+            icon: intern(&self.icon),
+            doc: intern(&self.doc),
+        }
+    }
+}
+
+/// A validated, merged catalog ready to install.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModCatalog {
+    pub name: String,
+    pub cards: Vec<CardDef>,
+    pub rules: Vec<GenerationRule>,
+    /// Modded cards with new ids.
+    pub added: usize,
+    /// Modded cards that replaced a built-in.
+    pub replaced: usize,
+}
+
+impl ModCatalog {
+    /// Make this the catalog every lookup and source generation on this thread uses.
+    ///
+    /// Installing a set that was installed before reuses it instead of leaking a new copy.
+    pub fn install(self) {
+        let mut sets = SETS.lock().unwrap_or_else(|e| e.into_inner());
+        let known = sets
+            .iter()
+            .find(|(cards, rules)| **cards == self.cards[..] && **rules == self.rules[..]);
+        let set = match known {
+            Some(set) => *set,
+            None => {
+                let set: CardSet = (
+                    Box::leak(self.cards.into_boxed_slice()),
+                    Box::leak(self.rules.into_boxed_slice()),
+                );
+                sets.push(set);
+                set
+            }
+        };
+        cards::set_active(Some(set));
+    }
+}
+
+/// Restore the built-in catalog and rules on this thread.
+pub fn reset() {
+    cards::set_active(None);
+}
+
+/// Parse, budget and static checks for a single card definition.
+pub fn validate_card(def: &CardDef) -> Result<(), ModError> {
+    let invalid = |reason: String| ModError::InvalidCard {
+        id: def.id.to_string(),
+        reason,
+    };
+    if def.id.trim().is_empty() || def.name.trim().is_empty() {
+        return Err(invalid("id and name must not be empty".to_string()));
+    }
+    check_script(def.script).map_err(|e| invalid(e.to_string()))?;
+    let cost = effective_len(def.script);
+    if cost > def.budget {
+        return Err(invalid(format!(
+            "script cost {cost} exceeds budget {}",
+            def.budget
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{
+        "name": "tiny",
+        "cards": [
+            { "id": "triple_tap", "name": "Triple Tap", "kind": "Score", "budget": 9, "script": "s(111)" },
+            { "id": "tap_score", "name": "Tap Score", "kind": "Score", "budget": 8, "script": "s(111)" }
+        ],
+        "rules": [
+            { "id": "triple_tap", "min_level": 2, "weight": 5 },
+            { "id": "tap_score", "max_level": 2, "weight": 1 }
+        ]
+    }"#;
+
+    const RON: &str = r#"(
+        name: "tiny",
+        cards: [
+            (id: "triple_tap", name: "Triple Tap", kind: Score, budget: 9, script: "s(111)"),
+            (id: "tap_score", name: "Tap Score", kind: Score, budget: 8, script: "s(111)"),
+        ],
+        rules: [
+            (id: "triple_tap", min_level: 2, weight: 5),
+            (id: "tap_score", max_level: Some(2), weight: 1),
+        ],
+    )"#;
+
+    const TOML: &str = r#"
+        name = "tiny"
+
+        [[cards]]
+        id = "triple_tap"
+        name = "Triple Tap"
+        kind = "Score"
+        budget = 9
+        script = "s(111)"
+
+        [[cards]]
+        id = "tap_score"
+        name = "Tap Score"
+        kind = "Score"
+        budget = 8
+        script = "s(111)"
+
+        [[rules]]
+        id = "triple_tap"
+        min_level = 2
+        weight = 5
+
+        [[rules]]
+        id = "tap_score"
+        max_level = 2
+        weight = 1
+    "#;
+
+    fn card(id: &str, budget: usize, script: &str) -> CardSpec {
+        CardSpec {
+            id: id.to_string(),
+            name: id.to_string(),
+            kind: CardKind::Score,
+            budget,
+            script: script.to_string(),
+            icon: default_icon(),
+            doc: String::new(),
+        }
+    }
+
+    #[test]
+    fn all_formats_parse_to_the_same_mod() {
+        let json = ModFile::from_file("tiny.json", JSON).unwrap();
+        assert_eq!(ModFile::from_file("tiny.ron", RON).unwrap(), json);
+        assert_eq!(ModFile::from_file("tiny.TOML", TOML).unwrap(), json);
+        assert!(matches!(
+            ModFile::from_file("tiny.yaml", JSON),
+            Err(ModError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            ModFile::from_file("tiny.json", RON),
+            Err(ModError::Syntax { format: "JSON", .. })
+        ));
+    }
+
+    #[test]
+    fn merge_adds_and_replaces_over_the_builtins() {
+        let merged = ModFile::parse(JSON, ModFormat::Json)
+            .unwrap()
+            .merge()
+            .unwrap();
+        assert_eq!((merged.added, merged.replaced), (1, 1));
+        assert_eq!(merged.cards.len(), cards::builtin_catalog().len() + 1);
+
+        let tap = merged.cards.iter().find(|c| c.id == "tap_score").unwrap();
+        assert_eq!(tap.script, "s(111)");
+        let rule = merged.rules.iter().find(|r| r.id == "tap_score").unwrap();
+        assert_eq!((rule.min_level, rule.max_level, rule.weight), (1, 2, 1));
+        let rule = merged.rules.iter().find(|r| r.id == "triple_tap").unwrap();
+        assert_eq!((rule.min_level, rule.max_level), (2, ANY_LEVEL));
+    }
+
+    #[test]
+    fn merge_rejects_invalid_cards_and_rules() {
+        let reject = |file: ModFile| file.merge().unwrap_err();
+
+        let over = reject(ModFile {
+            cards: vec![card("big", 2, "s(11111)")],
+            ..ModFile::default()
+        });
+        assert!(
+            matches!(over, ModError::InvalidCard { ref reason, .. } if reason.contains("budget"))
+        );
+
+        for script in ["zap(1)", "s(NOPE)", "s(1,1)", "s(("] {
+            let err = reject(ModFile {
+                cards: vec![card("bad", 20, script)],
+                ..ModFile::default()
+            });
+            assert!(matches!(err, ModError::InvalidCard { .. }), "{script}");
+        }
+
+        let orphan = reject(ModFile {
+            rules: vec![RuleSpec {
+                id: "ghost".to_string(),
+                min_level: 1,
+                max_level: None,
+                weight: 1,
+            }],
+            ..ModFile::default()
+        });
+        assert!(matches!(orphan, ModError::UnknownRuleCard(_)));
+
+        let twice = reject(ModFile {
+            cards: vec![card("dup", 8, "s(1)"), card("dup", 8, "s(1)")],
+            ..ModFile::default()
+        });
+        assert!(matches!(twice, ModError::DuplicateId(_)));
+    }
+
+    #[test]
+    fn installed_cards_play_through_the_engine_and_reinstalls_reuse_the_set() {
+        use crate::game::{Action, CardInstance, Engine, TraceEvent};
+        use crate::vm::Limits;
+
+        let file = ModFile::parse(JSON, ModFormat::Json).unwrap();
+        file.merge().unwrap().install();
+        let installed = cards::catalog();
+        file.merge().unwrap().install();
+        assert!(std::ptr::eq(cards::catalog(), installed));
+
+        let mut engine = Engine::with_deck(1, Vec::new(), Limits::default());
+        engine.state.hand = vec![CardInstance::new(1, "triple_tap")];
+        engine.dispatch(Action::PlayHand).unwrap();
+        assert!(engine.state.score > 0);
+        assert!(
+            engine
+                .state
+                .trace
+                .iter()
+                .any(|e| matches!(e, TraceEvent::CardStart { name, .. } if name == "Triple Tap"))
+        );
+
+        reset();
+        assert!(cards::get("triple_tap").is_none());
+        assert!(std::ptr::eq(cards::catalog(), cards::builtin_catalog()));
+    }
+
+    #[test]
+    fn builtin_cards_pass_the_static_checker() {
+        for def in cards::builtin_catalog() {
+            validate_card(def).unwrap_or_else(|e| panic!("{e}"));
+        }
+    }
+}
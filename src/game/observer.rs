@@ -0,0 +1,97 @@
+use std::fmt;
+
+use crate::game::engine::Action;
+use crate::game::{CardInstance, TraceEvent};
+use crate::vm::Effect;
+
+/// Receives engine events as they happen, in the order they are recorded.
+///
+/// Every method has a no-op default, so implementors only override what they need.
+/// `on_event` sees every trace event, including the ones with a typed callback.
+pub trait EngineObserver {
+    fn on_action(&mut self, _action: &Action) {}
+    fn on_card_started(&mut self, _card_id: u64, _def_id: &str, _index: usize) {}
+    fn on_effect_applied(&mut self, _effect: &Effect) {}
+    /// A card moved from the draw pile into the Code Bank.
+    fn on_card_drawn(&mut self, _card: &CardInstance) {}
+    fn on_level_cleared(&mut self, _level: u32, _score: i64) {}
+    fn on_puzzle_solved(&mut self, _id: &str, _stars: u8) {}
+    fn on_event(&mut self, _event: &TraceEvent) {}
+}
+
+/// Any `FnMut(&TraceEvent)` closure can observe the raw event stream.
+impl<F: FnMut(&TraceEvent)> EngineObserver for F {
+    fn on_event(&mut self, event: &TraceEvent) {
+        self(event)
+    }
+}
+
+/// Handle returned by `Engine::subscribe`, used to unsubscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// The observers subscribed to one engine.
+///
+/// Cloning an engine does not clone its observers: solver and preview copies stay silent.
+#[derive(Default)]
+pub(crate) struct Observers {
+    next_id: u64,
+    list: Vec<(ObserverId, Box<dyn EngineObserver>)>,
+}
+
+impl Observers {
+    pub(crate) fn subscribe(&mut self, observer: Box<dyn EngineObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.list.push((id, observer));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: ObserverId) -> Option<Box<dyn EngineObserver>> {
+        let index = self.list.iter().position(|(i, _)| *i == id)?;
+        Some(self.list.remove(index).1)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub(crate) fn event(&mut self, event: &TraceEvent) {
+        for (_, observer) in &mut self.list {
+            match event {
+                TraceEvent::Action { action } => observer.on_action(action),
+                TraceEvent::CardStart {
+                    index,
+                    card_id,
+                    def_id,
+                    ..
+                } => observer.on_card_started(*card_id, def_id, *index),
+                TraceEvent::EffectApplied { effect } => observer.on_effect_applied(effect),
+                TraceEvent::LevelCleared { level, score, .. } => {
+                    observer.on_level_cleared(*level, *score)
+                }
+                TraceEvent::PuzzleSolved { id, stars } => observer.on_puzzle_solved(id, *stars),
+                _ => {}
+            }
+            observer.on_event(event);
+        }
+    }
+
+    pub(crate) fn card_drawn(&mut self, card: &CardInstance) {
+        for (_, observer) in &mut self.list {
+            observer.on_card_drawn(card);
+        }
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.list.len())
+    }
+}
@@ -0,0 +1,285 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::mods::ModFormat;
+use super::puzzles::{self, PuzzleData};
+use super::{cards, goals};
+
+/// Newest pack format this build reads.
+pub const PACK_FORMAT: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("unsupported pack file: {0} (expected .json, .ron or .toml)")]
+    UnknownFormat(String),
+
+    #[error("{format} syntax error: {message}")]
+    Syntax {
+        format: &'static str,
+        message: String,
+    },
+
+    #[error("could not read {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("pack format {0} is newer than this build supports ({PACK_FORMAT})")]
+    UnsupportedFormat(u32),
+
+    #[error("pack {0} has no puzzles")]
+    Empty(String),
+
+    #[error("puzzle id used twice or shadows a built-in: {0}")]
+    DuplicatePuzzle(String),
+
+    #[error("missing cards (load the mod that provides them first): {}", .0.join(", "))]
+    MissingCards(Vec<String>),
+
+    #[error("puzzle {id}: {reason}")]
+    InvalidPuzzle { id: String, reason: String },
+}
+
+/// A shareable set of puzzles with a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzlePack {
+    #[serde(default = "current_format")]
+    pub format: u32,
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    /// Card ids the pack relies on, so a missing card mod is reported up front.
+    #[serde(default)]
+    pub required_cards: Vec<String>,
+    /// Puzzles in play order.
+    pub puzzles: Vec<PuzzleData>,
+}
+
+fn current_format() -> u32 {
+    PACK_FORMAT
+}
+
+impl PuzzlePack {
+    /// Parse and validate a pack.
+    pub fn parse(text: &str, format: ModFormat) -> Result<Self, PackError> {
+        let pack: Self = format.parse(text).map_err(|message| PackError::Syntax {
+            format: format.label(),
+            message,
+        })?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Parse and validate a pack, picking the format from the file name.
+    pub fn from_file(file_name: &str, text: &str) -> Result<Self, PackError> {
+        let format = ModFormat::from_file_name(file_name)
+            .ok_or_else(|| PackError::UnknownFormat(file_name.to_string()))?;
+        Self::parse(text, format)
+    }
+
+    /// Read a pack from disk (native builds).
+    pub fn load(path: &Path) -> Result<Self, PackError> {
+        let text = std::fs::read_to_string(path).map_err(|e| PackError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        Self::from_file(name, &text)
+    }
+
+    /// The compiled-in lessons as a pack.
+    pub fn builtin() -> Self {
+        Self {
+            format: PACK_FORMAT,
+            id: "builtin".to_string(),
+            title: "Tutorials".to_string(),
+            author: "Kardinality".to_string(),
+            description: String::new(),
+            required_cards: Vec::new(),
+            puzzles: puzzles::catalog().iter().map(PuzzleData::from).collect(),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PuzzleData> {
+        self.puzzles.iter().find(|p| p.id == id)
+    }
+
+    pub fn validate(&self) -> Result<(), PackError> {
+        if self.format > PACK_FORMAT {
+            return Err(PackError::UnsupportedFormat(self.format));
+        }
+        if self.puzzles.is_empty() {
+            return Err(PackError::Empty(self.id.clone()));
+        }
+
+        let mut missing: Vec<String> = Vec::new();
+        let referenced = self
+            .puzzles
+            .iter()
+            .flat_map(|p| p.card_ids())
+            .chain(self.required_cards.iter());
+        for id in referenced {
+            if cards::get(id).is_none() && !missing.contains(id) {
+                missing.push(id.clone());
+            }
+        }
+        if !missing.is_empty() {
+            return Err(PackError::MissingCards(missing));
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        for p in &self.puzzles {
+            if seen.contains(&p.id.as_str()) || puzzles::get(&p.id).is_some() {
+                return Err(PackError::DuplicatePuzzle(p.id.clone()));
+            }
+            seen.push(&p.id);
+
+            let invalid = |reason: &str| PackError::InvalidPuzzle {
+                id: p.id.clone(),
+                reason: reason.to_string(),
+            };
+            if p.name.trim().is_empty() {
+                return Err(invalid("name must not be empty"));
+            }
+            if p.hand_ids.is_empty() {
+                return Err(invalid("needs a starting hand"));
+            }
+            if p.target_score < 1 {
+                return Err(invalid("target must be positive"));
+            }
+            if p.play_limit == Some(0) {
+                return Err(invalid("play limit must be at least 1"));
+            }
+            goals::check(&p.goals).map_err(|reason| invalid(&reason))?;
+        }
+        Ok(())
+    }
+}
+
+/// Read every pack file in `dir`; files with other extensions are skipped.
+pub fn load_dir(dir: &Path) -> Vec<Result<PuzzlePack, PackError>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(ModFormat::from_file_name)
+                .is_some()
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.iter().map(|p| PuzzlePack::load(p)).collect()
+}
+
+/// Packs the player has loaded, in load order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackLibrary {
+    pub packs: Vec<PuzzlePack>,
+}
+
+impl PackLibrary {
+    /// Add a pack, replacing an earlier one with the same id.
+    pub fn add(&mut self, pack: PuzzlePack) {
+        match self.packs.iter_mut().find(|p| p.id == pack.id) {
+            Some(slot) => *slot = pack,
+            None => self.packs.push(pack),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.packs.retain(|p| p.id != id);
+    }
+
+    /// A puzzle from any loaded pack.
+    pub fn find_puzzle(&self, id: &str) -> Option<&PuzzleData> {
+        self.packs.iter().find_map(|pack| pack.get(id))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parse a stored library; unreadable data yields an empty library.
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::solver;
+
+    const SAMPLER: &str = include_str!("../../packs/sampler.json");
+
+    #[test]
+    fn sampler_pack_loads_and_its_hint_lines_solve() {
+        let pack = PuzzlePack::from_file("sampler.json", SAMPLER).unwrap();
+        assert_eq!(pack.puzzles.len(), 2);
+
+        for p in &pack.puzzles {
+            let plays = p
+                .solution
+                .iter()
+                .map(|play| play.iter().map(String::as_str).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let plays = plays.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            let engine = solver::puzzle_engine_for(p).unwrap();
+            assert!(solver::replay_line(engine, &plays).unwrap(), "{}", p.id);
+        }
+    }
+
+    #[test]
+    fn builtin_pack_round_trips_through_every_format() {
+        let pack = PuzzlePack::builtin();
+        let json = serde_json::to_string(&pack).unwrap();
+        let ron = ron::to_string(&pack).unwrap();
+        let toml = toml::to_string(&pack).unwrap();
+
+        for (name, text) in [("a.json", json), ("a.ron", ron), ("a.toml", toml)] {
+            let parsed: PuzzlePack = ModFormat::from_file_name(name)
+                .unwrap()
+                .parse(&text)
+                .unwrap();
+            assert_eq!(parsed, pack, "{name}");
+        }
+    }
+
+    #[test]
+    fn library_replaces_packs_by_id_and_round_trips() {
+        let pack = PuzzlePack::from_file("sampler.json", SAMPLER).unwrap();
+        let mut library = PackLibrary::default();
+        library.add(pack.clone());
+        library.add(pack);
+        assert_eq!(library.packs.len(), 1);
+        assert!(library.find_puzzle("sampler_echo").is_some());
+        assert_eq!(PackLibrary::from_json(&library.to_json()), library);
+
+        library.remove("sampler");
+        assert!(library.packs.is_empty());
+    }
+
+    #[test]
+    fn validation_reports_missing_cards_and_clashing_ids() {
+        let mut pack = PuzzlePack::from_file("sampler.json", SAMPLER).unwrap();
+        pack.required_cards.push("laser".to_string());
+        pack.puzzles[0].hand_ids.push("laser".to_string());
+        match pack.validate() {
+            Err(PackError::MissingCards(ids)) => assert_eq!(ids, vec!["laser".to_string()]),
+            other => panic!("expected missing cards, got {other:?}"),
+        }
+
+        let err = PuzzlePack::builtin().validate().unwrap_err();
+        assert!(matches!(err, PackError::DuplicatePuzzle(_)));
+
+        let newer = SAMPLER.replace("\"format\": 1", "\"format\": 99");
+        let err = PuzzlePack::from_file("sampler.json", &newer).unwrap_err();
+        assert!(matches!(err, PackError::UnsupportedFormat(99)));
+    }
+}
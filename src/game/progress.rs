@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::puzzles::Par;
+
+/// Most stars a single solve can earn.
+pub const MAX_STARS: u8 = 3;
+
+/// How a puzzle was solved: the values par is measured against, plus the stars they earned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleResult {
+    pub plays: u32,
+    pub cards: u32,
+    /// Bankroll left above the bankroll goal (or above zero when there is none).
+    pub surplus: i64,
+    pub stars: u8,
+}
+
+impl PuzzleResult {
+    /// Rate a solve against `par`.
+    ///
+    /// One star for solving, a second for staying within the play and card pars, a third for
+    /// also reaching the surplus par. Unset par values count as met.
+    pub fn rate(par: &Par, plays: u32, cards: u32, surplus: i64) -> Self {
+        let mut stars = 1;
+        if par.plays.is_none_or(|p| plays <= p) && par.cards.is_none_or(|c| cards <= c) {
+            stars += 1;
+            if par.surplus.is_none_or(|s| surplus >= s) {
+                stars += 1;
+            }
+        }
+        Self {
+            plays,
+            cards,
+            surplus,
+            stars,
+        }
+    }
+
+    /// Ranking key: more stars, then fewer plays, fewer cards, larger surplus.
+    fn rank(&self) -> (std::cmp::Reverse<u8>, u32, u32, std::cmp::Reverse<i64>) {
+        (
+            std::cmp::Reverse(self.stars),
+            self.plays,
+            self.cards,
+            std::cmp::Reverse(self.surplus),
+        )
+    }
+
+    pub fn beats(&self, other: &Self) -> bool {
+        self.rank() < other.rank()
+    }
+}
+
+/// Locally stored best result per puzzle id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleProgress {
+    pub best: BTreeMap<String, PuzzleResult>,
+}
+
+impl PuzzleProgress {
+    /// Keep `result` if it is the first or a better solve; returns whether it was stored.
+    pub fn record(&mut self, id: &str, result: PuzzleResult) -> bool {
+        match self.best.get(id) {
+            Some(prev) if !result.beats(prev) => false,
+            _ => {
+                self.best.insert(id.to_string(), result);
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PuzzleResult> {
+        self.best.get(id)
+    }
+
+    pub fn is_solved(&self, id: &str) -> bool {
+        self.best.contains_key(id)
+    }
+
+    /// Puzzles in an ordered list unlock once the one before them is solved.
+    pub fn is_unlocked<'a>(&self, mut ordered: impl Iterator<Item = &'a str>, id: &str) -> bool {
+        let mut prev: Option<&str> = None;
+        for current in ordered.by_ref() {
+            if current == id {
+                return prev.is_none_or(|p| self.is_solved(p));
+            }
+            prev = Some(current);
+        }
+        true
+    }
+
+    pub fn total_stars(&self) -> u32 {
+        self.best.values().map(|r| r.stars as u32).sum()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parse stored progress; unreadable data yields empty progress.
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_default()
+    }
+}
+
+/// `★★☆`-style rendering of a star count.
+pub fn star_text(stars: u8) -> String {
+    let stars = stars.min(MAX_STARS) as usize;
+    format!(
+        "{}{}",
+        "★".repeat(stars),
+        "☆".repeat(MAX_STARS as usize - stars)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stars_follow_play_card_and_surplus_pars() {
+        let par = Par {
+            plays: Some(1),
+            cards: Some(2),
+            surplus: Some(5),
+        };
+        assert_eq!(PuzzleResult::rate(&par, 2, 2, 9).stars, 1);
+        assert_eq!(PuzzleResult::rate(&par, 1, 3, 9).stars, 1);
+        assert_eq!(PuzzleResult::rate(&par, 1, 2, 4).stars, 2);
+        assert_eq!(PuzzleResult::rate(&par, 1, 2, 5).stars, 3);
+        assert_eq!(PuzzleResult::rate(&Par::default(), 9, 9, -1).stars, 3);
+        assert_eq!(star_text(2), "★★☆");
+    }
+
+    #[test]
+    fn only_better_results_replace_the_stored_best() {
+        let mut progress = PuzzleProgress::default();
+        let par = Par {
+            plays: Some(1),
+            ..Par::default()
+        };
+        assert!(progress.record("a", PuzzleResult::rate(&par, 2, 3, 0)));
+        assert!(!progress.record("a", PuzzleResult::rate(&par, 2, 4, 0)));
+        assert!(progress.record("a", PuzzleResult::rate(&par, 1, 4, 0)));
+        assert_eq!(progress.get("a").unwrap().stars, 3);
+
+        let restored = PuzzleProgress::from_json(&progress.to_json());
+        assert_eq!(restored, progress);
+        assert_eq!(restored.total_stars(), 3);
+    }
+
+    #[test]
+    fn later_puzzles_unlock_in_order() {
+        let mut progress = PuzzleProgress::default();
+        let order = ["one", "two", "three"];
+        assert!(progress.is_unlocked(order.into_iter(), "one"));
+        assert!(!progress.is_unlocked(order.into_iter(), "two"));
+        progress.record("one", PuzzleResult::rate(&Par::default(), 1, 1, 0));
+        assert!(progress.is_unlocked(order.into_iter(), "two"));
+        assert!(!progress.is_unlocked(order.into_iter(), "three"));
+        assert!(progress.is_unlocked(order.into_iter(), "elsewhere"));
+    }
+}
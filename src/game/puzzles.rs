@@ -1,3 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use super::goals::Goal;
+
+/// Optional par values a solve is rated against (see [`crate::game::progress`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Par {
+    /// Most plays for the second star.
+    #[serde(default)]
+    pub plays: Option<u32>,
+    /// Most cards played for the second star.
+    #[serde(default)]
+    pub cards: Option<u32>,
+    /// Least bankroll left above the goal for the third star.
+    #[serde(default)]
+    pub surplus: Option<i64>,
+}
+
+impl Par {
+    pub const NONE: Par = Par {
+        plays: None,
+        cards: None,
+        surplus: None,
+    };
+
+    /// Play and card pars matching a known solution line.
+    pub fn from_solution(solution: &[Vec<String>]) -> Self {
+        if solution.is_empty() {
+            return Self::NONE;
+        }
+        Self {
+            plays: Some(solution.len() as u32),
+            cards: Some(solution.iter().map(Vec::len).sum::<usize>() as u32),
+            surplus: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PuzzleDef {
     pub id: &'static str,
@@ -14,17 +56,102 @@ pub struct PuzzleDef {
     pub source_ids: &'static [&'static str],
     pub collection_ids: &'static [&'static str],
     pub hand_ids: &'static [&'static str],
+    /// Intended line described by `hint`: card ids per play, in execution order.
+    pub solution: &'static [&'static [&'static str]],
+    pub par: Par,
+}
+
+/// Owned counterpart of [`PuzzleDef`] for puzzles that are not compiled in (generated, loaded).
+///
+/// This is also the on-disk puzzle format; only `id`, `name`, `target_score` and `hand_ids`
+/// are required.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleData {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub blurb: String,
+    #[serde(default)]
+    pub hint: String,
+    #[serde(default)]
+    pub theme: String,
+    #[serde(default = "default_start_bankroll")]
+    pub start_bankroll: i64,
+    #[serde(default)]
+    pub start_score: i64,
+    #[serde(default = "default_start_level")]
+    pub start_level: u32,
+    pub target_score: i64,
+    #[serde(default)]
+    pub goal_bankroll: Option<i64>,
+    #[serde(default)]
+    pub play_limit: Option<u32>,
+    #[serde(default)]
+    pub source_ids: Vec<String>,
+    #[serde(default)]
+    pub collection_ids: Vec<String>,
+    pub hand_ids: Vec<String>,
+    #[serde(default)]
+    pub solution: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Par::is_empty")]
+    pub par: Par,
+    /// Extra goals on top of `target_score` and `goal_bankroll`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub goals: Vec<Goal>,
+}
+
+fn default_start_bankroll() -> i64 {
+    10
+}
+
+fn default_start_level() -> u32 {
+    1
+}
+
+impl PuzzleData {
+    /// Every card id the puzzle deals, in source, collection, hand order.
+    pub fn card_ids(&self) -> impl Iterator<Item = &String> {
+        self.source_ids
+            .iter()
+            .chain(self.collection_ids.iter())
+            .chain(self.hand_ids.iter())
+    }
+}
+
+impl From<&PuzzleDef> for PuzzleData {
+    fn from(p: &PuzzleDef) -> Self {
+        let owned = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        Self {
+            id: p.id.to_string(),
+            name: p.name.to_string(),
+            blurb: p.blurb.to_string(),
+            hint: p.hint.to_string(),
+            theme: p.theme.to_string(),
+            start_bankroll: p.start_bankroll,
+            start_score: p.start_score,
+            start_level: p.start_level,
+            target_score: p.target_score,
+            goal_bankroll: p.goal_bankroll,
+            play_limit: p.play_limit,
+            source_ids: owned(p.source_ids),
+            collection_ids: owned(p.collection_ids),
+            hand_ids: owned(p.hand_ids),
+            solution: p.solution.iter().map(|play| owned(play)).collect(),
+            par: p.par,
+            goals: Vec::new(),
+        }
+    }
 }
 
 pub fn catalog() -> &'static [PuzzleDef] {
-    &PUZZLES
+    PUZZLES
 }
 
 pub fn get(id: &str) -> Option<&'static PuzzleDef> {
     PUZZLES.iter().find(|p| p.id == id)
 }
 
-static PUZZLES: [PuzzleDef; 6] = [
+static PUZZLES: &[PuzzleDef] = &[
     PuzzleDef {
         id: "lesson_score_ping",
         name: "Lesson 1: Score Ping",
@@ -40,6 +167,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         source_ids: &["tap_score", "tap_bank", "spark_draw"],
         collection_ids: &[],
         hand_ids: &["tap_score"],
+        solution: &[&["tap_score"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(1),
+            surplus: None,
+        },
     },
     PuzzleDef {
         id: "lesson_money_loop",
@@ -62,6 +195,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         ],
         collection_ids: &[],
         hand_ids: &["tap_bank", "double_bank", "cash_two", "tap_score"],
+        solution: &[&["tap_bank", "double_bank", "cash_two", "tap_score"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(4),
+            surplus: Some(6),
+        },
     },
     PuzzleDef {
         id: "lesson_draw_math",
@@ -84,6 +223,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         ],
         collection_ids: &["tap_bank", "tap_score"],
         hand_ids: &["spark_draw", "tri_deck"],
+        solution: &[&["spark_draw", "tri_deck"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(2),
+            surplus: None,
+        },
     },
     PuzzleDef {
         id: "lesson_adaptive_branch",
@@ -100,6 +245,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         source_ids: &["hedge_two", "cash_two", "tap_score", "tap_bank"],
         collection_ids: &[],
         hand_ids: &["hedge_two", "cash_two", "tap_score"],
+        solution: &[&["hedge_two", "cash_two"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(2),
+            surplus: None,
+        },
     },
     PuzzleDef {
         id: "lesson_meta_clone",
@@ -116,6 +267,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         source_ids: &["tap_score", "clone_pair", "tap_bank", "spark_draw"],
         collection_ids: &[],
         hand_ids: &["tap_score", "clone_pair"],
+        solution: &[&["tap_score", "clone_pair"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(2),
+            surplus: None,
+        },
     },
     PuzzleDef {
         id: "lesson_fibo_sprint",
@@ -132,6 +289,12 @@ static PUZZLES: [PuzzleDef; 6] = [
         source_ids: &["fibo_level", "tap_bank", "tap_score"],
         collection_ids: &[],
         hand_ids: &["fibo_level"],
+        solution: &[&["fibo_level"]],
+        par: Par {
+            plays: Some(1),
+            cards: Some(1),
+            surplus: None,
+        },
     },
 ];
 
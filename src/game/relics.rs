@@ -0,0 +1,200 @@
+use crate::vm::Effect;
+
+use super::cards::CardKind;
+
+/// Most relics a run can hold at once.
+pub const MAX_RELICS: usize = 5;
+
+/// Moment in a run at which a relic's trigger script runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelicHook {
+    /// A card from the hand is about to execute.
+    CardStart,
+    /// A card's effect was just applied.
+    Effect,
+    /// A Classic level target was reached.
+    LevelClear,
+    /// A card's `draw` effect pulled cards into the Code Bank.
+    Draw,
+}
+
+impl RelicHook {
+    pub fn label(self) -> &'static str {
+        match self {
+            RelicHook::CardStart => "on card start",
+            RelicHook::Effect => "on effect",
+            RelicHook::LevelClear => "on level clear",
+            RelicHook::Draw => "on draw",
+        }
+    }
+}
+
+/// A passive item owned for the rest of a run.
+///
+/// Triggered relics run `script` (Kardlang, same registers as cards) when `hook` fires and
+/// the filters match; the resulting effects are applied like card effects but never
+/// re-trigger relics. Static relics adjust rules instead (`digit_discount`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelicDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub icon: &'static str,
+    pub doc: &'static str,
+    pub price: i64,
+    pub hook: Option<RelicHook>,
+    /// `CardStart` only: fire for cards of this kind.
+    pub kind: Option<CardKind>,
+    /// `Effect` only: fire for effects with this name (see [`Effect::name`]).
+    pub effect: Option<&'static str>,
+    /// Fire at most once per played hand.
+    pub once_per_hand: bool,
+    pub script: &'static str,
+    /// Digits in card scripts cost this much less (see `kardlang::CostRules`).
+    pub digit_discount: usize,
+}
+
+impl RelicDef {
+    /// Whether this relic reacts to `hook` for the given card kind / effect.
+    pub fn matches(
+        &self,
+        hook: RelicHook,
+        kind: Option<CardKind>,
+        effect: Option<&Effect>,
+    ) -> bool {
+        self.hook == Some(hook)
+            && self.kind.is_none_or(|k| Some(k) == kind)
+            && self
+                .effect
+                .is_none_or(|name| effect.is_some_and(|e| e.name() == name))
+    }
+}
+
+pub fn catalog() -> &'static [RelicDef] {
+    &RELICS
+}
+
+pub fn get(id: &str) -> Option<&'static RelicDef> {
+    RELICS.iter().find(|r| r.id == id)
+}
+
+const BASE: RelicDef = RelicDef {
+    id: "",
+    name: "",
+    icon: "",
+    doc: "",
+    price: 0,
+    hook: None,
+    kind: None,
+    effect: None,
+    once_per_hand: false,
+    script: "",
+    digit_discount: 0,
+};
+
+static RELICS: [RelicDef; 5] = [
+    RelicDef {
+        id: "score_chip",
+        name: "Score Chip",
+        icon: "✹",
+        doc: "+1 score whenever a Score card starts.",
+        price: 6,
+        hook: Some(RelicHook::CardStart),
+        kind: Some(CardKind::Score),
+        script: "s(1)",
+        ..BASE
+    },
+    RelicDef {
+        id: "digit_lens",
+        name: "Digit Lens",
+        icon: "🔍",
+        doc: "Digits in card scripts cost 1 less (never below 1).",
+        price: 7,
+        digit_discount: 1,
+        ..BASE
+    },
+    RelicDef {
+        id: "echo_chamber",
+        name: "Echo Chamber",
+        icon: "⟳",
+        doc: "The first clone each hand queues one extra copy for free.",
+        price: 8,
+        hook: Some(RelicHook::Effect),
+        effect: Some("clone"),
+        once_per_hand: true,
+        script: "c(1)",
+        ..BASE
+    },
+    RelicDef {
+        id: "dividend",
+        name: "Dividend",
+        icon: "💰",
+        doc: "Gain $L when a level is cleared.",
+        price: 5,
+        hook: Some(RelicHook::LevelClear),
+        script: "b(L)",
+        ..BASE
+    },
+    RelicDef {
+        id: "magnet",
+        name: "Magnet",
+        icon: "🧲",
+        doc: "+$1 whenever a card draws.",
+        price: 5,
+        hook: Some(RelicHook::Draw),
+        script: "b(1)",
+        ..BASE
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::kardlang::{CostRules, effective_len_with, parse_program};
+
+    #[test]
+    fn relic_ids_are_unique_and_scripts_parse() {
+        let mut ids = HashSet::new();
+        for relic in catalog() {
+            assert!(ids.insert(relic.id), "duplicate relic id: {}", relic.id);
+            assert!(relic.price >= 1, "relic {} is free", relic.id);
+            match relic.hook {
+                Some(_) => assert!(
+                    parse_program(relic.script).is_ok(),
+                    "relic {} script does not parse",
+                    relic.id
+                ),
+                None => assert!(
+                    relic.script.is_empty(),
+                    "static relic {} has a script",
+                    relic.id
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn filters_restrict_which_events_fire() {
+        let chip = get("score_chip").unwrap();
+        assert!(chip.matches(RelicHook::CardStart, Some(CardKind::Score), None));
+        assert!(!chip.matches(RelicHook::CardStart, Some(CardKind::Economy), None));
+        assert!(!chip.matches(RelicHook::Effect, Some(CardKind::Score), None));
+
+        let echo = get("echo_chamber").unwrap();
+        assert!(echo.matches(RelicHook::Effect, None, Some(&Effect::Clone(2))));
+        assert!(!echo.matches(RelicHook::Effect, None, Some(&Effect::Again(2))));
+    }
+
+    #[test]
+    fn digit_discount_never_drops_a_digit_below_one() {
+        let lens = get("digit_lens").unwrap();
+        let rules = CostRules {
+            digit_discount: lens.digit_discount,
+            ..CostRules::default()
+        };
+        // `s(9)` costs 12 normally; the 9 drops to 8.
+        assert_eq!(effective_len_with("s(9)", rules), 11);
+        assert_eq!(effective_len_with("s(11)", rules), 5);
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use super::config::RunConfig;
+use super::{Action, Engine, GameError};
+
+/// A recorded run: seed, configuration and every dispatched action, in order.
+///
+/// Replaying rebuilds the exact same `Engine`, since all randomness flows from the seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub config: RunConfig,
+    pub actions: Vec<Action>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: RunConfig) -> Self {
+        Self {
+            seed,
+            config,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Dispatch `action` on `engine` and record it (failed actions are recorded too, since
+    /// they can still leave trace output behind).
+    pub fn record(&mut self, engine: &mut Engine, action: Action) -> Result<(), GameError> {
+        self.actions.push(action.clone());
+        engine.dispatch(action)
+    }
+
+    /// Re-run every recorded action from a fresh engine; action errors are ignored just like
+    /// they were during recording.
+    pub fn play(&self) -> Engine {
+        let mut engine = Engine::with_config(self.seed, self.config.clone());
+        for action in &self.actions {
+            let _ = engine.dispatch(action.clone());
+        }
+        engine
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::config::Preset;
+
+    #[test]
+    fn replaying_reproduces_the_recorded_state() {
+        let config = RunConfig::from_preset(Preset::Hard);
+        let mut engine = Engine::with_config(21, config.clone());
+        let mut replay = Replay::new(21, config);
+
+        replay
+            .record(&mut engine, Action::DrawToCollection { count: 2 })
+            .unwrap();
+        replay
+            .record(&mut engine, Action::MoveCollectionToHand { index: 0 })
+            .unwrap();
+        let _ = replay.record(&mut engine, Action::PlayHand);
+
+        assert_eq!(replay.play().state, engine.state);
+    }
+}
@@ -0,0 +1,132 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+/// One random stream per purpose, all derived from the run seed.
+///
+/// Each purpose gets its own ChaCha stream id, so drawing from one never shifts another:
+/// a line that mutates a card still sees the same shuffles as one that doesn't.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StreamPositions", into = "StreamPositions")]
+pub struct RngStreams {
+    seed: u64,
+    /// Deck shuffles: the opening deck, recycling the pile and opened packs.
+    pub shuffle: ChaCha8Rng,
+    /// Replacement cards rolled by `mutate()`.
+    pub mutate: ChaCha8Rng,
+    /// Contents of booster packs.
+    pub booster: ChaCha8Rng,
+    /// Shop stock, relic offers and boss rewards.
+    pub shop: ChaCha8Rng,
+}
+
+impl RngStreams {
+    pub fn new(seed: u64) -> Self {
+        Self::at(seed, [0; 4])
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn at(seed: u64, positions: [u64; 4]) -> Self {
+        let [shuffle, mutate, booster, shop] = [0, 1, 2, 3].map(|stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream);
+            rng.set_word_pos(u128::from(positions[stream as usize]));
+            rng
+        });
+        Self {
+            seed,
+            shuffle,
+            mutate,
+            booster,
+            shop,
+        }
+    }
+}
+
+impl Default for RngStreams {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Stable FNV-1a hash, so seeds derived from text are reproducible across targets.
+pub fn stable_hash(text: &str) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in text.as_bytes() {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x1000_0000_01b3);
+    }
+    h
+}
+
+/// Saved form: the seed plus how far each stream has advanced.
+#[derive(Serialize, Deserialize)]
+struct StreamPositions {
+    seed: u64,
+    shuffle: u64,
+    mutate: u64,
+    booster: u64,
+    shop: u64,
+}
+
+impl From<StreamPositions> for RngStreams {
+    fn from(saved: StreamPositions) -> Self {
+        Self::at(
+            saved.seed,
+            [saved.shuffle, saved.mutate, saved.booster, saved.shop],
+        )
+    }
+}
+
+impl From<RngStreams> for StreamPositions {
+    fn from(streams: RngStreams) -> Self {
+        let pos = |rng: &ChaCha8Rng| rng.get_word_pos() as u64;
+        Self {
+            seed: streams.seed,
+            shuffle: pos(&streams.shuffle),
+            mutate: pos(&streams.mutate),
+            booster: pos(&streams.booster),
+            shop: pos(&streams.shop),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Engine, GameState};
+    use rand::Rng;
+
+    #[test]
+    fn streams_are_independent_and_survive_a_save() {
+        let mut a = RngStreams::new(42);
+        let mut b = RngStreams::new(42);
+        for _ in 0..5 {
+            a.mutate.random::<u64>();
+        }
+        assert_eq!(a.shuffle.random::<u64>(), b.shuffle.random::<u64>());
+        assert_ne!(a.shuffle.random::<u64>(), b.booster.random::<u64>());
+
+        let json = serde_json::to_string(&a).unwrap();
+        let mut restored: RngStreams = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, a);
+        assert_eq!(restored.mutate.random::<u64>(), a.mutate.random::<u64>());
+    }
+
+    #[test]
+    fn saves_without_streams_reseed_from_the_run_seed() {
+        let engine = Engine::new(42);
+        let mut json = serde_json::to_value(&engine.state).unwrap();
+        json.as_object_mut().unwrap().remove("rng");
+
+        let restored: GameState = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.rng, RngStreams::new(42));
+        // Saves that do carry streams keep their positions.
+        let saved = serde_json::to_string(&engine.state).unwrap();
+        let kept: GameState = serde_json::from_str(&saved).unwrap();
+        assert_eq!(kept.rng, engine.state.rng);
+    }
+}
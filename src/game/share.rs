@@ -0,0 +1,451 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use thiserror::Error;
+
+use super::goals::Goal;
+use super::puzzles::{Par, PuzzleData};
+
+/// Layout version written as the first byte of every share code.
+///
+/// Version 2 appended the par values and version 3 the extra goals; older codes still decode.
+pub const SHARE_VERSION: u8 = 3;
+
+/// Deepest `AnyOf` nesting a share code may carry.
+const MAX_GOAL_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShareError {
+    #[error("share code is not valid base64url")]
+    Encoding,
+
+    #[error("share code is damaged (checksum mismatch)")]
+    Checksum,
+
+    #[error("share code version {0} is not supported (supported versions are 1..={SHARE_VERSION})")]
+    Version(u8),
+
+    #[error("share code is truncated or malformed")]
+    Malformed,
+
+    #[error("{0} is too large to fit in a share code")]
+    OutOfRange(&'static str),
+}
+
+/// Pack a puzzle into a short, versioned, checksummed base64url string.
+///
+/// Layout: version byte, LEB128 integers (signed values zigzagged, `None` as 0), a
+/// deduplicated card-id table referenced by index, the text fields, the par values, the extra
+/// goals, then a 4-byte FNV-1a checksum over everything before it.
+///
+/// Fails only for optional values at the very edge of `i64`, which have no encoding.
+pub fn encode(puzzle: &PuzzleData) -> Result<String, ShareError> {
+    let mut w = Writer(vec![SHARE_VERSION]);
+
+    w.str(&puzzle.id);
+    w.str(&puzzle.name);
+    w.str(&puzzle.theme);
+    w.int(puzzle.start_bankroll);
+    w.int(puzzle.start_score);
+    w.uint(puzzle.start_level as u64);
+    w.int(puzzle.target_score);
+    w.opt_int("bankroll goal", puzzle.goal_bankroll)?;
+    w.uint(puzzle.play_limit.map_or(0, |l| l as u64 + 1));
+
+    let mut table: Vec<&str> = Vec::new();
+    let all_ids = puzzle
+        .card_ids()
+        .chain(puzzle.solution.iter().flatten())
+        .map(String::as_str);
+    for id in all_ids {
+        if !table.contains(&id) {
+            table.push(id);
+        }
+    }
+    w.uint(table.len() as u64);
+    for id in &table {
+        w.str(id);
+    }
+    let index = |id: &String| table.iter().position(|t| *t == id).unwrap_or(0) as u64;
+    for list in [&puzzle.source_ids, &puzzle.collection_ids, &puzzle.hand_ids] {
+        w.uint(list.len() as u64);
+        for id in list {
+            w.uint(index(id));
+        }
+    }
+    w.uint(puzzle.solution.len() as u64);
+    for play in &puzzle.solution {
+        w.uint(play.len() as u64);
+        for id in play {
+            w.uint(index(id));
+        }
+    }
+
+    w.str(&puzzle.blurb);
+    w.str(&puzzle.hint);
+    w.uint(puzzle.par.plays.map_or(0, |p| p as u64 + 1));
+    w.uint(puzzle.par.cards.map_or(0, |c| c as u64 + 1));
+    w.opt_int("surplus par", puzzle.par.surplus)?;
+    w.goals(&puzzle.goals);
+
+    let sum = checksum(&w.0);
+    w.0.extend_from_slice(&sum.to_le_bytes());
+    Ok(URL_SAFE_NO_PAD.encode(&w.0))
+}
+
+/// Inverse of [`encode`].
+pub fn decode(code: &str) -> Result<PuzzleData, ShareError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(code.trim())
+        .map_err(|_| ShareError::Encoding)?;
+    if bytes.len() < 5 {
+        return Err(ShareError::Malformed);
+    }
+    let (body, sum) = bytes.split_at(bytes.len() - 4);
+    if checksum(body).to_le_bytes() != sum {
+        return Err(ShareError::Checksum);
+    }
+    let version = body[0];
+    if !(1..=SHARE_VERSION).contains(&version) {
+        return Err(ShareError::Version(version));
+    }
+
+    let mut r = Reader {
+        bytes: body,
+        pos: 1,
+    };
+    let id = r.str()?;
+    let name = r.str()?;
+    let theme = r.str()?;
+    let start_bankroll = r.int()?;
+    let start_score = r.int()?;
+    let start_level = u32::try_from(r.uint()?).map_err(|_| ShareError::Malformed)?;
+    let target_score = r.int()?;
+    let goal_bankroll = r.uint()?.checked_sub(1).map(unzigzag);
+    let play_limit = r.opt_u32()?;
+
+    let table = (0..r.len()?)
+        .map(|_| r.str())
+        .collect::<Result<Vec<_>, _>>()?;
+    let source_ids = r.ids(&table)?;
+    let collection_ids = r.ids(&table)?;
+    let hand_ids = r.ids(&table)?;
+    let plays = r.len()?;
+    let solution = (0..plays)
+        .map(|_| r.ids(&table))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let blurb = r.str()?;
+    let hint = r.str()?;
+    let par = if version >= 2 {
+        Par {
+            plays: r.opt_u32()?,
+            cards: r.opt_u32()?,
+            surplus: r.uint()?.checked_sub(1).map(unzigzag),
+        }
+    } else {
+        Par::NONE
+    };
+    let goals = if version >= 3 {
+        r.goals(0)?
+    } else {
+        Vec::new()
+    };
+    if r.pos != body.len() {
+        return Err(ShareError::Malformed);
+    }
+
+    Ok(PuzzleData {
+        id,
+        name,
+        blurb,
+        hint,
+        theme,
+        start_bankroll,
+        start_score,
+        start_level,
+        target_score,
+        goal_bankroll,
+        play_limit,
+        source_ids,
+        collection_ids,
+        hand_ids,
+        solution,
+        par,
+        goals,
+    })
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut h: u32 = 0x811c_9dc5;
+    for b in bytes {
+        h ^= *b as u32;
+        h = h.wrapping_mul(0x0100_0193);
+    }
+    h
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn uint(&mut self, mut n: u64) {
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                self.0.push(byte);
+                return;
+            }
+            self.0.push(byte | 0x80);
+        }
+    }
+
+    fn int(&mut self, n: i64) {
+        self.uint(zigzag(n));
+    }
+
+    /// `None` as 0, otherwise the zigzagged value plus one.
+    fn opt_int(&mut self, field: &'static str, n: Option<i64>) -> Result<(), ShareError> {
+        let n = n
+            .map(|n| {
+                zigzag(n)
+                    .checked_add(1)
+                    .ok_or(ShareError::OutOfRange(field))
+            })
+            .transpose()?;
+        self.uint(n.unwrap_or(0));
+        Ok(())
+    }
+
+    fn str(&mut self, s: &str) {
+        self.uint(s.len() as u64);
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    /// A counted list of goals, each a tag followed by its payload.
+    fn goals(&mut self, goals: &[Goal]) {
+        self.uint(goals.len() as u64);
+        for goal in goals {
+            match goal {
+                Goal::ScoreAtLeast(n) => {
+                    self.uint(0);
+                    self.int(*n);
+                }
+                Goal::ExactScore(n) => {
+                    self.uint(1);
+                    self.int(*n);
+                }
+                Goal::BankrollAtLeast(n) => {
+                    self.uint(2);
+                    self.int(*n);
+                }
+                Goal::AccEquals(n) => {
+                    self.uint(3);
+                    self.int(*n);
+                }
+                Goal::MinPileSize(n) => {
+                    self.uint(4);
+                    self.uint(*n as u64);
+                }
+                Goal::EmptyCollection => self.uint(5),
+                Goal::MaxCards(n) => {
+                    self.uint(6);
+                    self.uint(*n as u64);
+                }
+                Goal::ForbidFunction(name) => {
+                    self.uint(7);
+                    self.str(name);
+                }
+                Goal::SolventEveryStep => self.uint(8),
+                Goal::AnyOf(inner) => {
+                    self.uint(9);
+                    self.goals(inner);
+                }
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn uint(&mut self) -> Result<u64, ShareError> {
+        let mut out: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.pos).ok_or(ShareError::Malformed)?;
+            self.pos += 1;
+            out |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(out);
+            }
+        }
+        Err(ShareError::Malformed)
+    }
+
+    fn int(&mut self) -> Result<i64, ShareError> {
+        self.uint().map(unzigzag)
+    }
+
+    /// An optional `u32` written as `value + 1`, with 0 for `None`.
+    fn opt_u32(&mut self) -> Result<Option<u32>, ShareError> {
+        match self.uint()?.checked_sub(1) {
+            Some(n) => u32::try_from(n)
+                .map(Some)
+                .map_err(|_| ShareError::Malformed),
+            None => Ok(None),
+        }
+    }
+
+    /// A length prefix, bounded by the bytes left so corrupt input cannot allocate wildly.
+    fn len(&mut self) -> Result<usize, ShareError> {
+        let n = self.uint()? as usize;
+        if n > self.bytes.len() - self.pos {
+            return Err(ShareError::Malformed);
+        }
+        Ok(n)
+    }
+
+    fn str(&mut self) -> Result<String, ShareError> {
+        let n = self.len()?;
+        let s = std::str::from_utf8(&self.bytes[self.pos..self.pos + n])
+            .map_err(|_| ShareError::Malformed)?;
+        self.pos += n;
+        Ok(s.to_string())
+    }
+
+    /// Inverse of `Writer::goals`.
+    fn goals(&mut self, depth: usize) -> Result<Vec<Goal>, ShareError> {
+        if depth > MAX_GOAL_DEPTH {
+            return Err(ShareError::Malformed);
+        }
+        (0..self.len()?)
+            .map(|_| {
+                Ok(match self.uint()? {
+                    0 => Goal::ScoreAtLeast(self.int()?),
+                    1 => Goal::ExactScore(self.int()?),
+                    2 => Goal::BankrollAtLeast(self.int()?),
+                    3 => Goal::AccEquals(self.int()?),
+                    4 => Goal::MinPileSize(self.uint()? as usize),
+                    5 => Goal::EmptyCollection,
+                    6 => Goal::MaxCards(
+                        u32::try_from(self.uint()?).map_err(|_| ShareError::Malformed)?,
+                    ),
+                    7 => Goal::ForbidFunction(self.str()?),
+                    8 => Goal::SolventEveryStep,
+                    9 => Goal::AnyOf(self.goals(depth + 1)?),
+                    _ => return Err(ShareError::Malformed),
+                })
+            })
+            .collect()
+    }
+
+    /// A counted list of card-table indices.
+    fn ids(&mut self, table: &[String]) -> Result<Vec<String>, ShareError> {
+        (0..self.len()?)
+            .map(|_| {
+                let i = self.uint()? as usize;
+                table.get(i).cloned().ok_or(ShareError::Malformed)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::puzzles;
+
+    #[test]
+    fn every_builtin_puzzle_round_trips() {
+        for def in puzzles::catalog() {
+            let puzzle = PuzzleData::from(def);
+            let code = encode(&puzzle).unwrap();
+            assert!(
+                code.bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'),
+                "{code}"
+            );
+            assert_eq!(decode(&code).unwrap(), puzzle, "{}", def.id);
+        }
+    }
+
+    #[test]
+    fn negative_values_and_missing_goals_survive() {
+        let mut puzzle = PuzzleData::from(&puzzles::catalog()[0]);
+        puzzle.start_bankroll = -7;
+        puzzle.goal_bankroll = Some(-3);
+        puzzle.play_limit = None;
+        puzzle.par.surplus = Some(-2);
+        puzzle.goals = vec![
+            Goal::ExactScore(-4),
+            Goal::ForbidFunction("c".to_string()),
+            Goal::MaxCards(2),
+            Goal::AnyOf(vec![Goal::EmptyCollection, Goal::MinPileSize(2)]),
+        ];
+        assert_eq!(decode(&encode(&puzzle).unwrap()).unwrap(), puzzle);
+
+        puzzle.goal_bankroll = Some(i64::MAX);
+        puzzle.par.surplus = Some(i64::MIN + 1);
+        assert_eq!(decode(&encode(&puzzle).unwrap()).unwrap(), puzzle);
+        puzzle.goal_bankroll = Some(i64::MIN);
+        assert_eq!(
+            encode(&puzzle),
+            Err(ShareError::OutOfRange("bankroll goal"))
+        );
+    }
+
+    #[test]
+    fn version_one_codes_decode_without_par() {
+        let mut puzzle = PuzzleData::from(&puzzles::catalog()[0]);
+        let mut bytes = URL_SAFE_NO_PAD.decode(encode(&puzzle).unwrap()).unwrap();
+        // Drop the checksum, the three single-byte par fields and the empty goal list, then
+        // re-sign as v1.
+        bytes.truncate(bytes.len() - 8);
+        bytes[0] = 1;
+        let sum = checksum(&bytes);
+        bytes.extend_from_slice(&sum.to_le_bytes());
+
+        puzzle.par = Par::NONE;
+        assert_eq!(decode(&URL_SAFE_NO_PAD.encode(&bytes)).unwrap(), puzzle);
+    }
+
+    #[test]
+    fn damaged_codes_are_rejected() {
+        let code = encode(&PuzzleData::from(&puzzles::catalog()[1])).unwrap();
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&code).unwrap();
+        bytes[3] ^= 0x01;
+        let flipped = URL_SAFE_NO_PAD.encode(&bytes);
+        assert_eq!(decode(&flipped), Err(ShareError::Checksum));
+
+        assert_eq!(decode("not*base64"), Err(ShareError::Encoding));
+        let half = &URL_SAFE_NO_PAD.decode(&code).unwrap()[..bytes.len() / 2];
+        assert_eq!(
+            decode(&URL_SAFE_NO_PAD.encode(half)),
+            Err(ShareError::Checksum)
+        );
+
+        let mut future = vec![SHARE_VERSION + 1];
+        let sum = checksum(&future);
+        future.extend_from_slice(&sum.to_le_bytes());
+        assert_eq!(
+            decode(&URL_SAFE_NO_PAD.encode(&future)),
+            Err(ShareError::Version(SHARE_VERSION + 1))
+        );
+        assert!(
+            ShareError::Version(SHARE_VERSION + 1)
+                .to_string()
+                .ends_with(&format!("supported versions are 1..={SHARE_VERSION})"))
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use super::cards::{self, CardDef, CardKind};
+use super::relics;
+
+/// Cards offered each time the shop is stocked or rerolled.
+pub const OFFER_COUNT: usize = 4;
+
+/// Cost of the first reroll in a shop visit; each further reroll costs one more.
+pub const BASE_REROLL_COST: i64 = 2;
+
+/// Cost of the first card removal in a shop visit; each further removal costs one more.
+pub const BASE_REMOVAL_COST: i64 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShopOffer {
+    pub def_id: String,
+    pub price: i64,
+    pub sold: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelicOffer {
+    pub relic_id: String,
+    pub price: i64,
+    pub sold: bool,
+}
+
+/// Stock and running prices for one visit to the shop (between two Classic levels).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShopState {
+    pub offers: Vec<ShopOffer>,
+    pub reroll_cost: i64,
+    pub removal_cost: i64,
+    /// Price of a booster pack of freshly generated source cards.
+    pub pack_cost: i64,
+    pub pack_size: usize,
+    pub pack_bought: bool,
+    /// One relic per visit, if the run has room for another.
+    pub relic: Option<RelicOffer>,
+}
+
+impl ShopState {
+    pub fn new(level: u32, offer_ids: &[&str], pack_size: usize, relic_id: Option<&str>) -> Self {
+        Self {
+            offers: offers_for(offer_ids),
+            reroll_cost: BASE_REROLL_COST,
+            removal_cost: BASE_REMOVAL_COST,
+            pack_cost: pack_price(level),
+            pack_size,
+            pack_bought: false,
+            relic: relic_id.and_then(relics::get).map(|relic| RelicOffer {
+                relic_id: relic.id.to_string(),
+                price: relic.price,
+                sold: false,
+            }),
+        }
+    }
+
+    pub fn restock(&mut self, offer_ids: &[&str]) {
+        self.offers = offers_for(offer_ids);
+    }
+}
+
+fn offers_for(ids: &[&str]) -> Vec<ShopOffer> {
+    ids.iter()
+        .filter_map(|id| cards::get(id))
+        .map(|def| ShopOffer {
+            def_id: def.id.to_string(),
+            price: card_price(def),
+            sold: false,
+        })
+        .collect()
+}
+
+/// Shop price for a card: half its budget, plus a premium that grows the more its kind bends
+/// the rules.
+pub fn card_price(def: &CardDef) -> i64 {
+    let premium = match def.kind {
+        CardKind::Score => 0,
+        CardKind::Economy => 1,
+        CardKind::Control => 2,
+        CardKind::Meta => 3,
+    };
+    (def.budget as i64 / 2 + premium).max(1)
+}
+
+pub fn pack_price(level: u32) -> i64 {
+    4 + level.max(1) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prices_scale_with_budget_and_kind() {
+        let tap = cards::get("tap_score").unwrap();
+        let fibo = cards::get("fibo_level").unwrap();
+        let clone = cards::get("clone_one").unwrap();
+        assert!(card_price(fibo) > card_price(tap));
+        // Same budget, but Meta carries a premium.
+        assert_eq!(tap.budget, clone.budget);
+        assert!(card_price(clone) > card_price(tap));
+        assert_eq!(card_price(tap), tap.budget as i64 / 2);
+        assert_eq!(card_price(clone), clone.budget as i64 / 2 + 3);
+    }
+
+    #[test]
+    fn every_catalog_card_has_a_positive_price() {
+        for card in cards::catalog() {
+            assert!(card_price(card) >= 1, "card {} is free", card.id);
+        }
+    }
+}
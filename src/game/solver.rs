@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+
+use crate::game::puzzles::PuzzleData;
+use crate::game::{Action, Engine, GameError, Phase, RunMode};
+use crate::vm::Limits;
+
+/// Whether the solver should stop at the cheapest lines or keep every line it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveMode {
+    /// Every solving line within the limits.
+    All,
+    /// Only lines with the fewest plays, then the fewest cards.
+    Minimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverLimits {
+    /// Upper bound on plays searched (the puzzle's own play limit still applies).
+    pub max_plays: u32,
+    /// Longest hand the solver will assemble for a single play.
+    pub max_hand: usize,
+    /// Hard cap on simulated plays; the report is marked truncated when hit.
+    pub max_nodes: usize,
+}
+
+impl Default for SolverLimits {
+    fn default() -> Self {
+        Self {
+            max_plays: 3,
+            max_hand: 6,
+            max_nodes: 200_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// Card definition ids per play, in execution order.
+    pub plays: Vec<Vec<String>>,
+    /// Actions that reproduce the line from the starting state.
+    pub actions: Vec<Action>,
+}
+
+impl Solution {
+    pub fn play_count(&self) -> usize {
+        self.plays.len()
+    }
+
+    pub fn card_count(&self) -> usize {
+        self.plays.iter().map(Vec::len).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolveReport {
+    pub solutions: Vec<Solution>,
+    /// Number of simulated plays.
+    pub nodes: usize,
+    /// `true` if `max_nodes` was hit before the search space was exhausted.
+    pub truncated: bool,
+}
+
+impl SolveReport {
+    pub fn is_solvable(&self) -> bool {
+        !self.solutions.is_empty()
+    }
+}
+
+/// Exhaustive search over hand orderings, collection picks and multi-play sequences.
+///
+/// Each play is modelled as "choose an ordered hand from every card currently in
+/// hand or collection"; unused cards stay in the collection. Empty plays are never
+/// considered. In puzzle mode a line solves when `puzzle_solved` flips; in classic
+/// mode it solves when the level is cleared.
+#[derive(Debug, Clone, Copy)]
+pub struct Solver {
+    pub mode: SolveMode,
+    pub limits: SolverLimits,
+}
+
+impl Solver {
+    pub fn new(mode: SolveMode) -> Self {
+        Self {
+            mode,
+            limits: SolverLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: SolverLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn solve_puzzle(&self, id: &str) -> Result<SolveReport, GameError> {
+        Ok(self.solve(&puzzle_engine(id)?))
+    }
+
+    pub fn solve(&self, engine: &Engine) -> SolveReport {
+        let mut report = SolveReport::default();
+        let max_plays = self.play_budget(engine);
+        if max_plays == 0 || is_goal(engine, engine.state.level) {
+            return report;
+        }
+
+        let start_level = engine.state.level;
+        let mut solutions = Vec::new();
+        let mut record = |plays: &[Vec<String>], actions: &[Action], end: &Engine| {
+            if is_goal(end, start_level) {
+                solutions.push(Solution {
+                    plays: plays.to_vec(),
+                    actions: actions.to_vec(),
+                });
+            }
+        };
+        let mut search = Search {
+            limits: self.limits,
+            start_level,
+            nodes: 0,
+            truncated: false,
+            found_any: false,
+            visit: &mut record,
+        };
+
+        match self.mode {
+            SolveMode::All => {
+                search.dfs(engine, max_plays, &mut Vec::new(), &mut Vec::new());
+            }
+            SolveMode::Minimal => {
+                // Iterative deepening so the first depth with any solution is the minimum.
+                for depth in 1..=max_plays {
+                    search.dfs(engine, depth, &mut Vec::new(), &mut Vec::new());
+                    if search.truncated || search.found_any {
+                        break;
+                    }
+                }
+            }
+        }
+        report.nodes = search.nodes;
+        report.truncated = search.truncated;
+        report.solutions = solutions;
+
+        if self.mode == SolveMode::Minimal {
+            let fewest = report.solutions.iter().map(Solution::card_count).min();
+            report.solutions.retain(|s| Some(s.card_count()) == fewest);
+        }
+
+        report
+    }
+
+    /// Visit the end state of every line within the limits, solving or not.
+    ///
+    /// Lines stop extending once they solve or fail. Returns `true` if the search was
+    /// truncated by `max_nodes`.
+    pub fn explore(&self, engine: &Engine, mut visit: impl FnMut(&[Vec<String>], &Engine)) -> bool {
+        let max_plays = self.play_budget(engine);
+        let mut adapter = |plays: &[Vec<String>], _: &[Action], end: &Engine| visit(plays, end);
+        let mut search = Search {
+            limits: self.limits,
+            start_level: engine.state.level,
+            nodes: 0,
+            truncated: false,
+            found_any: false,
+            visit: &mut adapter,
+        };
+        search.dfs(engine, max_plays, &mut Vec::new(), &mut Vec::new());
+        search.truncated
+    }
+
+    fn play_budget(&self, engine: &Engine) -> u32 {
+        let remaining = match engine.state.mode {
+            RunMode::Puzzle => engine
+                .state
+                .puzzle_play_limit
+                .map(|limit| limit.saturating_sub(engine.state.turn))
+                .unwrap_or(u32::MAX),
+            RunMode::Classic => engine.state.hands_left,
+        };
+        remaining.min(self.limits.max_plays)
+    }
+}
+
+type Visit<'a> = dyn FnMut(&[Vec<String>], &[Action], &Engine) + 'a;
+
+struct Search<'a> {
+    limits: SolverLimits,
+    start_level: u32,
+    nodes: usize,
+    truncated: bool,
+    found_any: bool,
+    visit: &'a mut Visit<'a>,
+}
+
+impl Search<'_> {
+    fn dfs(
+        &mut self,
+        engine: &Engine,
+        depth: u32,
+        plays: &mut Vec<Vec<String>>,
+        actions: &mut Vec<Action>,
+    ) {
+        if depth == 0 {
+            return;
+        }
+
+        let pool = engine
+            .state
+            .hand
+            .iter()
+            .chain(engine.state.collection.iter())
+            .map(|c| c.def_id.clone());
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for id in pool {
+            *counts.entry(id).or_default() += 1;
+        }
+
+        let mut hands = Vec::new();
+        ordered_hands(
+            &mut counts,
+            self.limits.max_hand,
+            &mut Vec::new(),
+            &mut hands,
+        );
+
+        for hand in hands {
+            if self.nodes >= self.limits.max_nodes {
+                self.truncated = true;
+                return;
+            }
+            self.nodes += 1;
+
+            let mut next = engine.clone();
+            let ids = hand.iter().map(String::as_str).collect::<Vec<_>>();
+            let Some(staged) = stage_play(&mut next, &ids) else {
+                continue;
+            };
+            if next.dispatch(Action::PlayHand).is_err() {
+                continue;
+            }
+
+            let actions_len = actions.len();
+            actions.extend(staged);
+            actions.push(Action::PlayHand);
+            plays.push(hand);
+
+            (self.visit)(plays, actions, &next);
+            if is_goal(&next, self.start_level) {
+                self.found_any = true;
+            } else if next.state.phase != Phase::GameOver {
+                self.dfs(&next, depth - 1, plays, actions);
+            }
+
+            plays.pop();
+            actions.truncate(actions_len);
+        }
+    }
+}
+
+/// Build a fresh puzzle engine in its starting state.
+pub fn puzzle_engine(id: &str) -> Result<Engine, GameError> {
+    start_engine(Action::StartPuzzle { id: id.to_string() })
+}
+
+/// Build a fresh engine for a puzzle that is not in the catalog.
+pub fn puzzle_engine_for(puzzle: &PuzzleData) -> Result<Engine, GameError> {
+    start_engine(Action::LoadPuzzle {
+        puzzle: Box::new(puzzle.clone()),
+    })
+}
+
+fn start_engine(action: Action) -> Result<Engine, GameError> {
+    let mut engine = Engine::with_deck(0, Vec::new(), Limits::default());
+    engine.dispatch(action)?;
+    engine.state.trace.clear();
+    Ok(engine)
+}
+
+/// Replay a line (card ids per play) against a puzzle and report whether it solves it.
+pub fn check_line(id: &str, plays: &[&[&str]]) -> Result<bool, GameError> {
+    replay_line(puzzle_engine(id)?, plays)
+}
+
+/// Replay a line from an arbitrary starting engine and report whether it reaches the goal.
+pub fn replay_line(mut engine: Engine, plays: &[&[&str]]) -> Result<bool, GameError> {
+    let start_level = engine.state.level;
+    for play in plays {
+        if stage_play(&mut engine, play).is_none() {
+            return Ok(false);
+        }
+        engine.dispatch(Action::PlayHand)?;
+    }
+    Ok(is_goal(&engine, start_level))
+}
+
+/// Move every hand card back to the collection, then pull `ids` into the hand in order.
+///
+/// Returns the dispatched actions, or `None` if a requested card is not available.
+fn stage_play(engine: &mut Engine, ids: &[&str]) -> Option<Vec<Action>> {
+    let mut actions = Vec::new();
+    while !engine.state.hand.is_empty() {
+        let action = Action::MoveHandToCollection { index: 0 };
+        engine.dispatch(action.clone()).ok()?;
+        actions.push(action);
+    }
+    for id in ids {
+        let index = engine
+            .state
+            .collection
+            .iter()
+            .position(|c| c.def_id == *id)?;
+        let action = Action::MoveCollectionToHand { index };
+        engine.dispatch(action.clone()).ok()?;
+        actions.push(action);
+    }
+    Some(actions)
+}
+
+fn is_goal(engine: &Engine, start_level: u32) -> bool {
+    match engine.state.mode {
+        RunMode::Puzzle => engine.state.puzzle_solved,
+        RunMode::Classic => engine.state.level > start_level,
+    }
+}
+
+/// Every non-empty ordered hand drawable from `counts`, skipping orderings that only
+/// differ by swapping copies of the same card.
+fn ordered_hands(
+    counts: &mut BTreeMap<String, usize>,
+    max_len: usize,
+    prefix: &mut Vec<String>,
+    out: &mut Vec<Vec<String>>,
+) {
+    if prefix.len() >= max_len {
+        return;
+    }
+    let ids = counts
+        .iter()
+        .filter(|(_, n)| **n > 0)
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>();
+    for id in ids {
+        if let Some(n) = counts.get_mut(&id) {
+            *n -= 1;
+        }
+        prefix.push(id.clone());
+        out.push(prefix.clone());
+        ordered_hands(counts, max_len, prefix, out);
+        prefix.pop();
+        if let Some(n) = counts.get_mut(&id) {
+            *n += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::puzzles;
+
+    #[test]
+    fn every_catalog_puzzle_is_solvable() {
+        let solver = Solver::new(SolveMode::Minimal);
+        for p in puzzles::catalog() {
+            let report = solver.solve_puzzle(p.id).unwrap();
+            assert!(report.is_solvable(), "puzzle {} has no solution", p.id);
+            assert!(!report.truncated, "search for {} was truncated", p.id);
+        }
+    }
+
+    #[test]
+    fn every_hint_line_solves_its_puzzle() {
+        for p in puzzles::catalog() {
+            assert!(
+                check_line(p.id, p.solution).unwrap(),
+                "hint line for {} does not solve it: {:?}",
+                p.id,
+                p.solution
+            );
+        }
+    }
+
+    #[test]
+    fn hint_text_names_the_solution_cards_in_play_order() {
+        for p in puzzles::catalog() {
+            let line = p.solution.concat();
+            // Each backticked snippet (or `a -> b` step) is a card name or part of a script in the line.
+            let positions = p
+                .hint
+                .split('`')
+                .skip(1)
+                .step_by(2)
+                .flat_map(|snippet| snippet.split(" -> "))
+                .map(|snippet| {
+                    line.iter()
+                        .position(|id| {
+                            let def = crate::game::cards::get(id).unwrap();
+                            def.name == snippet || def.script.contains(snippet)
+                        })
+                        .unwrap_or_else(|| panic!("{}: `{snippet}` is not in {line:?}", p.id))
+                })
+                .collect::<Vec<_>>();
+            assert!(!positions.is_empty(), "{}: hint names no card", p.id);
+            assert!(
+                positions.windows(2).all(|w| w[0] <= w[1]),
+                "{}: hint order differs from {line:?}",
+                p.id
+            );
+        }
+    }
+
+    #[test]
+    fn hint_lines_use_the_minimum_number_of_plays() {
+        let solver = Solver::new(SolveMode::Minimal);
+        for p in puzzles::catalog() {
+            let report = solver.solve_puzzle(p.id).unwrap();
+            assert_eq!(
+                p.solution.len(),
+                report.solutions[0].play_count(),
+                "puzzle {} hint uses more plays than needed",
+                p.id
+            );
+        }
+    }
+
+    #[test]
+    fn hint_lines_earn_three_stars() {
+        for p in puzzles::catalog() {
+            let mut engine = puzzle_engine(p.id).unwrap();
+            for play in p.solution {
+                stage_play(&mut engine, play).unwrap();
+                engine.dispatch(Action::PlayHand).unwrap();
+            }
+            let result = engine.state.puzzle_result.unwrap();
+            assert_eq!(result.stars, 3, "{}: {result:?}", p.id);
+        }
+    }
+
+    #[test]
+    fn minimal_mode_finds_the_clone_line() {
+        let report = Solver::new(SolveMode::Minimal)
+            .solve_puzzle("lesson_meta_clone")
+            .unwrap();
+        let lines = report
+            .solutions
+            .iter()
+            .map(|s| s.plays.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![vec![vec![
+                "tap_score".to_string(),
+                "clone_pair".to_string()
+            ]]]
+        );
+    }
+
+    #[test]
+    fn all_mode_includes_every_minimal_line() {
+        let all = Solver::new(SolveMode::All)
+            .solve_puzzle("lesson_money_loop")
+            .unwrap();
+        let minimal = Solver::new(SolveMode::Minimal)
+            .solve_puzzle("lesson_money_loop")
+            .unwrap();
+        assert!(all.solutions.len() >= minimal.solutions.len());
+        for s in &minimal.solutions {
+            assert!(all.solutions.contains(s));
+        }
+    }
+
+    #[test]
+    fn solution_actions_replay_to_a_solved_state() {
+        let report = Solver::new(SolveMode::Minimal)
+            .solve_puzzle("lesson_draw_math")
+            .unwrap();
+        let mut engine = puzzle_engine("lesson_draw_math").unwrap();
+        for action in &report.solutions[0].actions {
+            engine.dispatch(action.clone()).unwrap();
+        }
+        assert!(engine.state.puzzle_solved);
+    }
+
+    #[test]
+    fn ordered_hands_skip_duplicate_orderings() {
+        let mut counts = BTreeMap::new();
+        counts.insert("a".to_string(), 2);
+        counts.insert("b".to_string(), 1);
+        let mut out = Vec::new();
+        ordered_hands(&mut counts, 3, &mut Vec::new(), &mut out);
+        // a, b, aa, ab, ba, aab, aba, baa
+        assert_eq!(out.len(), 8);
+    }
+}
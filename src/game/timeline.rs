@@ -0,0 +1,148 @@
+use crate::game::trace::{SegmentKind, Snapshot, StateDelta, Trace, TraceEvent, TraceSegment};
+use crate::kardlang::Span;
+use crate::vm::Effect;
+
+/// One builtin call made by a card, with its evaluated arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallRecord {
+    pub step: usize,
+    pub name: String,
+    pub span: Span,
+    pub args: Vec<String>,
+    pub values: Vec<Option<i64>>,
+}
+
+/// Everything one executed card did: a lane of the hand timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardLane {
+    /// Execution order within the hand, copies included.
+    pub index: usize,
+    pub card_id: u64,
+    pub def_id: String,
+    pub name: String,
+    pub script: String,
+    pub before: Snapshot,
+    /// `None` when the hand stopped while this card ran.
+    pub after: Option<Snapshot>,
+    pub delta: Option<StateDelta>,
+    pub calls: Vec<CallRecord>,
+    pub effects: Vec<Effect>,
+    /// Other events raised by the card: relics, mutations, queue changes, errors.
+    pub notes: Vec<TraceEvent>,
+}
+
+/// Splits a hand segment into one lane per executed card.
+pub fn lanes(segment: &TraceSegment) -> Vec<CardLane> {
+    let mut lanes: Vec<CardLane> = Vec::new();
+    // Whether the newest lane is still running (between its start and end).
+    let mut open = false;
+    for event in &segment.events {
+        match event {
+            TraceEvent::CardStart {
+                index,
+                card_id,
+                def_id,
+                name,
+                script,
+                before,
+                ..
+            } => {
+                lanes.push(CardLane {
+                    index: *index,
+                    card_id: *card_id,
+                    def_id: def_id.clone(),
+                    name: name.clone(),
+                    script: script.clone(),
+                    before: before.clone(),
+                    after: None,
+                    delta: None,
+                    calls: Vec::new(),
+                    effects: Vec::new(),
+                    notes: Vec::new(),
+                });
+                open = true;
+            }
+            _ if !open => {}
+            TraceEvent::CardEnd { delta, after, .. } => {
+                let lane = lanes.last_mut().expect("open lane");
+                lane.after = Some(after.clone());
+                lane.delta = Some(delta.clone());
+                open = false;
+            }
+            TraceEvent::Call {
+                step,
+                name,
+                span,
+                args,
+                values,
+                ..
+            } => lanes.last_mut().expect("open lane").calls.push(CallRecord {
+                step: *step,
+                name: name.clone(),
+                span: *span,
+                args: args.clone(),
+                values: values.clone(),
+            }),
+            TraceEvent::EffectApplied { effect } => lanes
+                .last_mut()
+                .expect("open lane")
+                .effects
+                .push(effect.clone()),
+            other => lanes
+                .last_mut()
+                .expect("open lane")
+                .notes
+                .push(other.clone()),
+        }
+    }
+    lanes
+}
+
+/// The newest hand still in the trace, with its lanes.
+pub fn last_hand(trace: &Trace) -> Option<(&TraceSegment, Vec<CardLane>)> {
+    let segment = trace
+        .segments()
+        .iter()
+        .rev()
+        .find(|s| s.kind == SegmentKind::Hand)?;
+    Some((segment, lanes(segment)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Action, Engine};
+
+    #[test]
+    fn each_card_gets_a_lane_with_snapshots_calls_and_effects() {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_meta_clone".to_string(),
+            })
+            .unwrap();
+        let hand = engine.state.hand.iter().map(|c| c.id).collect::<Vec<_>>();
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let (segment, lanes) = last_hand(&engine.state.trace).unwrap();
+        assert_eq!(segment.kind, SegmentKind::Hand);
+        assert!(lanes.len() > hand.len(), "clone copies get lanes too");
+        assert_eq!(lanes[0].card_id, hand[0]);
+        assert_eq!(lanes[0].before.queue, hand[1..]);
+        for (i, lane) in lanes.iter().enumerate() {
+            assert_eq!(lane.index, i);
+            assert!(!lane.calls.is_empty());
+            assert!(!lane.effects.is_empty());
+            let after = lane.after.as_ref().unwrap();
+            assert_eq!(
+                after.regs.delta_from(&lane.before.regs),
+                *lane.delta.as_ref().unwrap()
+            );
+            assert!(after.pile.ends_with(&[lane.card_id]));
+            if let Some(next) = lanes.get(i + 1) {
+                assert_eq!(next.before.regs, after.regs);
+                assert_eq!(after.queue.first(), Some(&next.card_id));
+            }
+        }
+    }
+}
@@ -1,7 +1,15 @@
+use std::collections::VecDeque;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+use super::{Action, GameState};
+use crate::kardlang::Span;
 use crate::vm::Effect;
 
+/// Version written on every JSON-lines trace record; bumped when event shapes change.
+pub const TRACE_SCHEMA: u32 = 3;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StateDelta {
     pub score: i64,
@@ -12,30 +20,631 @@ pub struct StateDelta {
     pub len_hand: i64,
 }
 
+/// Register values at one point of execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registers {
+    pub score: i64,
+    pub bankroll: i64,
+    pub acc: i64,
+    pub level: u32,
+    pub target: i64,
+    pub len_source: usize,
+    pub len_deck: usize,
+    pub len_hand: usize,
+    pub len_pile: usize,
+}
+
+impl Registers {
+    pub fn capture(state: &GameState) -> Self {
+        Self {
+            score: state.score,
+            bankroll: state.bankroll,
+            acc: state.acc,
+            level: state.level,
+            target: state.target_score,
+            len_source: state.deck.len(),
+            len_deck: state.collection.len(),
+            len_hand: state.hand.len(),
+            len_pile: state.pile.len(),
+        }
+    }
+
+    pub fn delta_from(&self, before: &Registers) -> StateDelta {
+        StateDelta {
+            score: self.score - before.score,
+            bankroll: self.bankroll - before.bankroll,
+            acc: self.acc - before.acc,
+            len_source: self.len_source as i64 - before.len_source as i64,
+            len_deck: self.len_deck as i64 - before.len_deck as i64,
+            len_hand: self.len_hand as i64 - before.len_hand as i64,
+        }
+    }
+}
+
+/// Registers plus the card ids in every zone, taken around each executed card.
+///
+/// While a hand runs its cards sit in `queue`, so the hand zone is empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub regs: Registers,
+    /// Cards still waiting to run this hand, front first.
+    pub queue: Vec<u64>,
+    pub source: Vec<u64>,
+    pub bank: Vec<u64>,
+    pub pile: Vec<u64>,
+}
+
+impl Snapshot {
+    pub fn capture(state: &GameState, queue: impl IntoIterator<Item = u64>) -> Self {
+        let ids = |cards: &[super::CardInstance]| cards.iter().map(|c| c.id).collect();
+        Self {
+            regs: Registers::capture(state),
+            queue: queue.into_iter().collect(),
+            source: ids(&state.deck),
+            bank: ids(&state.collection),
+            pile: ids(&state.pile),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TraceEvent {
     Action {
-        action: String,
+        action: Action,
     },
     CardStart {
         index: usize,
+        card_id: u64,
+        def_id: String,
         name: String,
         script: String,
         budget: usize,
         cost: usize,
+        before: Snapshot,
     },
     CardEnd {
         index: usize,
+        card_id: u64,
         name: String,
         delta: StateDelta,
+        after: Snapshot,
     },
     Call {
+        /// VM steps already taken this hand.
+        step: usize,
+        card_id: u64,
         name: String,
+        /// Byte range of the call in the card's script.
+        span: Span,
+        /// Source text of each argument.
         args: Vec<String>,
+        /// Evaluated argument values (`None` where evaluation failed).
+        values: Vec<Option<i64>>,
     },
     EffectApplied {
         effect: Effect,
     },
+    RelicTriggered {
+        name: String,
+        hook: String,
+    },
+    Mutated {
+        card_id: u64,
+        from: String,
+        to: String,
+    },
+    /// A queue builtin rearranged the cards still to run this hand.
+    QueueChanged {
+        /// Canonical builtin name (`skip`, `swap`, `rev`, `pull`).
+        op: String,
+        /// Card ids still queued, front first.
+        queue: Vec<u64>,
+        /// Card ids sent to the pile by `skip`.
+        skipped: Vec<u64>,
+    },
+    /// A Classic level's target was reached; values are as of the clear.
+    LevelCleared {
+        level: u32,
+        score: i64,
+        target: i64,
+        bankroll: i64,
+        turn: u32,
+        hands_left: u32,
+    },
+    PuzzleSolved {
+        id: String,
+        stars: u8,
+    },
     Info(String),
     Error(String),
 }
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::Action { action } => write!(f, "» {action:?}"),
+            TraceEvent::CardStart {
+                index,
+                card_id,
+                name,
+                script,
+                cost,
+                budget,
+                ..
+            } => write!(
+                f,
+                "▶ #{index} {name} [card {card_id}] `{script}` ({cost}/{budget})"
+            ),
+            TraceEvent::CardEnd {
+                index, name, delta, ..
+            } => write!(
+                f,
+                "■ #{index} {name}: score {:+}, bank {:+}",
+                delta.score, delta.bankroll
+            ),
+            TraceEvent::Call {
+                step,
+                name,
+                args,
+                values,
+                ..
+            } => {
+                let shown = args
+                    .iter()
+                    .zip(values)
+                    .map(|(src, v)| match v {
+                        Some(v) if v.to_string() != *src => format!("{src}={v}"),
+                        _ => src.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "ƒ [{step}] {name}({})", shown.join(", "))
+            }
+            TraceEvent::EffectApplied { effect } => write!(f, "  → {effect}"),
+            TraceEvent::RelicTriggered { name, hook } => write!(f, "◈ {name} ({hook})"),
+            TraceEvent::Mutated { card_id, from, to } => {
+                write!(f, "mutate: card {card_id} {from} → {to}")
+            }
+            TraceEvent::QueueChanged { op, queue, skipped } => {
+                write!(f, "queue {op}: {queue:?}")?;
+                if !skipped.is_empty() {
+                    write!(f, " (skipped {skipped:?})")?;
+                }
+                Ok(())
+            }
+            TraceEvent::LevelCleared {
+                level,
+                score,
+                target,
+                bankroll,
+                ..
+            } => write!(
+                f,
+                "Level {level} cleared: score {score}/{target}, bank {bankroll}"
+            ),
+            TraceEvent::PuzzleSolved { id, stars } => write!(f, "Puzzle {id} solved ({stars}★)"),
+            TraceEvent::Info(msg) => f.write_str(msg),
+            TraceEvent::Error(msg) => write!(f, "error: {msg}"),
+        }
+    }
+}
+
+/// Hands kept by the default retention policy.
+pub const DEFAULT_KEPT_HANDS: usize = 25;
+
+/// Summaries of pruned segments kept; older ones only count toward `pruned_events`.
+pub const MAX_SUMMARIES: usize = 200;
+
+/// How much trace history a game keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TracePolicy {
+    KeepAll,
+    /// Keep the last N hand segments and everything after the oldest of them.
+    LastHands(usize),
+    /// Keep at most N events, dropping the oldest segments (or events) first.
+    RingBuffer(usize),
+}
+
+impl Default for TracePolicy {
+    fn default() -> Self {
+        TracePolicy::LastHands(DEFAULT_KEPT_HANDS)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentKind {
+    /// One `PlayHand` dispatch and everything it caused.
+    Hand,
+    /// Actions between hands: setup, moves, shop visits.
+    Between,
+}
+
+/// A run of consecutive events, opened by an `Action`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceSegment {
+    pub kind: SegmentKind,
+    pub level: u32,
+    /// Hand number for `Hand` segments; turns played so far otherwise.
+    pub turn: u32,
+    /// Sequence number of the first event.
+    pub first_seq: u64,
+    pub events: Vec<TraceEvent>,
+}
+
+impl TraceSegment {
+    pub fn summary(&self) -> SegmentSummary {
+        let mut summary = SegmentSummary {
+            kind: self.kind,
+            level: self.level,
+            turn: self.turn,
+            first_seq: self.first_seq,
+            events: self.events.len(),
+            cards: 0,
+            score: 0,
+            bankroll: 0,
+            errors: 0,
+        };
+        for event in &self.events {
+            match event {
+                TraceEvent::CardEnd { delta, .. } => {
+                    summary.cards += 1;
+                    summary.score += delta.score;
+                    summary.bankroll += delta.bankroll;
+                }
+                TraceEvent::Error(_) => summary.errors += 1,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+/// What a segment contained, kept after its events are pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentSummary {
+    pub kind: SegmentKind,
+    pub level: u32,
+    pub turn: u32,
+    pub first_seq: u64,
+    pub events: usize,
+    /// Cards that finished executing.
+    pub cards: usize,
+    /// Score and bankroll gained by those cards.
+    pub score: i64,
+    pub bankroll: i64,
+    pub errors: usize,
+}
+
+/// Trace storage: events grouped into per-hand segments, bounded by a [`TracePolicy`].
+///
+/// Every event gets a sequence number that keeps counting across pruning and `clear`, so
+/// readers can resume with [`Trace::since`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trace {
+    policy: TracePolicy,
+    segments: VecDeque<TraceSegment>,
+    summaries: VecDeque<SegmentSummary>,
+    next_seq: u64,
+    pruned_events: u64,
+}
+
+impl Trace {
+    pub fn with_policy(policy: TracePolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    pub fn policy(&self) -> TracePolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: TracePolicy) {
+        self.policy = policy;
+        self.enforce();
+    }
+
+    /// Record a dispatched action. `PlayHand` opens a hand segment; other actions join the
+    /// current between-hands segment or open one.
+    pub fn push_action(&mut self, action: Action, level: u32, turn: u32) {
+        let kind = match action {
+            Action::PlayHand => SegmentKind::Hand,
+            _ => SegmentKind::Between,
+        };
+        let joins_current = kind == SegmentKind::Between
+            && self
+                .segments
+                .back()
+                .is_some_and(|s| s.kind == SegmentKind::Between);
+        if !joins_current {
+            self.open(kind, level, turn);
+        }
+        self.push(TraceEvent::Action { action });
+    }
+
+    pub fn push(&mut self, event: TraceEvent) {
+        if self.segments.is_empty() {
+            self.open(SegmentKind::Between, 0, 0);
+        }
+        if let Some(segment) = self.segments.back_mut() {
+            segment.events.push(event);
+        }
+        self.next_seq += 1;
+        if matches!(self.policy, TracePolicy::RingBuffer(_)) {
+            self.enforce();
+        }
+    }
+
+    fn open(&mut self, kind: SegmentKind, level: u32, turn: u32) {
+        self.segments.push_back(TraceSegment {
+            kind,
+            level,
+            turn,
+            first_seq: self.next_seq,
+            events: Vec::new(),
+        });
+        self.enforce();
+    }
+
+    fn enforce(&mut self) {
+        match self.policy {
+            TracePolicy::KeepAll => {}
+            TracePolicy::LastHands(n) => {
+                let n = n.max(1);
+                let mut hands = self
+                    .segments
+                    .iter()
+                    .filter(|s| s.kind == SegmentKind::Hand)
+                    .count();
+                // Once N hands are kept, nothing before the oldest of them is.
+                while let Some(front) = self.segments.front() {
+                    let is_hand = front.kind == SegmentKind::Hand;
+                    if hands < n || (hands == n && is_hand) {
+                        break;
+                    }
+                    hands -= usize::from(is_hand);
+                    self.prune_front();
+                }
+            }
+            TracePolicy::RingBuffer(n) => {
+                let n = n.max(1);
+                while self.len() > n && self.segments.len() > 1 {
+                    self.prune_front();
+                }
+                if let Some(segment) = self.segments.front_mut() {
+                    let excess = segment.events.len().saturating_sub(n);
+                    if excess > 0 {
+                        segment.events.drain(..excess);
+                        segment.first_seq += excess as u64;
+                        self.pruned_events += excess as u64;
+                    }
+                }
+            }
+        }
+    }
+
+    fn prune_front(&mut self) {
+        let Some(segment) = self.segments.pop_front() else {
+            return;
+        };
+        self.pruned_events += segment.events.len() as u64;
+        self.summaries.push_back(segment.summary());
+        if self.summaries.len() > MAX_SUMMARIES {
+            self.summaries.pop_front();
+        }
+    }
+
+    /// Retained segments, oldest first.
+    pub fn segments(&self) -> &VecDeque<TraceSegment> {
+        &self.segments
+    }
+
+    /// Summaries of pruned segments, oldest first.
+    pub fn summaries(&self) -> &VecDeque<SegmentSummary> {
+        &self.summaries
+    }
+
+    /// Events dropped by the retention policy so far.
+    pub fn pruned_events(&self) -> u64 {
+        self.pruned_events
+    }
+
+    /// Sequence number the next event will get.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Retained events with their sequence numbers, oldest first.
+    pub fn iter_seq(&self) -> impl DoubleEndedIterator<Item = (u64, &TraceEvent)> {
+        self.segments.iter().flat_map(|s| {
+            s.events
+                .iter()
+                .enumerate()
+                .map(move |(i, e)| (s.first_seq + i as u64, e))
+        })
+    }
+
+    /// Retained events, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &TraceEvent> {
+        self.segments.iter().flat_map(|s| s.events.iter())
+    }
+
+    /// Retained events numbered `seq` or later.
+    pub fn since(&self, seq: u64) -> impl Iterator<Item = &TraceEvent> {
+        self.iter_seq()
+            .filter(move |(s, _)| *s >= seq)
+            .map(|(_, e)| e)
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.iter().map(|s| s.events.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.iter().all(|s| s.events.is_empty())
+    }
+
+    /// Drop every event and summary; sequence numbers keep counting.
+    pub fn clear(&mut self) {
+        self.segments.clear();
+        self.summaries.clear();
+        self.pruned_events = 0;
+    }
+
+    /// Retained events as JSON lines (one [`TraceLine`] per line).
+    pub fn to_json_lines(&self) -> String {
+        let mut out = String::new();
+        for (seq, event) in self.iter_seq() {
+            let line = TraceLine {
+                v: TRACE_SCHEMA,
+                seq,
+                event: event.clone(),
+            };
+            if let Ok(json) = serde_json::to_string(&line) {
+                out.push_str(&json);
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// One line of the JSON-lines trace export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceLine {
+    pub v: u32,
+    pub seq: u64,
+    pub event: TraceEvent,
+}
+
+/// Parse a JSON-lines export; blank lines are skipped.
+pub fn from_json_lines(text: &str) -> Result<Vec<TraceLine>, serde_json::Error> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Engine;
+
+    #[test]
+    fn json_lines_round_trip_a_played_hand() {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_meta_clone".to_string(),
+            })
+            .unwrap();
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let trace = &engine.state.trace;
+        let text = trace.to_json_lines();
+        assert_eq!(text.lines().count(), trace.len());
+        let lines = from_json_lines(&text).unwrap();
+        assert!(lines.iter().all(|l| l.v == TRACE_SCHEMA));
+        assert_eq!(lines.last().unwrap().seq + 1, trace.next_seq());
+        let events = lines.into_iter().map(|l| l.event).collect::<Vec<_>>();
+        assert_eq!(events, trace.iter().cloned().collect::<Vec<_>>());
+    }
+
+    fn hand(trace: &mut Trace, turn: u32, cards: usize) {
+        trace.push_action(Action::MoveCollectionToHand { index: 0 }, 1, turn - 1);
+        trace.push_action(Action::PlayHand, 1, turn);
+        for index in 0..cards {
+            trace.push(TraceEvent::Info(format!("card {index}")));
+        }
+    }
+
+    #[test]
+    fn effects_read_as_text() {
+        let applied = |effect| {
+            TraceEvent::EffectApplied { effect }
+                .to_string()
+                .trim()
+                .to_string()
+        };
+        assert_eq!(applied(Effect::AddScore(2)), "→ score +2");
+        assert_eq!(applied(Effect::AddBankroll(-3)), "→ bank -3");
+        assert_eq!(applied(Effect::MulBankroll(2)), "→ bank ×2");
+        assert_eq!(applied(Effect::Clone(1)), "→ clone ×1");
+        assert_eq!(applied(Effect::Pull(3)), "→ pull #3");
+        assert_eq!(applied(Effect::Reverse), "→ rev");
+    }
+
+    #[test]
+    fn hands_get_their_own_segments_and_old_ones_are_summarised() {
+        let mut trace = Trace::with_policy(TracePolicy::LastHands(2));
+        for turn in 1..=4 {
+            hand(&mut trace, turn, 3);
+        }
+        let kinds = trace.segments().iter().map(|s| s.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [SegmentKind::Hand, SegmentKind::Between, SegmentKind::Hand]
+        );
+        assert_eq!(trace.segments()[0].turn, 3);
+        // Two pruned hands plus the between-hands segments up to the oldest kept hand.
+        assert_eq!(trace.summaries().len(), 5);
+        assert_eq!(trace.pruned_events() as usize + trace.len(), 4 * 5);
+        assert_eq!(trace.since(trace.next_seq() - 2).count(), 2);
+
+        trace.clear();
+        assert!(trace.is_empty());
+        assert_eq!(trace.next_seq(), 20);
+    }
+
+    #[test]
+    fn ring_buffer_caps_the_event_count() {
+        let mut trace = Trace::with_policy(TracePolicy::RingBuffer(7));
+        for turn in 1..=5 {
+            hand(&mut trace, turn, 4);
+        }
+        assert!(trace.len() <= 7);
+        let seqs = trace.iter_seq().map(|(s, _)| s).collect::<Vec<_>>();
+        assert_eq!(*seqs.last().unwrap(), trace.next_seq() - 1);
+        assert!(seqs.windows(2).all(|w| w[1] == w[0] + 1));
+
+        trace.set_policy(TracePolicy::RingBuffer(2));
+        assert_eq!(trace.len(), 2);
+    }
+
+    #[test]
+    fn calls_carry_spans_values_and_card_ids() {
+        let mut engine = Engine::new(0);
+        engine
+            .dispatch(Action::StartPuzzle {
+                id: "lesson_fibo_sprint".to_string(),
+            })
+            .unwrap();
+        let card_id = engine.state.hand[0].id;
+        engine.dispatch(Action::PlayHand).unwrap();
+
+        let calls = engine
+            .state
+            .trace
+            .iter()
+            .filter_map(|e| match e {
+                TraceEvent::Call {
+                    step,
+                    card_id: id,
+                    span,
+                    values,
+                    ..
+                } => Some((*step, *id, *span, values.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // `f(L+11);s(A)` at level 5.
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0], (0, card_id, Span::new(0, 7), vec![Some(7)]));
+        assert_eq!(calls[1].0, 1);
+        assert_eq!(calls[1].3, vec![Some(13)]);
+        assert!(engine.state.trace.iter().any(|e| matches!(
+            e,
+            TraceEvent::CardEnd { card_id: id, after, .. } if *id == card_id && after.regs.score == 13
+        )));
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+use super::cards::{self, CardDef, CardKind};
+
+/// Highest upgrade level a single card can reach.
+pub const MAX_UPGRADE_LEVEL: u32 = 3;
+
+/// Bonus budget granted per upgrade level.
+pub const BUDGET_PER_LEVEL: usize = 2;
+
+/// Score a Foil card adds each time it finishes executing.
+pub const FOIL_SCORE: i64 = 2;
+
+/// Rare per-instance tag that changes how a card behaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Edition {
+    /// +`FOIL_SCORE` score whenever the card runs.
+    Foil,
+    /// Ignores its budget check.
+    Glitched,
+    /// Cannot be removed or mutated.
+    Eternal,
+}
+
+impl Edition {
+    pub const ALL: [Edition; 3] = [Edition::Foil, Edition::Glitched, Edition::Eternal];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Edition::Foil => "Foil",
+            Edition::Glitched => "Glitched",
+            Edition::Eternal => "Eternal",
+        }
+    }
+
+    pub fn doc(self) -> String {
+        match self {
+            Edition::Foil => format!("+{FOIL_SCORE} score every time this card runs."),
+            Edition::Glitched => "Ignores its budget check.".to_string(),
+            Edition::Eternal => "Cannot be removed or mutated.".to_string(),
+        }
+    }
+}
+
+/// Per-instance state layered over a catalog definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardUpgrade {
+    pub level: u32,
+    pub bonus_budget: usize,
+    /// Replaces the catalog script when set.
+    pub script: Option<String>,
+    pub edition: Option<Edition>,
+}
+
+impl CardUpgrade {
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A catalog definition with an instance's upgrades applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveDef {
+    pub base: &'static CardDef,
+    pub name: String,
+    pub kind: CardKind,
+    pub budget: usize,
+    pub script: String,
+    pub level: u32,
+    pub edition: Option<Edition>,
+}
+
+impl EffectiveDef {
+    pub fn resolve(base: &'static CardDef, upgrade: &CardUpgrade) -> Self {
+        let name = if upgrade.level > 0 {
+            format!("{} +{}", base.name, upgrade.level)
+        } else {
+            base.name.to_string()
+        };
+        Self {
+            base,
+            name,
+            kind: base.kind,
+            budget: base.budget + upgrade.bonus_budget,
+            script: upgrade
+                .script
+                .clone()
+                .unwrap_or_else(|| base.script.to_string()),
+            level: upgrade.level,
+            edition: upgrade.edition,
+        }
+    }
+
+    pub fn ignores_budget(&self) -> bool {
+        self.edition == Some(Edition::Glitched)
+    }
+}
+
+/// Script for upgrade tier `level`: the first unary literal passed to `score` or `bank` grows
+/// by one `1` per level, so upgrades raise the payout rather than counts like `clone(1)`.
+///
+/// Scripts without such a literal (e.g. `x()` or `c(1)`) only gain budget, so this returns
+/// `None`.
+pub fn tier_script(script: &str, level: u32) -> Option<String> {
+    if level == 0 {
+        return None;
+    }
+    let bytes = script.as_bytes();
+    let pays_out = |open: usize| {
+        let name_start = script[..open]
+            .rfind(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .map_or(0, |i| i + 1);
+        let name = &script[name_start..open];
+        cards::function_visual(name).is_some_and(|f| matches!(f.canonical, "score" | "bank"))
+    };
+    let start = (1..bytes.len()).find(|&i| {
+        bytes[i] == b'1'
+            && bytes[i - 1] == b'('
+            && bytes[i..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .all(|b| *b == b'1')
+            && pays_out(i - 1)
+    })?;
+    let mut out = String::with_capacity(script.len() + level as usize);
+    out.push_str(&script[..start]);
+    out.push_str(&"1".repeat(level as usize));
+    out.push_str(&script[start..]);
+    Some(out)
+}
+
+/// Shop price to raise a card from `level` to `level + 1`.
+pub fn upgrade_price(level: u32) -> i64 {
+    4 + 3 * level as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kardlang::{effective_len, parse_program};
+
+    #[test]
+    fn tier_scripts_widen_the_first_payout_literal() {
+        assert_eq!(tier_script("s(11)", 1).as_deref(), Some("s(111)"));
+        assert_eq!(
+            tier_script("c(1);s(11)", 2).as_deref(),
+            Some("c(1);s(1111)")
+        );
+        assert_eq!(
+            tier_script("k(1);bank(11)", 1).as_deref(),
+            Some("k(1);bank(111)")
+        );
+        assert_eq!(tier_script("c(1)", 1), None);
+        assert_eq!(tier_script("m();c(1)", 1), None);
+        assert_eq!(tier_script("x()", 1), None);
+        assert_eq!(tier_script("s(9)", 1), None);
+        assert_eq!(tier_script("s(11)", 0), None);
+    }
+
+    #[test]
+    fn every_tier_of_every_card_parses_and_fits_its_budget() {
+        for card in cards::catalog() {
+            for level in 1..=MAX_UPGRADE_LEVEL {
+                let upgrade = CardUpgrade {
+                    level,
+                    bonus_budget: BUDGET_PER_LEVEL * level as usize,
+                    script: tier_script(card.script, level),
+                    edition: None,
+                };
+                let def = EffectiveDef::resolve(card, &upgrade);
+                assert!(parse_program(&def.script).is_ok(), "{} L{level}", card.id);
+                assert!(
+                    effective_len(&def.script) <= def.budget,
+                    "{} L{level} over budget",
+                    card.id
+                );
+            }
+        }
+    }
+}
@@ -1,9 +1,25 @@
+/// Adjustments to the per-character cost model (relics, boss levels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CostRules {
+    /// Subtracted from each digit's cost; a digit never costs less than 1.
+    pub digit_discount: usize,
+    /// Every digit costs twice its value (applied before the discount).
+    pub double_digits: bool,
+}
+
 pub fn effective_len(source: &str) -> usize {
+    effective_len_with(source, CostRules::default())
+}
+
+pub fn effective_len_with(source: &str, rules: CostRules) -> usize {
     source
         .chars()
         .map(|c| match c {
-            '0' => 1,
-            '1'..='9' => c.to_digit(10).unwrap_or(1) as usize,
+            '0'..='9' => {
+                let base = (c.to_digit(10).unwrap_or(1) as usize).max(1);
+                let base = if rules.double_digits { base * 2 } else { base };
+                base.saturating_sub(rules.digit_discount).max(1)
+            }
             _ => 1,
         })
         .sum()
@@ -7,7 +7,7 @@ mod span;
 mod token;
 
 pub use ast::{BinOp, Call, Expr, Program};
-pub use cost::effective_len;
+pub use cost::{CostRules, effective_len, effective_len_with};
 pub use grammar::GRAMMAR;
 pub use lexer::{LexError, lex};
 pub use parser::{ParseError, parse_program};
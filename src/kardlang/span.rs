@@ -15,6 +15,10 @@ impl Span {
         self.end.saturating_sub(self.start)
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
     pub fn merge(a: Span, b: Span) -> Span {
         Span {
             start: a.start.min(b.start),
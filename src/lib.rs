@@ -2,4 +2,4 @@ pub mod game;
 pub mod kardlang;
 pub mod vm;
 
-pub use game::{Action, Engine, GameError, GameState, TraceEvent};
+pub use game::{Action, Engine, EngineObserver, GameError, GameState, TraceEvent};
@@ -9,6 +9,8 @@ mod imp {
 
     use wasm_bindgen::JsCast;
 
+    use crate::ui::state::FocusZone;
+
     #[derive(Debug, Clone, Copy)]
     pub struct Rect {
         pub left: f64,
@@ -75,23 +77,6 @@ mod imp {
         })
     }
 
-    pub fn rect_for_id(id: &str) -> Option<Rect> {
-        let Some(window) = web_sys::window() else {
-            return None;
-        };
-        let Some(doc) = window.document() else {
-            return None;
-        };
-        let el = doc.get_element_by_id(id)?;
-        let rect = el.get_bounding_client_rect();
-        Some(Rect {
-            left: rect.left(),
-            top: rect.top(),
-            width: rect.width(),
-            height: rect.height(),
-        })
-    }
-
     pub fn set_opacity_for_id(id: &str, opacity: f64) {
         let Some(window) = web_sys::window() else {
             return;
@@ -266,6 +251,41 @@ mod imp {
         None
     }
 
+    /// Whole days since 1970-01-01 (UTC).
+    pub fn today_days() -> i64 {
+        (js_sys::Date::now() / 86_400_000.0).floor() as i64
+    }
+
+    /// Seed for a fresh, unshared run.
+    pub fn fresh_seed() -> u64 {
+        let now = js_sys::Date::now() as u64;
+        let noise = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        (now << 20) ^ noise
+    }
+
+    pub fn storage_get(key: &str) -> Option<String> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        storage.get_item(key).ok()?
+    }
+
+    pub fn storage_set(key: &str, value: &str) {
+        if let Some(Ok(Some(storage))) = web_sys::window().map(|w| w.local_storage()) {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    pub fn local_puzzle_packs() -> Vec<kardinality::game::packs::PuzzlePack> {
+        // Web: no filesystem; packs arrive as uploads.
+        Vec::new()
+    }
+
+    pub fn copy_to_clipboard(text: &str) {
+        if let Some(window) = web_sys::window() {
+            // Fire-and-forget: the promise only rejects without clipboard permission.
+            let _ = window.navigator().clipboard().write_text(text);
+        }
+    }
+
     pub fn play_flip(before: HashMap<String, Rect>, duration_ms: f64) {
         let Some(window) = web_sys::window() else {
             return;
@@ -471,24 +491,18 @@ mod imp {
         cb.forget();
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
-    pub enum HitZone {
-        Hand,
-        Deck,
-    }
-
     #[derive(Debug, Clone, Copy, PartialEq)]
     pub struct HitTest {
         pub card_id: Option<u64>,
         /// Relative X within the hit card (0..1). Only set when `card_id` is Some.
         pub rel_x: Option<f64>,
         /// The zone under the pointer (hand/deck), if any.
-        pub zone: Option<HitZone>,
+        pub zone: Option<FocusZone>,
         /// If the pointer is over a drop sliver, the insertion index (0..=len).
         pub drop_index: Option<usize>,
     }
 
-    pub fn point_zone_hint(client_x: f64, client_y: f64) -> Option<HitZone> {
+    pub fn point_zone_hint(client_x: f64, client_y: f64) -> Option<FocusZone> {
         let Some(window) = web_sys::window() else {
             return None;
         };
@@ -514,12 +528,12 @@ mod imp {
             || point_in_selector(r#"[data-testid="hand-zone"] .row-scroll"#)
             || point_in_selector(r#"[data-testid="hand-zone"]"#)
         {
-            Some(HitZone::Hand)
+            Some(FocusZone::Hand)
         } else if point_in_selector(r#"[data-testid="deck-dropzone"]"#)
             || point_in_selector(r#"[data-testid="deck-zone"] .row-scroll"#)
             || point_in_selector(r#"[data-testid="deck-zone"]"#)
         {
-            Some(HitZone::Deck)
+            Some(FocusZone::Deck)
         } else {
             None
         }
@@ -562,7 +576,7 @@ mod imp {
 
         let el = doc.element_from_point(client_x as f32, client_y as f32);
 
-        let mut zone: Option<HitZone> = zone_pref;
+        let mut zone: Option<FocusZone> = zone_pref;
         if let Some(el) = el.as_ref() {
             if el
                 .closest(r#"[data-testid="hand-zone"]"#)
@@ -570,14 +584,14 @@ mod imp {
                 .flatten()
                 .is_some()
             {
-                zone = zone.or(Some(HitZone::Hand));
+                zone = zone.or(Some(FocusZone::Hand));
             } else if el
                 .closest(r#"[data-testid="deck-zone"]"#)
                 .ok()
                 .flatten()
                 .is_some()
             {
-                zone = zone.or(Some(HitZone::Deck));
+                zone = zone.or(Some(FocusZone::Deck));
             }
         }
 
@@ -596,8 +610,8 @@ mod imp {
                 .get_attribute("data-drop-zone")
                 .as_deref()
                 .and_then(|s| match s {
-                    "hand" => Some(HitZone::Hand),
-                    "deck" => Some(HitZone::Deck),
+                    "hand" => Some(FocusZone::Hand),
+                    "deck" => Some(FocusZone::Deck),
                     _ => None,
                 });
             let idx = slot
@@ -663,6 +677,8 @@ mod imp {
 mod imp {
     use std::collections::HashMap;
 
+    use crate::ui::state::FocusZone;
+
     #[derive(Debug, Clone, Copy)]
     pub struct Rect {
         pub left: f64,
@@ -704,11 +720,6 @@ mod imp {
         None
     }
 
-    pub fn rect_for_id(_id: &str) -> Option<Rect> {
-        // Desktop: no-op; used for drag overlay positioning, gracefully skips.
-        None
-    }
-
     pub fn set_opacity_for_id(_id: &str, _opacity: f64) {
         // Desktop: no-op; opacity changes are CSS-only, no DOM manipulation needed.
     }
@@ -724,6 +735,41 @@ mod imp {
         None
     }
 
+    pub fn today_days() -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| (d.as_secs() / 86_400) as i64)
+            .unwrap_or(0)
+    }
+
+    pub fn fresh_seed() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    }
+
+    pub fn storage_get(_key: &str) -> Option<String> {
+        // Desktop: no persistent storage yet.
+        None
+    }
+
+    pub fn storage_set(_key: &str, _value: &str) {
+        // Desktop: no-op.
+    }
+
+    pub fn copy_to_clipboard(_text: &str) {
+        // Desktop: no clipboard bridge yet; the code is shown as selectable text.
+    }
+
+    /// Valid packs from `./packs` next to the working directory.
+    pub fn local_puzzle_packs() -> Vec<kardinality::game::packs::PuzzlePack> {
+        kardinality::game::packs::load_dir(std::path::Path::new("packs"))
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
     pub fn add_temp_class_for_id(_id: &str, _class: &str, _ms: i32) {
         // Desktop: no-op; temporary class manipulation for "pop-in" effect, CSS-only fallback.
     }
@@ -732,17 +778,11 @@ mod imp {
         // Desktop: no-op.
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
-    pub enum HitZone {
-        Hand,
-        Deck,
-    }
-
     #[derive(Debug, Clone, Copy, PartialEq)]
     pub struct HitTest {
         pub card_id: Option<u64>,
         pub rel_x: Option<f64>,
-        pub zone: Option<HitZone>,
+        pub zone: Option<FocusZone>,
         pub drop_index: Option<usize>,
     }
 
@@ -755,7 +795,7 @@ mod imp {
         }
     }
 
-    pub fn point_zone_hint(_client_x: f64, _client_y: f64) -> Option<HitZone> {
+    pub fn point_zone_hint(_client_x: f64, _client_y: f64) -> Option<FocusZone> {
         None
     }
 }
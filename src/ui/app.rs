@@ -1,10 +1,18 @@
 use dioxus::prelude::*;
+use kardinality::game::achievements::{AchievementDef, AchievementLog};
+use kardinality::game::daily::{RunHistory, RunRecord};
+use kardinality::game::packs::PackLibrary;
+use kardinality::game::progress::PuzzleProgress;
+use kardinality::game::share;
 
 use crate::ui::anim;
-use crate::ui::state::UiSettings;
+use crate::ui::state::{
+    ACHIEVEMENTS_KEY, FocusZone, HISTORY_KEY, PACKS_KEY, PROGRESS_KEY, UiSettings,
+};
 use crate::ui::theme;
 use crate::ui::views::{
-    DeckWidget, KardinomiconModal, PileWidget, RegistersBody, Sidebar, SidebarTab,
+    AchievementToasts, DeckWidget, KardinomiconModal, PileWidget, RegistersBody, RelicsBody,
+    RunOverModal, ShopModal, Sidebar, SidebarTab,
 };
 use std::collections::{HashSet, VecDeque};
 use std::rc::Rc;
@@ -125,15 +133,6 @@ fn FxOverlayBurst(burst: FxBurst) -> Element {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum FocusZone {
-    Sidebar,
-    Deck,
-    Hand,
-    Play,
-    Shop,
-}
-
 #[derive(Debug, Clone, PartialEq)]
 struct PtrDrag {
     zone: FocusZone,
@@ -160,9 +159,139 @@ struct DragHover {
     swap_card_id: Option<u64>,
 }
 
+const SHOP_CLOSED_HINT: &str = "Shop: clear the level target to open the Lab";
+
 #[component]
 pub fn App() -> Element {
-    let mut engine = use_signal(|| kardinality::Engine::new(0));
+    let mut achievements = use_signal(|| {
+        anim::storage_get(ACHIEVEMENTS_KEY)
+            .map(|json| AchievementLog::from_json(&json))
+            .unwrap_or_default()
+    });
+    // Unlocks reported by the engine observer, waiting to be saved and toasted.
+    let mut unlocked = use_signal(Vec::<&'static AchievementDef>::new);
+    // Counts the runs started in this session, so each finished run is recorded once.
+    let mut run_serial = use_signal(|| 0u64);
+
+    // `?seed=N` reproduces a shared run; otherwise start from the default seed.
+    let mut engine = use_signal(|| {
+        let seed = anim::query_param("seed")
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        let mut eng = kardinality::Engine::new(seed);
+        // Feed every engine event to the achievement rules as it is recorded.
+        eng.subscribe(move |event: &kardinality::TraceEvent| {
+            if let kardinality::TraceEvent::Action {
+                action: kardinality::Action::NewRun { .. } | kardinality::Action::NewDailyRun { .. },
+            } = event
+            {
+                *run_serial.write() += 1;
+            }
+            let fresh = achievements.write().observe([event]);
+            if !fresh.is_empty() {
+                unlocked.write().extend(fresh);
+            }
+        });
+        if let Some(code) = anim::query_param("puzzle") {
+            let loaded = share::decode(&code)
+                .map_err(|e| e.to_string())
+                .and_then(|puzzle| {
+                    eng.dispatch(kardinality::Action::LoadPuzzle {
+                        puzzle: Box::new(puzzle),
+                    })
+                    .map_err(|e| e.to_string())
+                });
+            if let Err(e) = loaded {
+                eng.state
+                    .trace
+                    .push(kardinality::TraceEvent::Error(format!("?puzzle=: {e}")));
+            }
+        }
+        eng
+    });
+    let mut history = use_signal(|| {
+        anim::storage_get(HISTORY_KEY)
+            .map(|json| RunHistory::from_json(&json))
+            .unwrap_or_default()
+    });
+    let packs = use_signal(|| {
+        let mut library = anim::storage_get(PACKS_KEY)
+            .map(|json| PackLibrary::from_json(&json))
+            .unwrap_or_default();
+        for pack in anim::local_puzzle_packs() {
+            library.add(pack);
+        }
+        library
+    });
+    let mut progress = use_signal(|| {
+        anim::storage_get(PROGRESS_KEY)
+            .map(|json| PuzzleProgress::from_json(&json))
+            .unwrap_or_default()
+    });
+    let mut last_recorded = use_signal(|| None::<u64>);
+
+    // Record finished Classic runs into the local history (once per run).
+    use_effect(move || {
+        let run = *run_serial.peek();
+        let record = {
+            let eng = engine.read();
+            if eng.state.phase != kardinality::game::Phase::GameOver {
+                return;
+            }
+            let Some(record) = RunRecord::from_state(&eng.state) else {
+                return;
+            };
+            record
+        };
+        // Two runs can finish with equal records; only the same run is skipped.
+        if *last_recorded.peek() == Some(run) {
+            return;
+        }
+        last_recorded.set(Some(run));
+        let mut h = history.write();
+        h.push(record);
+        anim::storage_set(HISTORY_KEY, &h.to_json());
+    });
+
+    // Keep the best result per solved puzzle.
+    use_effect(move || {
+        let (id, result) = {
+            let eng = engine.read();
+            match (&eng.state.puzzle_id, eng.state.puzzle_result) {
+                (Some(id), Some(result)) => (id.clone(), result),
+                _ => return,
+            }
+        };
+        if progress
+            .peek()
+            .get(&id)
+            .is_some_and(|best| !result.beats(best))
+        {
+            return;
+        }
+        let mut p = progress.write();
+        p.record(&id, result);
+        anim::storage_set(PROGRESS_KEY, &p.to_json());
+    });
+
+    let mut toasts = use_signal(Vec::<(u64, &'static AchievementDef)>::new);
+
+    // Toast and persist new unlocks outside the dispatch that produced them.
+    use_effect(move || {
+        if unlocked.read().is_empty() {
+            return;
+        }
+        let fresh = std::mem::take(&mut *unlocked.write());
+        anim::storage_set(ACHIEVEMENTS_KEY, &achievements.peek().to_json());
+        for def in fresh {
+            let key = anim::fresh_seed();
+            toasts.write().push((key, def));
+            spawn(async move {
+                anim::sleep_ms(4000).await;
+                toasts.write().retain(|(k, _)| *k != key);
+            });
+        }
+    });
     let tab = use_signal(|| SidebarTab::Controls);
 
     let settings = use_signal(UiSettings::default);
@@ -179,21 +308,21 @@ pub fn App() -> Element {
 
     // Playback / animated execution state (UI-only).
     let pb_active = use_signal(|| false);
-    let pb_cards = use_signal(|| Vec::<FxCard>::new());
-    let pb_bumps = use_signal(|| Vec::<FxBump>::new());
-    let pb_projs = use_signal(|| Vec::<FxProj>::new());
-    let pb_bursts = use_signal(|| Vec::<FxBurst>::new());
+    let pb_cards = use_signal(Vec::<FxCard>::new);
+    let pb_bumps = use_signal(Vec::<FxBump>::new);
+    let pb_projs = use_signal(Vec::<FxProj>::new);
+    let pb_bursts = use_signal(Vec::<FxBurst>::new);
     let pb_score = use_signal(|| 0i64);
     let pb_bank = use_signal(|| 0i64);
     let pb_len_deck = use_signal(|| 0i64);
     let pb_len_source = use_signal(|| 0i64);
     let pb_len_pile = use_signal(|| 0i64);
-    let pb_pile_recent = use_signal(|| Vec::<String>::new());
+    let pb_pile_recent = use_signal(Vec::<String>::new);
     let pb_deck_shake = use_signal(|| false);
     // Playback tooltip near the executing card: x,y,text,class
     let pb_step = use_signal(|| None::<(f64, f64, String, &'static str)>);
-    let pb_view_hand = use_signal(|| Vec::<kardinality::game::CardInstance>::new());
-    let pb_view_deck = use_signal(|| Vec::<kardinality::game::CardInstance>::new());
+    let pb_view_hand = use_signal(Vec::<kardinality::game::CardInstance>::new);
+    let pb_view_deck = use_signal(Vec::<kardinality::game::CardInstance>::new);
 
     let engine_read = engine.read();
     let state = &engine_read.state;
@@ -206,6 +335,9 @@ pub fn App() -> Element {
     let focus_value = focus();
     let selected_collection = sel_collection();
     let selected_hand = sel_hand();
+    let can_discard = engine_read.is_legal(&kardinality::Action::Discard {
+        indices: vec![selected_collection],
+    });
     let drag_value = drag();
     let _dragging_id = drag_value.as_ref().map(|d| d.card.id);
 
@@ -243,7 +375,7 @@ pub fn App() -> Element {
             .iter()
             .rev()
             .take(12)
-            .filter_map(|c| c.def().map(|d| d.name.to_string()))
+            .filter_map(|c| c.effective_def().map(|d| d.name))
             .collect::<Vec<_>>()
     };
 
@@ -258,42 +390,24 @@ pub fn App() -> Element {
     }
 
     let start_playback: Rc<dyn Fn()> = {
-        let engine = engine.clone();
-        let focus = focus.clone();
-        let pb_active = pb_active.clone();
-        let pb_cards = pb_cards.clone();
-        let pb_bumps = pb_bumps.clone();
-        let pb_projs = pb_projs.clone();
-        let pb_bursts = pb_bursts.clone();
-        let pb_score = pb_score.clone();
-        let pb_bank = pb_bank.clone();
-        let pb_len_deck = pb_len_deck.clone();
-        let pb_len_source = pb_len_source.clone();
-        let pb_len_pile = pb_len_pile.clone();
-        let pb_pile_recent = pb_pile_recent.clone();
-        let pb_deck_shake = pb_deck_shake.clone();
-        let pb_step = pb_step.clone();
-        let pb_view_hand = pb_view_hand.clone();
-        let pb_view_deck = pb_view_deck.clone();
-
         Rc::new(move || {
-            let mut engine = engine.clone();
-            let mut focus = focus.clone();
-            let mut pb_active = pb_active.clone();
-            let mut pb_cards = pb_cards.clone();
-            let mut pb_bumps = pb_bumps.clone();
-            let mut pb_projs = pb_projs.clone();
-            let mut pb_bursts = pb_bursts.clone();
-            let mut pb_score = pb_score.clone();
-            let mut pb_bank = pb_bank.clone();
-            let mut pb_len_deck = pb_len_deck.clone();
-            let mut pb_len_source = pb_len_source.clone();
-            let mut pb_len_pile = pb_len_pile.clone();
-            let mut pb_pile_recent = pb_pile_recent.clone();
-            let mut pb_deck_shake = pb_deck_shake.clone();
-            let mut pb_step = pb_step.clone();
-            let mut pb_view_hand = pb_view_hand.clone();
-            let mut pb_view_deck = pb_view_deck.clone();
+            let mut engine = engine;
+            let mut focus = focus;
+            let mut pb_active = pb_active;
+            let mut pb_cards = pb_cards;
+            let mut pb_bumps = pb_bumps;
+            let mut pb_projs = pb_projs;
+            let mut pb_bursts = pb_bursts;
+            let mut pb_score = pb_score;
+            let mut pb_bank = pb_bank;
+            let mut pb_len_deck = pb_len_deck;
+            let mut pb_len_source = pb_len_source;
+            let mut pb_len_pile = pb_len_pile;
+            let mut pb_pile_recent = pb_pile_recent;
+            let mut pb_deck_shake = pb_deck_shake;
+            let mut pb_step = pb_step;
+            let mut pb_view_hand = pb_view_hand;
+            let mut pb_view_deck = pb_view_deck;
 
             if pb_active() {
                 return;
@@ -307,7 +421,7 @@ pub fn App() -> Element {
                 pre_len_deck,
                 pre_len_source,
                 pre_len_pile,
-                pre_trace_len,
+                pre_trace_seq,
             ) = {
                 let pre = engine.read();
                 (
@@ -317,7 +431,7 @@ pub fn App() -> Element {
                     pre.state.collection.len() as i64,
                     pre.state.deck.len() as i64,
                     pre.state.pile.len() as i64,
-                    pre.state.trace.len(),
+                    pre.state.trace.next_seq(),
                 )
             };
             if pre_hand.is_empty() {
@@ -381,28 +495,27 @@ pub fn App() -> Element {
                 .read()
                 .state
                 .trace
-                .iter()
-                .skip(pre_trace_len)
+                .since(pre_trace_seq)
                 .cloned()
                 .collect();
 
             // Run playback asynchronously.
-            let engine2 = engine.clone();
-            let pb_cards2 = pb_cards.clone();
-            let pb_bumps2 = pb_bumps.clone();
-            let pb_projs2 = pb_projs.clone();
-            let pb_bursts2 = pb_bursts.clone();
-            let pb_score2 = pb_score.clone();
-            let pb_bank2 = pb_bank.clone();
-            let pb_len_deck2 = pb_len_deck.clone();
-            let pb_len_source2 = pb_len_source.clone();
-            let pb_len_pile2 = pb_len_pile.clone();
-            let pb_pile_recent2 = pb_pile_recent.clone();
-            let pb_active2 = pb_active.clone();
-            let pb_deck_shake2 = pb_deck_shake.clone();
-            let pb_step2 = pb_step.clone();
-            let pb_view_hand2 = pb_view_hand.clone();
-            let pb_view_deck2 = pb_view_deck.clone();
+            let engine2 = engine;
+            let pb_cards2 = pb_cards;
+            let pb_bumps2 = pb_bumps;
+            let pb_projs2 = pb_projs;
+            let pb_bursts2 = pb_bursts;
+            let pb_score2 = pb_score;
+            let pb_bank2 = pb_bank;
+            let pb_len_deck2 = pb_len_deck;
+            let pb_len_source2 = pb_len_source;
+            let pb_len_pile2 = pb_len_pile;
+            let pb_pile_recent2 = pb_pile_recent;
+            let pb_active2 = pb_active;
+            let pb_deck_shake2 = pb_deck_shake;
+            let pb_step2 = pb_step;
+            let pb_view_hand2 = pb_view_hand;
+            let pb_view_deck2 = pb_view_deck;
 
             spawn(async move {
                 let mut pb_cards2 = pb_cards2;
@@ -439,12 +552,6 @@ pub fn App() -> Element {
                     .filter(|c| !pre_ids.contains(&c.id))
                     .collect();
 
-                // Build a queue of overlay ids in hand order.
-                let mut queue: VecDeque<u64> = VecDeque::new();
-                for c in &pre_hand {
-                    queue.push_back(c.id);
-                }
-
                 let mut current: Option<u64> = None;
                 let mut executed_count: usize = 0;
 
@@ -464,7 +571,7 @@ pub fn App() -> Element {
                         next_bump_id = next_bump_id.saturating_add(1);
                         pb_bumps2.write().push(bump.clone());
 
-                        let mut pb_bumps3 = pb_bumps2.clone();
+                        let mut pb_bumps3 = pb_bumps2;
                         spawn(async move {
                             anim::sleep_ms(920).await;
                             pb_bumps3.write().retain(|b| b.id != bump.id);
@@ -497,8 +604,8 @@ pub fn App() -> Element {
                     next_proj_id = next_proj_id.saturating_add(1);
                     pb_projs2.write().push(proj.clone());
 
-                    let mut pb_projs3 = pb_projs2.clone();
-                    let mut pb_bursts3 = pb_bursts2.clone();
+                    let mut pb_projs3 = pb_projs2;
+                    let mut pb_bursts3 = pb_bursts2;
                     spawn(async move {
                         anim::sleep_ms(16).await;
                         {
@@ -523,11 +630,13 @@ pub fn App() -> Element {
 
                 for evt in post_events {
                     match evt {
-                        kardinality::TraceEvent::CardStart { .. } => {
-                            // Pop next overlay card from the queue and focus it in place.
-                            let Some(id) = queue.pop_front() else {
+                        kardinality::TraceEvent::CardStart { card_id, .. } => {
+                            // Focus the executing card in place; clones have no overlay card.
+                            if !pb_cards2.read().iter().any(|c| c.id == card_id) {
+                                current = None;
                                 continue;
-                            };
+                            }
+                            let id = card_id;
                             current = Some(id);
 
                             // Hide the real card so layout stays stable but only the overlay is visible.
@@ -555,7 +664,7 @@ pub fn App() -> Element {
                                 let name = pre_hand
                                     .iter()
                                     .find(|c| c.id == id)
-                                    .and_then(|c| c.def().map(|d| d.name.to_string()))
+                                    .and_then(|c| c.effective_def().map(|d| d.name))
                                     .unwrap_or_else(|| "Card".to_string());
                                 let x = card.left + card.width * 0.5 + card.tx;
                                 let y = card.top + card.height * 0.12 + card.ty;
@@ -563,7 +672,7 @@ pub fn App() -> Element {
                             }
                             anim::sleep_ms(180).await;
                         }
-                        kardinality::TraceEvent::Call { name, args } => {
+                        kardinality::TraceEvent::Call { name, values, .. } => {
                             if let Some(id) = current {
                                 // Anchor to the overlay card (the real DOM card may not exist during playback).
                                 let cards = pb_cards2.read();
@@ -573,7 +682,15 @@ pub fn App() -> Element {
                                     pb_step2.set(Some((
                                         x,
                                         y,
-                                        format!("ƒ {name}({})", args.join(", ")),
+                                        format!(
+                                            "ƒ {name}({})",
+                                            values
+                                                .iter()
+                                                .map(|v| v
+                                                    .map_or("?".to_string(), |v| v.to_string()))
+                                                .collect::<Vec<_>>()
+                                                .join(", ")
+                                        ),
                                         "call",
                                     )));
                                     anim::sleep_ms(260).await;
@@ -664,7 +781,7 @@ pub fn App() -> Element {
                                         "info",
                                     );
                                     pb_deck_shake2.set(true);
-                                    let mut pb_deck_shake3 = pb_deck_shake2.clone();
+                                    let mut pb_deck_shake3 = pb_deck_shake2;
                                     spawn(async move {
                                         anim::sleep_ms(520).await;
                                         pb_deck_shake3.set(false);
@@ -767,10 +884,43 @@ pub fn App() -> Element {
                                     anim::sleep_ms(620).await;
                                     push_bump("deck-zone", "mutate()".to_string(), "info");
                                 }
+                                kardinality::vm::Effect::Skip(_)
+                                | kardinality::vm::Effect::SwapNext
+                                | kardinality::vm::Effect::Reverse
+                                | kardinality::vm::Effect::Pull(_) => {
+                                    // The QueueChanged event that precedes this carries the details.
+                                    pb_step2.set(Some((
+                                        from_x,
+                                        from_y - 44.0,
+                                        effect.to_string(),
+                                        "info",
+                                    )));
+                                    anim::sleep_ms(320).await;
+                                }
                             }
                             // Next step will overwrite this; clear if we stay idle.
                             pb_step2.set(None);
                         }
+                        kardinality::TraceEvent::QueueChanged { op, skipped, .. } => {
+                            push_bump("hand-zone", format!("{op}()"), "info");
+                            // Skipped hand cards never start; drop them straight onto the Pile.
+                            for id in skipped {
+                                if !pb_cards2.read().iter().any(|c| c.id == id) {
+                                    continue;
+                                }
+                                if let Some(card) =
+                                    pb_cards2.write().iter_mut().find(|c| c.id == id)
+                                {
+                                    card.opacity = 0.0;
+                                    card.scale = 0.86;
+                                }
+                                anim::sleep_ms(180).await;
+                                pb_cards2.write().retain(|c| c.id != id);
+                                pb_view_hand2.write().retain(|c| c.id != id);
+                                *pb_len_pile2.write() += 1;
+                                anim::set_opacity_for_id(&format!("card-{id}"), 1.0);
+                            }
+                        }
                         kardinality::TraceEvent::CardEnd { .. } => {
                             let Some(id) = current.take() else { continue };
 
@@ -806,12 +956,12 @@ pub fn App() -> Element {
                             pb_view_hand2.write().retain(|c| c.id != id);
 
                             // Update Pile recent list (most recent first).
-                            if let Some(card) = pre_hand.iter().find(|c| c.id == id) {
-                                if let Some(def) = kardinality::game::cards::get(&card.def_id) {
-                                    let mut v = pb_pile_recent2.write();
-                                    v.insert(0, def.name.to_string());
-                                    v.truncate(12);
-                                }
+                            if let Some(card) = pre_hand.iter().find(|c| c.id == id)
+                                && let Some(def) = kardinality::game::cards::get(&card.def_id)
+                            {
+                                let mut v = pb_pile_recent2.write();
+                                v.insert(0, def.name.to_string());
+                                v.truncate(12);
                             }
                             // Restore opacity if the DOM element still exists (it might not).
                             anim::set_opacity_for_id(&format!("card-{id}"), 1.0);
@@ -919,12 +1069,11 @@ pub fn App() -> Element {
                 anim::start_focus_halo_loop();
 
                 // E2E / debug harness: allow pre-filling the Deck with many cards via `?prefill=N`.
-                if let Some(v) = anim::query_param("prefill") {
-                    if let Ok(n) = v.parse::<usize>() {
+                if let Some(v) = anim::query_param("prefill")
+                    && let Ok(n) = v.parse::<usize>() {
                         let mut eng = engine.write();
                         let _ = eng.dispatch(kardinality::Action::DrawToCollection { count: n });
                     }
-                }
             },
             onpointermove: move |evt: PointerEvent| {
                 let Some(mut d) = drag() else {
@@ -954,11 +1103,7 @@ pub fn App() -> Element {
                     let st = engine.read();
 
                     let mut next: Option<DragHover> = None;
-                    if let (Some(z), Some(idx)) = (hit.zone, hit.drop_index) {
-                        let zone = match z {
-                            anim::HitZone::Hand => FocusZone::Hand,
-                            anim::HitZone::Deck => FocusZone::Deck,
-                        };
+                    if let (Some(zone), Some(idx)) = (hit.zone, hit.drop_index) {
                         next = Some(DragHover {
                             zone,
                             insert_index: Some(idx),
@@ -1034,17 +1179,7 @@ pub fn App() -> Element {
                     let mut target_zone: Option<FocusZone> = None;
                     let mut target_index: Option<usize> = None;
                     let mut swap: bool = false;
-                    let preferred_zone = zone_hint
-                        .map(|z| match z {
-                            anim::HitZone::Hand => FocusZone::Hand,
-                            anim::HitZone::Deck => FocusZone::Deck,
-                        })
-                        .or_else(|| {
-                            hit.zone.map(|z| match z {
-                                anim::HitZone::Hand => FocusZone::Hand,
-                                anim::HitZone::Deck => FocusZone::Deck,
-                            })
-                        });
+                    let preferred_zone = zone_hint.or(hit.zone);
 
                     if let Some(id) = hit.card_id {
                         let hand_pos = eng.state.hand.iter().position(|c| c.id == id);
@@ -1081,8 +1216,8 @@ pub fn App() -> Element {
                     // * If we hit a drop sliver: insert at its boundary index.
                     // * Else if we hit a card: before/after insert, or swap if centered.
                     let mut insert_at: Option<usize> = hit.drop_index;
-                    if insert_at.is_none() {
-                        if let Some(ti) = target_index {
+                    if insert_at.is_none()
+                        && let Some(ti) = target_index {
                             let rel = hit.rel_x.unwrap_or(0.5);
                             let hint = if rel < 0.28 { 0 } else if rel > 0.72 { 2 } else { 1 }; // 0 before,1 swap,2 after
                             if hint == 1 {
@@ -1091,15 +1226,14 @@ pub fn App() -> Element {
                                 insert_at = Some(if hint == 0 { ti } else { ti.saturating_add(1) });
                             }
                         }
-                    }
 
                     // Pointer-up can miss precise card/slit targeting due overlap; fall back to
                     // last hover intent only when hit-test didn't already indicate a cross-zone drop.
                     let no_specific_target = target_index.is_none() && insert_at.is_none() && !swap;
                     let should_use_hover = no_specific_target
                         && (target_zone.is_none() || target_zone == Some(src_zone));
-                    if should_use_hover {
-                        if let Some(h) = hover {
+                    if should_use_hover
+                        && let Some(h) = hover {
                             target_zone = Some(h.zone);
                             if let Some(id) = h.swap_card_id {
                                 swap = true;
@@ -1115,7 +1249,6 @@ pub fn App() -> Element {
                                 insert_at = Some(ins);
                             }
                         }
-                    }
 
                     // Last-resort fallback: if we couldn't resolve any drop zone, route across zones.
                     // This keeps drag-and-drop responsive when pointer hit-testing is blocked by overlap.
@@ -1418,7 +1551,7 @@ pub fn App() -> Element {
                             let max = match tab() {
                                 SidebarTab::Controls => 1usize,
                                 SidebarTab::Debug => 0usize,
-                                SidebarTab::Settings => 0usize,
+                                SidebarTab::Editor | SidebarTab::Settings => 0usize,
                             };
                             sidebar_index.set((idx + 1).min(max));
                             return;
@@ -1453,7 +1586,7 @@ pub fn App() -> Element {
                                     let mut eng = engine.write();
                                     match idx {
                                         0 => {
-                                            if let Err(e) = eng.dispatch(kardinality::Action::NewRun { seed: 0 }) {
+                                            if let Err(e) = eng.dispatch(kardinality::Action::NewRun { seed: anim::fresh_seed(), config: None }) {
                                                 eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
                                             }
                                         }
@@ -1468,7 +1601,7 @@ pub fn App() -> Element {
                                     let mut eng = engine.write();
                                     let _ = eng.dispatch(kardinality::Action::ClearTrace);
                                 }
-                                SidebarTab::Settings => {}
+                                SidebarTab::Editor | SidebarTab::Settings => {}
                             }
                             return;
                         }
@@ -1480,7 +1613,7 @@ pub fn App() -> Element {
                             let mut eng = engine.write();
                             eng.state
                                 .trace
-                                .push(kardinality::TraceEvent::Info("Shop: coming soon".to_string()));
+                                .push(kardinality::TraceEvent::Info(SHOP_CLOSED_HINT.to_string()));
                         }
                     }
                     Key::Tab => {
@@ -1518,6 +1651,10 @@ pub fn App() -> Element {
                 settings,
                 kardinomicon_open,
                 kardinomicon_target,
+                history,
+                packs,
+                progress,
+                achievements,
                 focused: focus_value == FocusZone::Sidebar,
                 focus_index: sidebar_index(),
             }
@@ -1554,11 +1691,11 @@ pub fn App() -> Element {
                                         button {
                                             class: if focus_value == FocusZone::Shop { "play-btn focused shop-btn" } else { "play-btn shop-btn" },
                                             "data-testid": "shop",
-                                            title: "Shop (coming soon)",
+                                            title: "Shop (opens after each level clear)",
                                             onclick: move |_| {
                                                 focus.set(FocusZone::Shop);
                                                 let mut eng = engine.write();
-                                                eng.state.trace.push(kardinality::TraceEvent::Info("Shop: coming soon".to_string()));
+                                                eng.state.trace.push(kardinality::TraceEvent::Info(SHOP_CLOSED_HINT.to_string()));
                                             },
                                             div { class: "play-head",
                                                 span { class: "play-icon", "◆" }
@@ -1582,6 +1719,22 @@ pub fn App() -> Element {
                                             span { class: "strip-k", "Bank" }
                                             span { class: "strip-v", "data-testid": "money-value", "${display_bank}" }
                                         }
+                                        if state.mode == kardinality::game::RunMode::Classic {
+                                            div { class: "strip-item",
+                                                span { class: "strip-k", "Hands" }
+                                                span { class: "strip-v", "data-testid": "hands-value", "{state.hands_left}" }
+                                            }
+                                            div { class: "strip-item",
+                                                span { class: "strip-k", "Discards" }
+                                                span { class: "strip-v", "data-testid": "discards-value", "{state.discards_left}" }
+                                            }
+                                        }
+                                        if let Some(boss) = state.boss.filter(|_| state.phase == kardinality::game::Phase::InLevel) {
+                                            div { class: "strip-item boss", title: "{boss.doc()}",
+                                                span { class: "strip-k", "Boss" }
+                                                span { class: "strip-v", "data-testid": "boss-value", "{boss.name()}" }
+                                            }
+                                        }
                                     }
 
                                     div { class: "run-progress",
@@ -1600,6 +1753,13 @@ pub fn App() -> Element {
                             pile_count: display_pile_count,
                             acc: state.acc,
                             level: state.level,
+                            source_hidden: state.source_frozen(),
+                        }
+                    }
+
+                    if state.mode == kardinality::game::RunMode::Classic {
+                        div { class: "panel hud-panel", "data-testid": "relics-panel",
+                            RelicsBody { relics: state.relics.clone() }
                         }
                     }
 
@@ -1608,6 +1768,7 @@ pub fn App() -> Element {
                             deck_count: display_source_count,
                             collection_count: display_collection_count,
                             level: state.level,
+                            frozen: state.source_frozen(),
                         }
                     }
                 }
@@ -1750,6 +1911,24 @@ pub fn App() -> Element {
                     div { class: "hand-title",
                         span { "Code Bank" }
                         span { class: "hint", "{display_collection_count} cards • drag cards into Program Queue" }
+                        if state.mode == kardinality::game::RunMode::Classic {
+                            button {
+                                class: "btn secondary",
+                                "data-testid": "discard",
+                                title: "Cycle the selected card to the pile and draw a replacement",
+                                disabled: pb_active() || !can_discard,
+                                onclick: move |_| {
+                                    let idx = sel_collection();
+                                    let mut eng = engine.write();
+                                    if let Err(e) = eng.dispatch(kardinality::Action::Discard { indices: vec![idx] }) {
+                                        eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                                    }
+                                    let len = eng.state.collection.len();
+                                    sel_collection.set(if len == 0 { 0 } else { idx.min(len - 1) });
+                                },
+                                "Discard ({state.discards_left})"
+                            }
+                        }
                     }
 
                     div { class: "row-scroll",
@@ -1876,6 +2055,10 @@ pub fn App() -> Element {
                 }
             }
 
+            ShopModal { engine }
+            RunOverModal { engine }
+            AchievementToasts { toasts }
+
             KardinomiconModal {
                 open: kardinomicon_open(),
                 target: kardinomicon_target(),
@@ -1,3 +1,13 @@
+/// Which panel currently holds keyboard focus; drag hit-tests report zones in the same terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusZone {
+    Sidebar,
+    Deck,
+    Hand,
+    Play,
+    Shop,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UiTheme {
     Crt,
@@ -38,3 +48,15 @@ impl Default for UiSettings {
         }
     }
 }
+
+/// localStorage key for the run history (`kardinality::game::daily::RunHistory`, JSON).
+pub const HISTORY_KEY: &str = "kardinality.history";
+
+/// localStorage key for loaded puzzle packs (`kardinality::game::packs::PackLibrary`, JSON).
+pub const PACKS_KEY: &str = "kardinality.packs";
+
+/// localStorage key for best puzzle results (`kardinality::game::progress::PuzzleProgress`, JSON).
+pub const PROGRESS_KEY: &str = "kardinality.progress";
+
+/// localStorage key for unlocked achievements (`kardinality::game::achievements::AchievementLog`, JSON).
+pub const ACHIEVEMENTS_KEY: &str = "kardinality.achievements";
@@ -2626,7 +2626,8 @@ body {
 
 .run-strip {
   display: grid;
-  grid-template-columns: repeat(3, minmax(0, 1fr));
+  grid-auto-flow: column;
+  grid-auto-columns: minmax(0, 1fr);
   gap: 8px;
 }
 
@@ -3319,6 +3320,428 @@ body {
   80% { transform: translateX(1px); }
 }
 
+.shop-modal {
+  width: min(880px, calc(100vw - 48px));
+}
+
+.shop-bank {
+  display: flex;
+  align-items: baseline;
+  gap: 8px;
+  font-size: 18px;
+}
+
+.shop-body {
+  display: grid;
+  gap: 10px;
+  padding: 14px;
+  overflow: auto;
+}
+
+.shop-offers {
+  display: grid;
+  grid-template-columns: repeat(auto-fill, minmax(180px, 1fr));
+  gap: 10px;
+}
+
+.shop-offer {
+  display: grid;
+  gap: 8px;
+  padding: 10px;
+  border-radius: 12px;
+  border: 1px solid rgba(130, 172, 248, 0.22);
+  background: rgba(8, 12, 22, 0.58);
+}
+
+.shop-offer.sold {
+  opacity: 0.45;
+}
+
+.shop-offer-head {
+  display: flex;
+  justify-content: space-between;
+  gap: 6px;
+}
+
+.shop-offer-name {
+  font-weight: 700;
+}
+
+.shop-offer-kind {
+  font-size: 11px;
+  color: var(--muted);
+}
+
+.shop-actions,
+.shop-removals {
+  display: flex;
+  flex-wrap: wrap;
+  gap: 8px;
+}
+
+.shop-continue {
+  justify-self: end;
+}
+
+.share-code {
+  max-width: 60%;
+  overflow-wrap: anywhere;
+  font-size: 11px;
+}
+
+.editor-field {
+  display: grid;
+  gap: 4px;
+  margin-top: 8px;
+  font-size: 12px;
+  color: var(--muted);
+}
+
+.editor-field input,
+.editor-field textarea,
+.editor-field select {
+  font: inherit;
+  color: inherit;
+  background: rgba(8, 14, 30, 0.7);
+  border: 1px solid rgba(130, 172, 248, 0.3);
+  border-radius: 6px;
+  padding: 4px 6px;
+}
+
+.editor-field textarea {
+  min-height: 54px;
+  resize: vertical;
+}
+
+.editor-actions {
+  display: flex;
+  flex-wrap: wrap;
+  gap: 6px;
+  margin: 10px 0 6px;
+}
+
+.trace-segment {
+  margin-top: 6px;
+}
+
+.trace-segment-head {
+  display: flex;
+  gap: 6px;
+  width: 100%;
+  padding: 4px 6px;
+  font: inherit;
+  font-size: 12px;
+  text-align: left;
+  color: inherit;
+  background: rgba(8, 14, 30, 0.55);
+  border: 1px solid rgba(130, 172, 248, 0.22);
+  border-radius: 6px;
+  cursor: pointer;
+}
+
+.trace-segment-head.open {
+  border-color: rgba(95, 214, 255, 0.45);
+}
+
+.trace-item.pruned {
+  opacity: 0.6;
+  font-style: italic;
+}
+
+.timeline-lanes {
+  display: flex;
+  gap: 4px;
+  overflow-x: auto;
+  padding-bottom: 4px;
+}
+
+.timeline-lane {
+  flex: 0 0 auto;
+  padding: 4px 8px;
+  font: inherit;
+  font-size: 11px;
+  color: inherit;
+  background: rgba(8, 14, 30, 0.55);
+  border: 1px solid rgba(130, 172, 248, 0.22);
+  border-radius: 6px;
+  cursor: pointer;
+}
+
+.timeline-lane.active {
+  border-color: rgba(95, 214, 255, 0.75);
+  box-shadow: 0 0 10px rgba(95, 214, 255, 0.25);
+}
+
+.timeline-scrubber {
+  width: 100%;
+  margin: 6px 0;
+}
+
+.timeline-script {
+  display: block;
+  margin: 4px 0;
+  font-size: 11px;
+}
+
+.timeline-regs {
+  width: 100%;
+  margin-bottom: 6px;
+  font-size: 11px;
+  border-collapse: collapse;
+}
+
+.timeline-regs th,
+.timeline-regs td {
+  padding: 2px 4px;
+  text-align: right;
+}
+
+.timeline-regs td:first-child {
+  text-align: left;
+  color: var(--muted);
+}
+
+.achievement-grid {
+  display: grid;
+  grid-template-columns: repeat(auto-fill, minmax(96px, 1fr));
+  gap: 6px;
+}
+
+.achievement {
+  display: grid;
+  justify-items: center;
+  gap: 2px;
+  padding: 6px 4px;
+  border: 1px dashed rgba(130, 172, 248, 0.25);
+  border-radius: 8px;
+  font-size: 11px;
+  text-align: center;
+  opacity: 0.55;
+}
+
+.achievement.unlocked {
+  border-style: solid;
+  border-color: rgba(255, 211, 107, 0.55);
+  opacity: 1;
+}
+
+.achievement-icon {
+  font-size: 20px;
+}
+
+.toast-stack {
+  position: fixed;
+  right: 18px;
+  bottom: 18px;
+  z-index: 60;
+  display: grid;
+  gap: 8px;
+  pointer-events: none;
+}
+
+.toast {
+  display: flex;
+  align-items: center;
+  gap: 10px;
+  min-width: 220px;
+  padding: 10px 14px;
+  background: rgba(10, 18, 38, 0.94);
+  border: 1px solid rgba(255, 211, 107, 0.6);
+  border-radius: 10px;
+  box-shadow: 0 8px 24px rgba(0, 0, 0, 0.45);
+  animation: toast-in 220ms ease-out;
+}
+
+.toast-kicker {
+  font-size: 10px;
+  letter-spacing: 0.08em;
+  text-transform: uppercase;
+  color: #ffd36b;
+}
+
+@keyframes toast-in {
+  from {
+    transform: translateY(12px);
+    opacity: 0;
+  }
+}
+
+.puzzle-button {
+  display: flex;
+  justify-content: space-between;
+  gap: 8px;
+}
+
+.puzzle-button.solved {
+  border-color: rgba(120, 220, 160, 0.45);
+}
+
+.puzzle-stars {
+  color: #ffd36b;
+  letter-spacing: 1px;
+}
+
+.puzzle-stars.locked {
+  opacity: 0.6;
+}
+
+.pack {
+  display: grid;
+  gap: 6px;
+  margin-top: 10px;
+  padding-top: 8px;
+  border-top: 1px solid rgba(130, 172, 248, 0.14);
+}
+
+.pack-upload {
+  margin-top: 10px;
+  text-align: center;
+}
+
+.pack-upload input {
+  display: none;
+}
+
+.mod-drop {
+  padding: 14px 10px;
+  border: 1px dashed rgba(130, 172, 248, 0.45);
+  border-radius: 10px;
+  text-align: center;
+  font-size: 12px;
+  color: var(--muted);
+}
+
+.mod-drop.hover {
+  border-color: #ffd76a;
+  color: #ffd76a;
+}
+
+.mod-error {
+  font-size: 12px;
+  color: #ff7a7a;
+}
+
+.card-upgrade {
+  display: flex;
+  gap: 6px;
+  font-size: 11px;
+}
+
+.upgrade-level {
+  color: #ffd76a;
+}
+
+.upgrade-edition {
+  padding: 0 6px;
+  border-radius: 999px;
+  border: 1px solid currentColor;
+}
+
+.card.edition-foil {
+  box-shadow: 0 0 0 1px rgba(255, 215, 106, 0.55), 0 0 14px rgba(255, 215, 106, 0.25);
+}
+
+.card.edition-foil .upgrade-edition {
+  color: #ffd76a;
+}
+
+.card.edition-glitched {
+  box-shadow: 0 0 0 1px rgba(255, 92, 200, 0.55), 0 0 14px rgba(92, 255, 236, 0.2);
+}
+
+.card.edition-glitched .upgrade-edition {
+  color: #ff5cc8;
+}
+
+.card.edition-eternal {
+  box-shadow: 0 0 0 1px rgba(160, 200, 255, 0.6), 0 0 14px rgba(160, 200, 255, 0.22);
+}
+
+.card.edition-eternal .upgrade-edition {
+  color: #a0c8ff;
+}
+
+.best-table {
+  width: 100%;
+  border-collapse: collapse;
+  font-size: 12px;
+}
+
+.best-table th,
+.best-table td {
+  padding: 3px 4px;
+  text-align: left;
+  border-bottom: 1px solid rgba(130, 172, 248, 0.14);
+}
+
+.best-table th {
+  color: var(--muted);
+  font-weight: 600;
+}
+
+.best-run-label {
+  max-width: 120px;
+  overflow: hidden;
+  text-overflow: ellipsis;
+  white-space: nowrap;
+}
+
+.difficulty-select {
+  border-radius: 10px;
+  border: 1px solid rgba(130, 172, 248, 0.3);
+  background: rgba(8, 12, 22, 0.72);
+  color: inherit;
+  padding: 6px 8px;
+}
+
+.shop-boss {
+  border-radius: 10px;
+  border: 1px solid rgba(255, 90, 90, 0.45);
+  background: rgba(48, 8, 12, 0.55);
+  padding: 8px 10px;
+}
+
+.strip-item.boss .strip-v {
+  color: rgba(255, 110, 110, 0.95);
+}
+
+.shop-relic {
+  max-width: 320px;
+}
+
+.relic-list {
+  margin-top: 6px;
+  display: flex;
+  flex-wrap: wrap;
+  gap: 6px;
+}
+
+.relic-chip {
+  display: inline-flex;
+  align-items: center;
+  gap: 6px;
+  border-radius: 999px;
+  border: 1px solid rgba(245, 196, 92, 0.35);
+  background: rgba(40, 28, 8, 0.55);
+  padding: 4px 10px;
+  font-size: 12px;
+  cursor: help;
+}
+
+.relic-icon {
+  font-size: 14px;
+}
+
+.run-over-modal {
+  width: min(520px, calc(100vw - 48px));
+}
+
+.run-over-body {
+  display: grid;
+  gap: 10px;
+  padding: 14px;
+}
+
 @media (max-width: 1180px) {
   .topbar.status-grid {
     grid-template-columns: 1fr;
@@ -3364,7 +3787,7 @@ body {
   }
 
   .run-strip {
-    grid-template-columns: 1fr;
+    grid-auto-flow: row;
   }
 }
 "#;
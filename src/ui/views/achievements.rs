@@ -0,0 +1,59 @@
+use dioxus::prelude::*;
+
+use kardinality::game::achievements::{self, AchievementDef, AchievementLog};
+
+/// Every achievement, unlocked ones first in unlock order.
+#[component]
+pub fn AchievementGallery(log: Signal<AchievementLog>) -> Element {
+    let log = log.read();
+    let unlocked = log
+        .unlocked
+        .iter()
+        .filter_map(|id| achievements::get(id))
+        .collect::<Vec<_>>();
+    let locked = achievements::catalog()
+        .iter()
+        .filter(|a| !log.is_unlocked(&a.id))
+        .collect::<Vec<_>>();
+    let total = achievements::catalog().len();
+
+    rsx! {
+        div { class: "panel sidebar-panel", "data-testid": "achievements",
+            h3 { "Achievements" }
+            div { class: "hint", "{unlocked.len()} / {total} unlocked" }
+            div { class: "achievement-grid",
+                for def in unlocked {
+                    div { class: "achievement unlocked", key: "{def.id}", title: "{def.description}",
+                        span { class: "achievement-icon", "{def.icon}" }
+                        span { class: "achievement-name", "{def.name}" }
+                    }
+                }
+                for def in locked {
+                    div { class: "achievement", key: "{def.id}", title: "{def.description}",
+                        span { class: "achievement-icon", "?" }
+                        span { class: "achievement-name", "{def.name}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stack of "achievement unlocked" toasts; each removes itself after a few seconds.
+#[component]
+pub fn AchievementToasts(toasts: Signal<Vec<(u64, &'static AchievementDef)>>) -> Element {
+    rsx! {
+        div { class: "toast-stack", "data-testid": "toasts",
+            for (key, def) in toasts() {
+                div { class: "toast", key: "{key}",
+                    span { class: "achievement-icon", "{def.icon}" }
+                    div {
+                        div { class: "toast-kicker", "Achievement unlocked" }
+                        strong { "{def.name}" }
+                        div { class: "hint", "{def.description}" }
+                    }
+                }
+            }
+        }
+    }
+}
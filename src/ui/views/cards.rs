@@ -28,8 +28,10 @@ pub fn CardView(
     let badge = format!("#{}", index + 1);
 
     let def_id = card.def_id.clone();
+    let level = card.upgrade.level;
+    let edition = card.upgrade.edition;
     let (name, script, budget, icon, kind_label, kind_icon, kind_class, fn_visuals) = card
-        .def()
+        .effective_def()
         .map(|d| {
             let kind_class = match d.kind {
                 kardinality::game::cards::CardKind::Economy => "kind-economy",
@@ -38,20 +40,21 @@ pub fn CardView(
                 kardinality::game::cards::CardKind::Meta => "kind-meta",
             };
             let kind_visual = kardinality::game::cards::kind_visual(d.kind);
+            let fn_visuals = kardinality::game::cards::script_function_visuals(&d.script);
             (
                 d.name,
                 d.script,
                 d.budget,
-                d.icon,
+                d.base.icon,
                 kind_visual.label.to_string(),
                 kind_visual.icon,
                 kind_class,
-                kardinality::game::cards::script_function_visuals(d.script),
+                fn_visuals,
             )
         })
         .unwrap_or((
-            "Missing Card",
-            "/* missing */",
+            "Missing Card".to_string(),
+            "/* missing */".to_string(),
             0,
             "?",
             "Missing".to_string(),
@@ -60,7 +63,7 @@ pub fn CardView(
             Vec::new(),
         ));
 
-    let script_spans: Vec<(String, &'static str)> = match kardinality::kardlang::lex(script) {
+    let script_spans: Vec<(String, &'static str)> = match kardinality::kardlang::lex(&script) {
         Ok(tokens) => tokens
             .into_iter()
             .filter(|t| !matches!(t.kind, kardinality::kardlang::TokenKind::Eof))
@@ -101,6 +104,14 @@ pub fn CardView(
                                 | "h"
                                 | "wild"
                                 | "w"
+                                | "skip"
+                                | "k"
+                                | "swap"
+                                | "z"
+                                | "rev"
+                                | "r"
+                                | "pull"
+                                | "p"
                         );
                         let is_reg = matches!(
                             name.as_str(),
@@ -120,6 +131,8 @@ pub fn CardView(
                                 | "score"
                                 | "deck"
                                 | "hand"
+                                | "len_queue"
+                                | "U"
                                 | "D"
                                 | "H"
                                 | "S"
@@ -167,6 +180,12 @@ pub fn CardView(
     if dragging {
         class.push_str(" dragging");
     }
+    if level > 0 {
+        class.push_str(" upgraded");
+    }
+    if let Some(edition) = edition {
+        class.push_str(&format!(" edition-{}", edition.label().to_lowercase()));
+    }
     let extra_fx = fn_visuals.len().saturating_sub(3);
 
     rsx! {
@@ -233,6 +252,16 @@ pub fn CardView(
             div { class: "card-body",
                 h3 { class: "card-title", "{name}" }
                 div { class: "card-sub", "{kind_label} • budget {budget}" }
+                if level > 0 || edition.is_some() {
+                    div { class: "card-upgrade",
+                        if level > 0 {
+                            span { class: "upgrade-level", title: "Upgrade level", "★{level}" }
+                        }
+                        if let Some(edition) = edition {
+                            span { class: "upgrade-edition", title: "{edition.doc()}", "{edition.label()}" }
+                        }
+                    }
+                }
                 div { class: "card-script",
                     for (text, cls) in script_spans {
                         span { class: "tok tok-{cls}", "{text}" }
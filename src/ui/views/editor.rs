@@ -0,0 +1,218 @@
+use dioxus::prelude::*;
+
+use kardinality::game::editor;
+use kardinality::game::puzzles::PuzzleData;
+
+use super::sidebar::ShareCodeRow;
+use crate::ui::anim;
+
+/// Designer tool: snapshot the live game into a draft puzzle, tune it, verify, export.
+#[component]
+pub fn PuzzleEditor(
+    mut engine: Signal<kardinality::Engine>,
+    mut draft: Signal<Option<PuzzleData>>,
+) -> Element {
+    let mut verdict = use_signal(|| None::<Result<String, String>>);
+    let mut verifying = use_signal(|| false);
+
+    let snapshot = move |_| {
+        let next = editor::snapshot(&engine.read().state);
+        draft.set(Some(next));
+        verdict.set(None);
+    };
+
+    let Some(current) = draft() else {
+        return rsx! {
+            div { class: "panel sidebar-panel", "data-testid": "puzzle-editor",
+                h3 { "Puzzle Editor" }
+                div { class: "hint",
+                    "Set up a position in a normal run, then snapshot it: source, Code Bank, hand, bankroll, score and level become a draft puzzle."
+                }
+                button { class: "btn", "data-testid": "editor-snapshot", onclick: snapshot, "Snapshot current game" }
+            }
+        };
+    };
+
+    // Field edits invalidate the last verification.
+    let mut edit = move |f: &dyn Fn(&mut PuzzleData)| {
+        if let Some(d) = draft.write().as_mut() {
+            f(d);
+        }
+        verdict.set(None);
+    };
+
+    let verify = move |_| {
+        let Some(mut d) = draft() else {
+            return;
+        };
+        verifying.set(true);
+        // Yield a frame first so the busy state renders before the search runs.
+        spawn(async move {
+            anim::sleep_ms(16).await;
+            let result =
+                editor::verify(&mut d)
+                    .map_err(|e| e.to_string())
+                    .and_then(|report| match d.solution.is_empty() {
+                        false => Ok(format!(
+                            "Solvable in {} play(s): {}",
+                            d.solution.len(),
+                            d.solution
+                                .iter()
+                                .map(|play| play.join(" → "))
+                                .collect::<Vec<_>>()
+                                .join(" | ")
+                        )),
+                        true if report.truncated => {
+                            Err("Search hit the solver limit without a solution.".to_string())
+                        }
+                        true => Err("No solution within the solver limits.".to_string()),
+                    });
+            draft.set(Some(d));
+            verdict.set(Some(result));
+            verifying.set(false);
+        });
+    };
+
+    let play = move |_| {
+        let Some(d) = draft() else {
+            return;
+        };
+        let mut eng = engine.write();
+        if let Err(e) = eng.dispatch(kardinality::Action::LoadPuzzle {
+            puzzle: Box::new(d),
+        }) {
+            eng.state
+                .trace
+                .push(kardinality::TraceEvent::Error(e.to_string()));
+        }
+    };
+
+    let verified = matches!(verdict(), Some(Ok(_)));
+    let export = editor::export_pack(&current, "").map(|pack| {
+        let json = serde_json::to_string_pretty(&pack).unwrap_or_default();
+        format!(
+            "data:application/json;charset=utf-8,{}",
+            percent_encode(&json)
+        )
+    });
+    let goal_text = current
+        .goal_bankroll
+        .map(|g| g.to_string())
+        .unwrap_or_default();
+    let limit_text = current
+        .play_limit
+        .map(|l| l.to_string())
+        .unwrap_or_default();
+
+    rsx! {
+        div { class: "panel sidebar-panel editor", "data-testid": "puzzle-editor",
+            h3 { "Puzzle Editor" }
+            div { class: "kv", span { "Source" } code { "{current.source_ids.len()} cards" } }
+            div { class: "kv", span { "Code Bank" } code { "{current.collection_ids.len()} cards" } }
+            div { class: "kv", span { "Hand" } code { class: "selectable", "{current.hand_ids.join(\", \")}" } }
+            div { class: "kv", span { "Start" }
+                code { "${current.start_bankroll} • score {current.start_score} • L{current.start_level}" }
+            }
+
+            label { class: "editor-field", span { "Name" }
+                input {
+                    value: "{current.name}",
+                    oninput: move |evt| edit(&|d| d.name = evt.value()),
+                }
+            }
+            label { class: "editor-field", span { "Target score" }
+                input {
+                    r#type: "number",
+                    value: "{current.target_score}",
+                    oninput: move |evt| {
+                        if let Ok(v) = evt.value().trim().parse::<i64>() {
+                            edit(&|d| d.target_score = v.max(1));
+                        }
+                    },
+                }
+            }
+            label { class: "editor-field", span { "Bankroll goal (blank = none)" }
+                input {
+                    r#type: "number",
+                    value: "{goal_text}",
+                    oninput: move |evt| {
+                        let v = evt.value().trim().parse::<i64>().ok();
+                        edit(&|d| d.goal_bankroll = v);
+                    },
+                }
+            }
+            label { class: "editor-field", span { "Play limit (blank = none)" }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{limit_text}",
+                    oninput: move |evt| {
+                        let v = evt.value().trim().parse::<u32>().ok().filter(|v| *v >= 1);
+                        edit(&|d| d.play_limit = v);
+                    },
+                }
+            }
+            label { class: "editor-field", span { "Hint" }
+                textarea {
+                    value: "{current.hint}",
+                    oninput: move |evt| edit(&|d| d.hint = evt.value()),
+                }
+            }
+            label { class: "editor-field", span { "Theme" }
+                select {
+                    value: "{current.theme}",
+                    onchange: move |evt| edit(&|d| d.theme = evt.value()),
+                    for key in ["terminal", "crt", "magic"] {
+                        option { value: key, selected: current.theme == key, "{key}" }
+                    }
+                }
+            }
+
+            div { class: "editor-actions",
+                button {
+                    class: "btn",
+                    "data-testid": "editor-verify",
+                    disabled: verifying(),
+                    onclick: verify,
+                    if verifying() { "Verifying…" } else { "Verify with solver" }
+                }
+                button { class: "btn secondary", onclick: play, "Play draft" }
+                button { class: "btn secondary", onclick: snapshot, "Re-snapshot" }
+            }
+            match verdict() {
+                Some(Ok(msg)) => rsx! { div { class: "hint selectable", "data-testid": "editor-verdict", "{msg}" } },
+                Some(Err(msg)) => rsx! { div { class: "mod-error", "data-testid": "editor-verdict", "{msg}" } },
+                None => rsx! { div { class: "hint", "Verify before exporting so the draft ships with a solution line." } },
+            }
+
+            if verified {
+                h3 { "Export" }
+                match export {
+                    Ok(download_href) => rsx! {
+                        ShareCodeRow { puzzle: current.clone() }
+                        a {
+                            class: "btn secondary",
+                            href: "{download_href}",
+                            download: "{current.id}.json",
+                            "Download pack file"
+                        }
+                    },
+                    Err(e) => rsx! { div { class: "mod-error", "data-testid": "editor-export-error", "{e}" } },
+                }
+            }
+        }
+    }
+}
+
+/// Percent-encode everything outside the URL-unreserved set, for `data:` links.
+pub(super) fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 3);
+    for b in text.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
@@ -50,13 +50,14 @@ pub fn RegistersBody(
     pile_count: usize,
     acc: i64,
     level: u32,
+    #[props(default)] source_hidden: bool,
 ) -> Element {
     rsx! {
         h2 { class: "hud-title", "Registers Grid" }
         div { class: "register-grid",
             div { class: "kv", span { "📚 deck (D)" } code { "{collection_count}" } }
             div { class: "kv", span { "🧠 hand (H)" } code { "{hand_count}" } }
-            div { class: "kv", span { "⚡ source (S)" } code { "{source_count}" } }
+            div { class: "kv", span { "⚡ source (S)" } code { if source_hidden { "??" } else { "{source_count}" } } }
             div { class: "kv", span { "🗂 pile (P)" } code { "{pile_count}" } }
             div { class: "kv", span { "🧮 acc (A)" } code { "{acc}" } }
             div { class: "kv", span { "🎯 level (L)" } code { "{level}" } }
@@ -64,6 +65,34 @@ pub fn RegistersBody(
     }
 }
 
+#[component]
+pub fn RelicsBody(relics: Vec<String>) -> Element {
+    rsx! {
+        h2 { class: "hud-title", "Relics ({relics.len()}/{kardinality::game::relics::MAX_RELICS})" }
+        if relics.is_empty() {
+            div { class: "hint", "None yet. The Lab sells one per visit." }
+        } else {
+            div { class: "relic-list", "data-testid": "relic-list",
+                for id in relics.iter() {
+                    {
+                        let def = kardinality::game::relics::get(id);
+                        let icon = def.map(|r| r.icon).unwrap_or("?");
+                        let name = def.map(|r| r.name).unwrap_or("Missing Relic");
+                        let doc = def.map(|r| r.doc).unwrap_or("");
+                        let hook = def.and_then(|r| r.hook).map(|h| h.label()).unwrap_or("passive");
+                        rsx! {
+                            div { key: "{id}", class: "relic-chip", title: "{doc} ({hook})",
+                                span { class: "relic-icon", "{icon}" }
+                                span { class: "relic-name", "{name}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 pub fn TopHud(
     bankroll: i64,
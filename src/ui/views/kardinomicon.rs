@@ -19,10 +19,10 @@ pub fn KardinomiconModal(
 
     // If a target is provided, show it first.
     let mut ordered: Vec<kardinality::game::cards::CardDef> = Vec::with_capacity(catalog.len());
-    if let Some(t) = target.as_deref() {
-        if let Some(found) = catalog.iter().find(|c| c.id == t) {
-            ordered.push(*found);
-        }
+    if let Some(t) = target.as_deref()
+        && let Some(found) = catalog.iter().find(|c| c.id == t)
+    {
+        ordered.push(*found);
     }
     for c in catalog {
         if ordered.first().is_some_and(|first| first.id == c.id) {
@@ -0,0 +1,112 @@
+use dioxus::prelude::*;
+
+use kardinality::game::lineage;
+
+/// Pick any card in play, or a copy that already expired, and see where it sits, how long it
+/// lives and what it was copied from.
+#[component]
+pub fn LineageInspector(engine: Signal<kardinality::Engine>) -> Element {
+    let mut selected = use_signal(|| None::<u64>);
+
+    let eng = engine.read();
+    let state = &eng.state;
+    let in_play = lineage::cards_in_play(state).map(|(zone, card)| {
+        let label = format!("#{} {} ({})", card.id, card.name(), zone.label());
+        (card.id, label)
+    });
+    let spent = lineage::spent_copies(state)
+        .into_iter()
+        .map(|(entry, name)| {
+            (
+                entry.card_id,
+                format!("#{} {name} (expired)", entry.card_id),
+            )
+        });
+    let options = in_play.chain(spent).collect::<Vec<_>>();
+    let chosen = selected().filter(|id| options.iter().any(|(o, _)| o == id));
+    let details = chosen.map(|id| {
+        let chain = lineage::lineage(state, id);
+        let copies = chain[1..]
+            .iter()
+            .map(|link| match (link.card, link.ran) {
+                (Some((zone, card)), _) => {
+                    format!("#{} {} ({})", link.card_id, card.name(), zone.label())
+                }
+                (None, Some(entry)) => format!(
+                    "#{} {} (expired)",
+                    link.card_id,
+                    lineage::run_name(state, entry)
+                ),
+                (None, None) => format!("#{} (gone)", link.card_id),
+            })
+            .collect::<Vec<_>>();
+        match (chain[0].card, chain[0].ran) {
+            (Some((zone, card)), _) => {
+                let lifetime = if card.is_ephemeral() {
+                    "Ephemeral (this hand)"
+                } else {
+                    "Permanent"
+                };
+                let mutations = (!card.provenance.mutated_from.is_empty()).then(|| {
+                    let mut defs = card.provenance.mutated_from.clone();
+                    defs.push(card.def_id.clone());
+                    defs.join(" → ")
+                });
+                (
+                    card.id,
+                    card.name(),
+                    zone.label(),
+                    lifetime,
+                    Some(card.upgrade.level),
+                    copies,
+                    mutations,
+                )
+            }
+            (None, ran) => (
+                id,
+                ran.map_or_else(String::new, |entry| lineage::run_name(state, entry)),
+                "Out of play",
+                "Ephemeral (expired)",
+                None,
+                copies,
+                None,
+            ),
+        }
+    });
+    drop(eng);
+
+    rsx! {
+        div { class: "panel sidebar-panel", "data-testid": "lineage",
+            h3 { "Card Lineage" }
+            select {
+                "data-testid": "lineage-card",
+                value: chosen.map(|id| id.to_string()).unwrap_or_default(),
+                onchange: move |evt| selected.set(evt.value().parse().ok()),
+                option { value: "", "Pick a card…" }
+                for (id, label) in options {
+                    option { key: "{id}", value: "{id}", selected: Some(id) == chosen, "{label}" }
+                }
+            }
+            if let Some((id, name, zone, lifetime, level, copies, mutations)) = details {
+                div { class: "kv", span { "Card" } code { "#{id} {name}" } }
+                div { class: "kv", span { "Zone" } code { "{zone}" } }
+                div { class: "kv", span { "Lifetime" } code { "{lifetime}" } }
+                if let Some(level) = level {
+                    div { class: "kv", span { "Upgrade" } code { "+{level}" } }
+                }
+                if copies.is_empty() {
+                    div { class: "kv", span { "Origin" } code { "original" } }
+                }
+                for (depth, copy) in copies.into_iter().enumerate() {
+                    div { class: "kv", key: "{depth}",
+                        span { if depth == 0 { "Copy of" } else { "…of" } }
+                        code { "{copy}" }
+                    }
+                }
+                if let Some(mutations) = mutations {
+                    div { class: "kv", span { "Mutated" } code { "{mutations}" } }
+                }
+            }
+        }
+    }
+}
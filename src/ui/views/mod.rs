@@ -1,14 +1,25 @@
+mod achievements;
 mod cards;
 mod deck;
+mod editor;
 mod hud;
 mod kardinomicon;
+mod lineage;
 mod pile;
+mod run_over;
+mod shop;
 mod sidebar;
+mod timeline;
 
+pub use achievements::{AchievementGallery, AchievementToasts};
 pub use cards::CardView;
 pub use cards::PtrDown;
 pub use deck::DeckWidget;
-pub use hud::RegistersBody;
+pub use hud::{RegistersBody, RelicsBody};
 pub use kardinomicon::KardinomiconModal;
+pub use lineage::LineageInspector;
 pub use pile::PileWidget;
+pub use run_over::RunOverModal;
+pub use shop::ShopModal;
 pub use sidebar::{Sidebar, SidebarTab};
+pub use timeline::TraceTimeline;
@@ -0,0 +1,57 @@
+use dioxus::prelude::*;
+
+use kardinality::game::{Phase, RunMode};
+
+use crate::ui::anim;
+
+#[component]
+pub fn RunOverModal(mut engine: Signal<kardinality::Engine>) -> Element {
+    let engine_read = engine.read();
+    let state = &engine_read.state;
+    if state.phase != Phase::GameOver || state.mode != RunMode::Classic {
+        return rsx! {};
+    }
+
+    let level = state.level;
+    let score = state.score;
+    let target = state.target_score;
+    let turns = state.turn;
+    let preset = state.config.preset.label();
+    drop(engine_read);
+
+    rsx! {
+        div { class: "modal-overlay",
+            div {
+                class: "modal panel run-over-modal",
+                "data-testid": "run-over",
+                onclick: move |evt| evt.stop_propagation(),
+
+                div { class: "modal-header",
+                    div { class: "modal-title",
+                        span { class: "modal-glyph", "✕" }
+                        span { "Run over" }
+                    }
+                }
+
+                div { class: "run-over-body",
+                    div { class: "hint", "Out of hands before reaching the target." }
+                    div { class: "kv", span { "Difficulty" } code { "{preset}" } }
+                    div { class: "kv", span { "Level" } code { "{level}" } }
+                    div { class: "kv", span { "Score" } code { "{score}/{target}" } }
+                    div { class: "kv", span { "Hands played" } code { "{turns}" } }
+                    button {
+                        class: "btn danger",
+                        "data-testid": "run-over-restart",
+                        onclick: move |_| {
+                            let mut eng = engine.write();
+                            if let Err(e) = eng.dispatch(kardinality::Action::NewRun { seed: anim::fresh_seed(), config: None }) {
+                                eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                            }
+                        },
+                        "New run"
+                    }
+                }
+            }
+        }
+    }
+}
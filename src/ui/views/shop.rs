@@ -0,0 +1,194 @@
+use dioxus::prelude::*;
+
+use kardinality::Action;
+use kardinality::game::Phase;
+
+#[component]
+pub fn ShopModal(mut engine: Signal<kardinality::Engine>) -> Element {
+    let engine_read = engine.read();
+    let state = &engine_read.state;
+    if state.phase != Phase::Shop {
+        return rsx! {};
+    }
+    let Some(shop) = state.shop.clone() else {
+        return rsx! {};
+    };
+
+    let bankroll = state.bankroll;
+    let level = state.level;
+    let target = state.target_score;
+    let boss = state.boss;
+    let collection = state.collection.clone();
+    let legal = engine_read.legal_actions();
+    drop(engine_read);
+    let allowed = move |action: Action| legal.contains(&action);
+
+    let mut run = move |action: kardinality::Action| {
+        let mut eng = engine.write();
+        if let Err(e) = eng.dispatch(action) {
+            eng.state
+                .trace
+                .push(kardinality::TraceEvent::Error(e.to_string()));
+        }
+    };
+
+    rsx! {
+        div { class: "modal-overlay",
+            div {
+                class: "modal panel shop-modal",
+                "data-testid": "shop-modal",
+                onclick: move |evt| evt.stop_propagation(),
+
+                div { class: "modal-header",
+                    div { class: "modal-title",
+                        span { class: "modal-glyph", "◆" }
+                        span { "Lab // Level {level} prep" }
+                    }
+                    div { class: "shop-bank",
+                        span { class: "hint", "Bank" }
+                        strong { "data-testid": "shop-bank", "${bankroll}" }
+                    }
+                }
+
+                div { class: "modal-body shop-body",
+                    if let Some(boss) = boss {
+                        div { class: "shop-boss", "data-testid": "shop-boss",
+                            strong { "Boss ahead: {boss.name()}" }
+                            span { class: "hint", " {boss.doc()}" }
+                        }
+                    }
+                    h2 { class: "hud-title", "Offers" }
+                    div { class: "shop-offers",
+                        for (idx, offer) in shop.offers.iter().enumerate() {
+                            {
+                                let def = kardinality::game::cards::get(&offer.def_id);
+                                let name = def.map(|d| d.name).unwrap_or("Missing Card");
+                                let script = def.map(|d| d.script).unwrap_or("");
+                                let kind = def.map(|d| kardinality::game::cards::kind_visual(d.kind));
+                                let price = offer.price;
+                                let sold = offer.sold;
+                                rsx! {
+                                    div { class: if sold { "shop-offer sold" } else { "shop-offer" },
+                                        "data-testid": "shop-offer-{idx}",
+                                        div { class: "shop-offer-head",
+                                            span { class: "shop-offer-name", "{name}" }
+                                            if let Some(kind) = kind {
+                                                span { class: "shop-offer-kind", "{kind.icon} {kind.label}" }
+                                            }
+                                        }
+                                        code { class: "shop-offer-script", "{script}" }
+                                        button {
+                                            class: "btn",
+                                            disabled: !allowed(Action::BuyCard { index: idx }),
+                                            onclick: move |_| run(kardinality::Action::BuyCard { index: idx }),
+                                            if sold { "Sold" } else { "Buy ${price}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div { class: "shop-actions",
+                        button {
+                            class: "btn secondary",
+                            "data-testid": "shop-reroll",
+                            disabled: !allowed(Action::RerollShop),
+                            onclick: move |_| run(kardinality::Action::RerollShop),
+                            "Reroll ${shop.reroll_cost}"
+                        }
+                        button {
+                            class: "btn secondary",
+                            "data-testid": "shop-pack",
+                            disabled: !allowed(Action::BuyPack),
+                            onclick: move |_| run(kardinality::Action::BuyPack),
+                            if shop.pack_bought {
+                                "Pack opened"
+                            } else {
+                                "Source pack (+{shop.pack_size}) ${shop.pack_cost}"
+                            }
+                        }
+                    }
+
+                    if let Some(relic) = shop.relic.clone() {
+                        {
+                            let def = kardinality::game::relics::get(&relic.relic_id);
+                            let icon = def.map(|r| r.icon).unwrap_or("?");
+                            let name = def.map(|r| r.name).unwrap_or("Missing Relic");
+                            let doc = def.map(|r| r.doc).unwrap_or("");
+                            rsx! {
+                                h2 { class: "hud-title", "Relic" }
+                                div { class: if relic.sold { "shop-offer shop-relic sold" } else { "shop-offer shop-relic" },
+                                    "data-testid": "shop-relic",
+                                    div { class: "shop-offer-head",
+                                        span { class: "shop-offer-name", "{icon} {name}" }
+                                    }
+                                    div { class: "hint", "{doc}" }
+                                    button {
+                                        class: "btn",
+                                        disabled: !allowed(Action::BuyRelic),
+                                        onclick: move |_| run(kardinality::Action::BuyRelic),
+                                        if relic.sold { "Owned" } else { "Buy ${relic.price}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 { class: "hud-title", "Upgrade a card" }
+                    if collection.is_empty() {
+                        div { class: "empty", "Your Code Bank is empty." }
+                    } else {
+                        div { class: "shop-removals",
+                            for (idx, card) in collection.iter().enumerate() {
+                                {
+                                    let name = card
+                                        .effective_def()
+                                        .map_or_else(|| "Missing Card".to_string(), |d| d.name);
+                                    let maxed = card.upgrade.level >= kardinality::game::upgrades::MAX_UPGRADE_LEVEL;
+                                    let price = kardinality::game::upgrades::upgrade_price(card.upgrade.level);
+                                    rsx! {
+                                        button {
+                                            key: "upgrade-{card.id}",
+                                            class: "btn",
+                                            disabled: !allowed(Action::UpgradeCard { index: idx }),
+                                            onclick: move |_| run(kardinality::Action::UpgradeCard { index: idx }),
+                                            if maxed { "★ {name} (max)" } else { "★ {name} ${price}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 { class: "hud-title", "Remove a card (${shop.removal_cost})" }
+                    if collection.is_empty() {
+                        div { class: "empty", "Your Code Bank is empty." }
+                    } else {
+                        div { class: "shop-removals",
+                            for (idx, card) in collection.iter().enumerate() {
+                                button {
+                                    key: "remove-{card.id}",
+                                    class: "btn danger",
+                                    disabled: !allowed(Action::RemoveCard { index: idx }),
+                                    onclick: move |_| run(kardinality::Action::RemoveCard { index: idx }),
+                                    "✕ {card.effective_def().map_or_else(|| \"Missing Card\".to_string(), |d| d.name)}"
+                                }
+                            }
+                        }
+                    }
+
+                    button {
+                        class: "play-btn play-btn2 shop-continue",
+                        "data-testid": "shop-continue",
+                        onclick: move |_| run(kardinality::Action::LeaveShop),
+                        div { class: "play-head",
+                            span { class: "play-icon", "▶" }
+                            span { class: "play-text", "Start level {level} (target {target})" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,24 @@
 use dioxus::prelude::*;
 
-use crate::ui::state::{UiSettings, UiTheme};
+use kardinality::game::achievements::AchievementLog;
+use kardinality::game::daily::{self, RunHistory};
+use kardinality::game::goals;
+use kardinality::game::packs::{PackLibrary, PuzzlePack};
+use kardinality::game::progress::{self, PuzzleProgress, PuzzleResult};
+use kardinality::game::puzzles::PuzzleData;
+use kardinality::game::share;
+use kardinality::game::trace::{SegmentKind, SegmentSummary, TracePolicy};
+
+use crate::ui::anim;
+use crate::ui::state::{PACKS_KEY, UiSettings, UiTheme};
+use crate::ui::views::editor::{PuzzleEditor, percent_encode};
+use crate::ui::views::{AchievementGallery, LineageInspector, TraceTimeline};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SidebarTab {
     Controls,
     Debug,
+    Editor,
     Settings,
 }
 
@@ -16,12 +29,54 @@ pub fn Sidebar(
     mut settings: Signal<UiSettings>,
     mut kardinomicon_open: Signal<bool>,
     mut kardinomicon_target: Signal<Option<String>>,
+    history: Signal<RunHistory>,
+    mut packs: Signal<PackLibrary>,
+    progress: Signal<PuzzleProgress>,
+    achievements: Signal<AchievementLog>,
     focused: bool,
     focus_index: usize,
 ) -> Element {
     let tab_value = tab();
     let cur_settings = settings();
     let puzzles = kardinality::game::puzzles::catalog();
+    let presets = kardinality::game::config::Preset::all();
+    let today = daily::date_from_days(anim::today_days());
+    let history_read = history.read();
+    let best = history_read
+        .best(5)
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    let today_best = history_read.best_daily(&today).cloned();
+    drop(history_read);
+    let loaded_packs = packs.read().packs.clone();
+    let progress = progress.read().clone();
+    let lesson_order = puzzles.iter().map(|p| p.id).collect::<Vec<_>>();
+    let mut pack_status = use_signal(|| None::<Result<String, String>>);
+    let draft = use_signal(|| None::<PuzzleData>);
+
+    let on_pack_upload = move |evt: FormEvent| {
+        let Some(file) = evt.files().into_iter().next() else {
+            return;
+        };
+        spawn(async move {
+            let name = file.name();
+            let result = match file.read_string().await {
+                Ok(text) => PuzzlePack::from_file(&name, &text)
+                    .map(|pack| {
+                        let msg =
+                            format!("Loaded {} ({} puzzles).", pack.title, pack.puzzles.len());
+                        let mut library = packs.write();
+                        library.add(pack);
+                        anim::storage_set(PACKS_KEY, &library.to_json());
+                        msg
+                    })
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(format!("could not read {name}: {e}")),
+            };
+            pack_status.set(Some(result));
+        });
+    };
 
     // Sidebar focus highlight handled inline for each button (controls are dynamic).
 
@@ -48,6 +103,12 @@ pub fn Sidebar(
                     onclick: move |_| tab.set(SidebarTab::Debug),
                     "Debug"
                 }
+                button {
+                    class: if tab_value == SidebarTab::Editor { "tab active" } else { "tab" },
+                    "data-testid": "tab-editor",
+                    onclick: move |_| tab.set(SidebarTab::Editor),
+                    "Editor"
+                }
                 button {
                     class: if tab_value == SidebarTab::Settings { "tab active" } else { "tab" },
                     onclick: move |_| tab.set(SidebarTab::Settings),
@@ -64,12 +125,66 @@ pub fn Sidebar(
                         title: "Warning: resets your run state",
                         onclick: move |_| {
                             let mut eng = engine.write();
-                            if let Err(e) = eng.dispatch(kardinality::Action::NewRun { seed: 0 }) {
+                            if let Err(e) = eng.dispatch(kardinality::Action::NewRun { seed: anim::fresh_seed(), config: None }) {
                                 eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
                             }
                         },
                         "Reset game"
                     }
+                    button {
+                        "data-testid": "daily-run",
+                        class: "btn secondary",
+                        title: "Same seed for everyone today",
+                        onclick: {
+                            let date = today.clone();
+                            move |_| {
+                                let mut eng = engine.write();
+                                if let Err(e) = eng.dispatch(kardinality::Action::NewDailyRun { date: date.clone() }) {
+                                    eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                                }
+                            }
+                        },
+                        "Daily challenge · {today}"
+                    }
+                    if state.mode == kardinality::game::RunMode::Classic {
+                        div { class: "kv", span { if state.daily.is_some() { "Daily seed" } else { "Seed" } }
+                            code { class: "selectable", "data-testid": "run-seed", "{state.seed}" }
+                        }
+                        div { class: "kv", span { "Share link" }
+                            code { class: "selectable", "?seed={state.seed}" }
+                        }
+                    }
+                    label { class: "kv", r#for: "difficulty",
+                        span { "Difficulty (starts a new run)" }
+                    }
+                    select {
+                        id: "difficulty",
+                        class: "difficulty-select",
+                        "data-testid": "difficulty",
+                        value: "{state.config.preset.label()}",
+                        onchange: move |evt| {
+                            let label = evt.value();
+                            let Some(preset) = kardinality::game::config::Preset::all()
+                                .into_iter()
+                                .find(|p| p.label() == label)
+                            else {
+                                return;
+                            };
+                            let config = kardinality::game::config::RunConfig::from_preset(preset);
+                            let mut eng = engine.write();
+                            let action = kardinality::Action::NewRun { seed: anim::fresh_seed(), config: Some(Box::new(config)) };
+                            if let Err(e) = eng.dispatch(action) {
+                                eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                            }
+                        },
+                        for preset in presets.iter() {
+                            option {
+                                value: "{preset.label()}",
+                                selected: *preset == state.config.preset,
+                                "{preset.label()}"
+                            }
+                        }
+                    }
                     button {
                         "data-testid": "open-docs",
                         class: if focused && focus_index == 1 { "btn secondary focused" } else { "btn secondary" },
@@ -81,6 +196,41 @@ pub fn Sidebar(
                     }
                 }
 
+                div { class: "panel sidebar-panel", "data-testid": "best-runs",
+                    h3 { "Personal Best" }
+                    if let Some(record) = today_best {
+                        div { class: "kv", span { "Today's daily" } code { "L{record.level} · {record.score} pts · {record.turns} turns" } }
+                    }
+                    if best.is_empty() {
+                        div { class: "hint", "No finished runs yet. Results are saved when a run ends." }
+                    } else {
+                        table { class: "best-table",
+                            thead {
+                                tr { th { "#" } th { "Lvl" } th { "Score" } th { "Turns" } th { "Run" } }
+                            }
+                            tbody {
+                                for (rank, record) in best.iter().enumerate() {
+                                    tr {
+                                        td { "{rank + 1}" }
+                                        td { "{record.level}" }
+                                        td { "{record.score}" }
+                                        td { "{record.turns}" }
+                                        td { class: "best-run-label",
+                                            if let Some(date) = record.daily.as_deref() {
+                                                "Daily {date}"
+                                            } else {
+                                                "{record.preset} · {record.seed}"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                AchievementGallery { log: achievements }
+
                 div { class: "panel sidebar-panel",
                     h3 { "Puzzles / Tutorials" }
                     div { class: "hint",
@@ -99,6 +249,14 @@ pub fn Sidebar(
                             }
                         } }
                         div { class: "kv", span { "Goal" } code { "{puzzle_goal_text(state)}" } }
+                        if let Some(par) = state.puzzle_source.as_deref().map(|p| p.par).filter(|p| !p.is_empty()) {
+                            div { class: "kv", span { "Par" } code { "{par_text(&par)}" } }
+                        }
+                        if let Some(result) = state.puzzle_result {
+                            div { class: "kv", span { "Rating" }
+                                code { class: "puzzle-stars", "{progress::star_text(result.stars)}" }
+                            }
+                        }
                         if let Some(limit) = state.puzzle_play_limit {
                             div { class: "kv", span { "Plays Left" } code { "{limit.saturating_sub(state.turn)}" } }
                         }
@@ -111,48 +269,97 @@ pub fn Sidebar(
                         if let Some(msg) = state.puzzle_message.as_deref() {
                             div { class: "puzzle-message-banner selectable", "{msg}" }
                         }
-                        if let Some(id) = state.puzzle_id.as_deref() {
+                        if let Some(puzzle) = state.puzzle_source.clone() {
                             button {
                                 class: "btn secondary",
                                 onclick: {
-                                    let retry_id = id.to_string();
+                                    let puzzle = puzzle.clone();
                                     move |_| {
                                         let mut eng = engine.write();
-                                        if let Err(e) = eng.dispatch(kardinality::Action::StartPuzzle { id: retry_id.clone() }) {
+                                        let action = kardinality::Action::LoadPuzzle { puzzle: puzzle.clone() };
+                                        if let Err(e) = eng.dispatch(action) {
                                             eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
                                         }
                                     }
                                 },
                                 "Retry puzzle"
                             }
+                            ShareCodeRow { puzzle: (*puzzle).clone() }
                         }
                     } else {
                         div { class: "hint", "No puzzle active. Pick one below." }
                     }
 
+                    div { class: "hint",
+                        "Stars earned: {progress.total_stars()} / {puzzles.len() * progress::MAX_STARS as usize}"
+                    }
                     div { class: "trace-list",
                         for p in puzzles {
-                            button {
-                                class: "btn secondary",
-                                "data-testid": "puzzle-{p.id}",
-                                onclick: move |_| {
-                                    {
-                                        let mut s = settings.write();
-                                        if let Some(theme) = theme_from_puzzle_key(p.theme) {
-                                            s.theme = theme;
-                                        }
+                            PuzzleButton {
+                                engine,
+                                settings,
+                                puzzle: PuzzleData::from(p),
+                                best: progress.get(p.id).copied(),
+                                locked: !progress.is_unlocked(lesson_order.iter().copied(), p.id),
+                            }
+                        }
+                    }
+
+                    for pack in loaded_packs {
+                        div { class: "pack", key: "pack-{pack.id}", "data-testid": "pack-{pack.id}",
+                            div { class: "pack-head",
+                                strong { "{pack.title}" }
+                                if !pack.author.is_empty() {
+                                    span { class: "hint", " by {pack.author}" }
+                                }
+                            }
+                            if !pack.description.is_empty() {
+                                div { class: "hint", "{pack.description}" }
+                            }
+                            div { class: "trace-list",
+                                for p in pack.puzzles.clone() {
+                                    PuzzleButton {
+                                        best: progress.get(&p.id).copied(),
+                                        locked: !progress.is_unlocked(pack.puzzles.iter().map(|q| q.id.as_str()), &p.id),
+                                        engine,
+                                        settings,
+                                        puzzle: p,
                                     }
-                                    let mut eng = engine.write();
-                                    if let Err(e) = eng.dispatch(kardinality::Action::StartPuzzle { id: p.id.to_string() }) {
-                                        eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                                }
+                            }
+                            button {
+                                class: "btn danger",
+                                onclick: {
+                                    let id = pack.id.clone();
+                                    move |_| {
+                                        let mut library = packs.write();
+                                        library.remove(&id);
+                                        anim::storage_set(PACKS_KEY, &library.to_json());
                                     }
                                 },
-                                "{p.name}"
+                                "Remove pack"
                             }
                         }
                     }
+
+                    label { class: "btn secondary pack-upload",
+                        "Load puzzle pack…"
+                        input {
+                            r#type: "file",
+                            accept: ".json,.ron,.toml",
+                            "data-testid": "pack-upload",
+                            onchange: on_pack_upload,
+                        }
+                    }
+                    match pack_status() {
+                        Some(Ok(msg)) => rsx! { div { class: "hint", "{msg}" } },
+                        Some(Err(msg)) => rsx! { div { class: "mod-error", "{msg}" } },
+                        None => rsx! {},
+                    }
                 }
             } else if tab_value == SidebarTab::Debug {
+                TraceTimeline { engine }
+
                 div { class: "panel sidebar-panel",
                     h3 { "Trace (latest first)" }
             button {
@@ -163,18 +370,24 @@ pub fn Sidebar(
                     let _ = eng.dispatch(kardinality::Action::ClearTrace);
                 },
                 "Clear trace"
+            }
+            if !state.trace.is_empty() {
+                a {
+                    class: "btn secondary",
+                    "data-testid": "export-trace",
+                    href: "data:application/x-ndjson;charset=utf-8,{percent_encode(&state.trace.to_json_lines())}",
+                    download: "kardinality-trace-{state.seed}.jsonl",
+                    "Export trace (.jsonl)"
+                }
             }
                     if state.trace.is_empty() {
                         div { class: "empty", "No trace yet. Assemble a hand and execute." }
-                    } else {
-                        div { class: "trace-list selectable",
-                            for evt in state.trace.iter().rev().take(120) {
-                                TraceItem { evt: evt.clone() }
-                            }
-                        }
                     }
+                    TraceSegments { engine }
                 }
 
+        LineageInspector { engine }
+
         div { class: "panel sidebar-panel",
             h3 { "Telemetry" }
             div { class: "kv", span { "Source" } code { "{state.deck.len()}" } }
@@ -184,6 +397,8 @@ pub fn Sidebar(
             div { class: "kv", span { "Trace" } code { "{state.trace.len()}" } }
             div { class: "kv", span { "Phase" } code { "{state.phase:?}" } }
         }
+            } else if tab_value == SidebarTab::Editor {
+                PuzzleEditor { engine, draft }
             } else {
                 div { class: "panel sidebar-panel",
                     h3 { "Theme" }
@@ -214,19 +429,255 @@ pub fn Sidebar(
                         "Hotkeys: arrows select • ↑/↓ move • Shift+←/→ reorder • Enter executes."
                     }
                 }
+                ModDropZone {}
+            }
+        }
+    }
+}
+
+#[component]
+pub(super) fn ShareCodeRow(puzzle: PuzzleData) -> Element {
+    let mut copied = use_signal(|| false);
+    let code = match share::encode(&puzzle) {
+        Ok(code) => code,
+        Err(e) => return rsx! { div { class: "mod-error", "{e}" } },
+    };
+    let link = format!("?puzzle={code}");
+    rsx! {
+        div { class: "kv", span { "Share code" }
+            code { class: "selectable share-code", "data-testid": "puzzle-share-code", "{link}" }
+        }
+        button {
+            class: "btn secondary",
+            "data-testid": "copy-share-code",
+            onclick: {
+                let code = code.clone();
+                move |_| {
+                    anim::copy_to_clipboard(&code);
+                    copied.set(true);
+                }
+            },
+            if copied() { "Copied!" } else { "Copy share code" }
+        }
+    }
+}
+
+/// Start button for a puzzle, showing its best rating; locked until the previous one is solved.
+#[component]
+fn PuzzleButton(
+    mut engine: Signal<kardinality::Engine>,
+    mut settings: Signal<UiSettings>,
+    puzzle: PuzzleData,
+    best: Option<PuzzleResult>,
+    locked: bool,
+) -> Element {
+    let name = puzzle.name.clone();
+    rsx! {
+        button {
+            class: if best.is_some() { "btn secondary puzzle-button solved" } else { "btn secondary puzzle-button" },
+            "data-testid": "puzzle-{puzzle.id}",
+            disabled: locked,
+            title: if locked { "Solve the previous puzzle to unlock" } else { "" },
+            onclick: move |_| {
+                if let Some(theme) = theme_from_puzzle_key(&puzzle.theme) {
+                    settings.write().theme = theme;
+                }
+                let mut eng = engine.write();
+                let action = kardinality::Action::LoadPuzzle {
+                    puzzle: Box::new(puzzle.clone()),
+                };
+                if let Err(e) = eng.dispatch(action) {
+                    eng.state.trace.push(kardinality::TraceEvent::Error(e.to_string()));
+                }
+            },
+            span { "{name}" }
+            if let Some(best) = best {
+                span { class: "puzzle-stars", "{progress::star_text(best.stars)}" }
+            } else if locked {
+                span { class: "puzzle-stars locked", "🔒" }
+            }
+        }
+    }
+}
+
+/// Drop a `.json`/`.ron`/`.toml` card mod to merge it over the built-in catalog.
+#[component]
+fn ModDropZone() -> Element {
+    let mut status = use_signal(|| None::<Result<String, String>>);
+    let mut hovering = use_signal(|| false);
+
+    let ondrop = move |evt: DragEvent| {
+        evt.prevent_default();
+        hovering.set(false);
+        let Some(file) = evt.data().data_transfer().files().into_iter().next() else {
+            return;
+        };
+        spawn(async move {
+            let name = file.name();
+            let result = match file.read_string().await {
+                Ok(text) => kardinality::game::mods::ModFile::from_file(&name, &text)
+                    .and_then(|m| m.merge())
+                    .map(|catalog| {
+                        let msg = format!(
+                            "Loaded {}: {} new, {} replaced.",
+                            catalog.name, catalog.added, catalog.replaced
+                        );
+                        catalog.install();
+                        msg
+                    })
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(format!("could not read {name}: {e}")),
+            };
+            status.set(Some(result));
+        });
+    };
+
+    rsx! {
+        div { class: "panel sidebar-panel",
+            h3 { "Card Mods" }
+            div {
+                class: if hovering() { "mod-drop hover" } else { "mod-drop" },
+                "data-testid": "mod-drop",
+                ondragover: move |evt| {
+                    evt.prevent_default();
+                    hovering.set(true);
+                },
+                ondragleave: move |_| hovering.set(false),
+                ondrop,
+                "Drop a .json, .ron or .toml card mod here"
+            }
+            match status() {
+                Some(Ok(msg)) => rsx! { div { class: "hint", "{msg} New sources use it." } },
+                Some(Err(msg)) => rsx! { div { class: "mod-error", "{msg}" } },
+                None => rsx! {},
+            }
+            button {
+                class: "btn secondary",
+                onclick: move |_| {
+                    kardinality::game::mods::reset();
+                    status.set(None);
+                },
+                "Use built-in cards"
+            }
+        }
+    }
+}
+
+/// Retention choices offered in the Debug tab.
+const TRACE_POLICIES: [(&str, &str, TracePolicy); 5] = [
+    ("hands-10", "Last 10 hands", TracePolicy::LastHands(10)),
+    ("hands-25", "Last 25 hands", TracePolicy::LastHands(25)),
+    ("hands-100", "Last 100 hands", TracePolicy::LastHands(100)),
+    ("ring-500", "Last 500 events", TracePolicy::RingBuffer(500)),
+    ("all", "Everything", TracePolicy::KeepAll),
+];
+
+/// Trace grouped into segments, newest first; the newest is open unless collapsed.
+#[component]
+fn TraceSegments(mut engine: Signal<kardinality::Engine>) -> Element {
+    // Segments (by first seq) whose default open/closed state was flipped.
+    let mut toggled = use_signal(std::collections::HashSet::<u64>::new);
+
+    let eng = engine.read();
+    let trace = &eng.state.trace;
+    let newest = trace.segments().back().map(|s| s.first_seq);
+    let segments = trace.segments().iter().rev().cloned().collect::<Vec<_>>();
+    let summaries = trace
+        .summaries()
+        .iter()
+        .rev()
+        .take(20)
+        .copied()
+        .collect::<Vec<_>>();
+    let pruned = trace.pruned_events();
+    let policy_key = TRACE_POLICIES
+        .iter()
+        .find(|(_, _, p)| *p == trace.policy())
+        .map_or("custom", |(key, _, _)| key);
+    drop(eng);
+
+    rsx! {
+        label { class: "editor-field",
+            span { "Keep" }
+            select {
+                "data-testid": "trace-policy",
+                value: "{policy_key}",
+                onchange: move |evt| {
+                    if let Some((_, _, policy)) = TRACE_POLICIES.iter().find(|(key, _, _)| *key == evt.value()) {
+                        engine.write().state.trace.set_policy(*policy);
+                    }
+                },
+                for (key, label, _) in TRACE_POLICIES {
+                    option { value: key, selected: key == policy_key, "{label}" }
+                }
             }
         }
+        for segment in segments {
+            {
+                let seq = segment.first_seq;
+                let open = (Some(seq) == newest) != toggled.read().contains(&seq);
+                let label = segment_label(&segment.summary());
+                rsx! {
+                    div { class: "trace-segment", key: "{seq}",
+                        button {
+                            class: if open { "trace-segment-head open" } else { "trace-segment-head" },
+                            onclick: move |_| {
+                                let mut t = toggled.write();
+                                if !t.remove(&seq) {
+                                    t.insert(seq);
+                                }
+                            },
+                            span { class: "trace-caret", if open { "▾" } else { "▸" } }
+                            "{label}"
+                        }
+                        if open {
+                            div { class: "trace-list selectable",
+                                for evt in segment.events.iter().rev() {
+                                    TraceItem { evt: evt.clone() }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if pruned > 0 {
+            div { class: "hint", "{pruned} older events pruned." }
+            div { class: "trace-list",
+                for summary in summaries {
+                    div { class: "trace-item pruned", "{segment_label(&summary)}" }
+                }
+            }
+        }
+    }
+}
+
+fn segment_label(summary: &SegmentSummary) -> String {
+    let mut label = match summary.kind {
+        SegmentKind::Hand => format!(
+            "Hand {} · L{} · {} cards · score {:+} · bank {:+}",
+            summary.turn, summary.level, summary.cards, summary.score, summary.bankroll
+        ),
+        SegmentKind::Between => format!(
+            "Between hands · L{} · {} events",
+            summary.level, summary.events
+        ),
+    };
+    if summary.errors > 0 {
+        label.push_str(&format!(" · {} errors", summary.errors));
     }
+    label
 }
 
 #[component]
 fn TraceItem(evt: kardinality::TraceEvent) -> Element {
-    let (class, text) = match &evt {
-        kardinality::TraceEvent::Error(_) => ("trace-item error", format!("{evt:?}")),
-        kardinality::TraceEvent::Call { .. } => ("trace-item call", format!("{evt:?}")),
-        kardinality::TraceEvent::EffectApplied { .. } => ("trace-item effect", format!("{evt:?}")),
-        _ => ("trace-item", format!("{evt:?}")),
+    let class = match &evt {
+        kardinality::TraceEvent::Error(_) => "trace-item error",
+        kardinality::TraceEvent::Call { .. } => "trace-item call",
+        kardinality::TraceEvent::EffectApplied { .. } => "trace-item effect",
+        _ => "trace-item",
     };
+    let text = evt.to_string();
 
     rsx! { div { class: "{class}", "{text}" } }
 }
@@ -240,9 +691,20 @@ fn theme_from_puzzle_key(key: &str) -> Option<UiTheme> {
     }
 }
 
-fn puzzle_goal_text(state: &kardinality::game::GameState) -> String {
-    match state.puzzle_bankroll_goal {
-        Some(goal) => format!("score >= {} and bank >= {}", state.target_score, goal),
-        None => format!("score >= {}", state.target_score),
+fn par_text(par: &kardinality::game::puzzles::Par) -> String {
+    let mut parts = Vec::new();
+    if let Some(plays) = par.plays {
+        parts.push(format!("{plays} play(s)"));
+    }
+    if let Some(cards) = par.cards {
+        parts.push(format!("{cards} card(s)"));
     }
+    if let Some(surplus) = par.surplus {
+        parts.push(format!("+{surplus} bank"));
+    }
+    parts.join(", ")
+}
+
+fn puzzle_goal_text(state: &kardinality::game::GameState) -> String {
+    goals::describe(&goals::active(state))
 }
@@ -0,0 +1,136 @@
+use dioxus::prelude::*;
+
+use kardinality::game::timeline::{self, CallRecord, CardLane};
+use kardinality::game::trace::Registers;
+
+type Read = fn(&Registers) -> i64;
+
+/// Register rows of the before/after table.
+const ROWS: [(&str, Read); 6] = [
+    ("score", |r| r.score),
+    ("bankroll", |r| r.bankroll),
+    ("acc", |r| r.acc),
+    ("source", |r| r.len_source as i64),
+    ("Code Bank", |r| r.len_deck as i64),
+    ("pile", |r| r.len_pile as i64),
+];
+
+fn call_text(call: &CallRecord) -> String {
+    let args = call
+        .args
+        .iter()
+        .zip(&call.values)
+        .map(|(src, value)| match value {
+            Some(v) if v.to_string() != *src => format!("{src}={v}"),
+            Some(_) => src.clone(),
+            None => format!("{src}=?"),
+        })
+        .collect::<Vec<_>>();
+    format!("[{}] {}({})", call.step, call.name, args.join(", "))
+}
+
+fn lane_label(lane: &CardLane) -> String {
+    match &lane.delta {
+        Some(delta) if delta.score != 0 => format!("{} {:+}", lane.name, delta.score),
+        Some(_) => lane.name.clone(),
+        None => format!("{} ✕", lane.name),
+    }
+}
+
+/// The last hand as one lane per executed card, with a scrubber and per-card details.
+#[component]
+pub fn TraceTimeline(engine: Signal<kardinality::Engine>) -> Element {
+    // Selected lane, keyed by the hand's first seq so a new hand starts at its last card.
+    let mut cursor = use_signal(|| None::<(u64, usize)>);
+
+    let eng = engine.read();
+    let Some((segment, lanes)) = timeline::last_hand(&eng.state.trace) else {
+        return rsx! {};
+    };
+    if lanes.is_empty() {
+        return rsx! {};
+    }
+    let seq = segment.first_seq;
+    let turn = segment.turn;
+    drop(eng);
+
+    let last = lanes.len() - 1;
+    let selected = match cursor() {
+        Some((s, index)) if s == seq => index.min(last),
+        _ => last,
+    };
+    let lane = lanes[selected].clone();
+    let queue_before = lane.before.queue.len();
+    let queue_after = lane.after.as_ref().map(|a| a.queue.len());
+    let rows = ROWS
+        .iter()
+        .map(|(label, get)| {
+            let before = get(&lane.before.regs);
+            let after = lane.after.as_ref().map(|a| get(&a.regs));
+            (*label, before, after)
+        })
+        .collect::<Vec<_>>();
+
+    rsx! {
+        div { class: "panel sidebar-panel", "data-testid": "trace-timeline",
+            h3 { "Hand {turn} timeline" }
+            div { class: "timeline-lanes",
+                for (i, l) in lanes.iter().enumerate() {
+                    button {
+                        key: "{i}",
+                        class: if i == selected { "timeline-lane active" } else { "timeline-lane" },
+                        title: "{l.script}",
+                        onclick: move |_| cursor.set(Some((seq, i))),
+                        "{lane_label(l)}"
+                    }
+                }
+            }
+            input {
+                r#type: "range",
+                class: "timeline-scrubber",
+                "data-testid": "timeline-scrubber",
+                min: "0",
+                max: "{last}",
+                value: "{selected}",
+                oninput: move |evt| {
+                    if let Ok(index) = evt.value().parse::<usize>() {
+                        cursor.set(Some((seq, index)));
+                    }
+                },
+            }
+            div { class: "hint", "Card {selected + 1} of {lanes.len()}: #{lane.card_id} {lane.name}" }
+            code { class: "timeline-script", "{lane.script}" }
+            table { class: "timeline-regs",
+                tr { th { "" } th { "before" } th { "after" } th { "Δ" } }
+                for (label, before, after) in rows {
+                    tr { key: "{label}",
+                        td { "{label}" }
+                        td { "{before}" }
+                        td { {after.map_or("—".to_string(), |a| a.to_string())} }
+                        td { {after.map_or(String::new(), |a| format!("{:+}", a - before))} }
+                    }
+                }
+                tr {
+                    td { "queue" }
+                    td { "{queue_before}" }
+                    td { {queue_after.map_or("—".to_string(), |a| a.to_string())} }
+                    td { "" }
+                }
+            }
+            div { class: "trace-list selectable",
+                for call in lane.calls.iter() {
+                    div { class: "trace-item call", "{call_text(call)}" }
+                }
+                for effect in lane.effects.iter() {
+                    div { class: "trace-item effect", "→ {effect}" }
+                }
+                for note in lane.notes.iter() {
+                    div {
+                        class: if matches!(note, kardinality::TraceEvent::Error(_)) { "trace-item error" } else { "trace-item" },
+                        "{note}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -10,4 +12,63 @@ pub enum Effect {
     Clone(i64),
     Again(i64),
     Mutate,
+    /// Send the next `n` queued cards to the pile without running them.
+    Skip(i64),
+    /// Swap the next two queued cards.
+    SwapNext,
+    /// Reverse the order of the queued cards.
+    Reverse,
+    /// Move the `n`-th queued card (counting from 1) to the front.
+    Pull(i64),
+}
+
+/// Short readable form for traces and the timeline, e.g. `score +2` or `bank ×2`.
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name();
+        match self {
+            Effect::AddScore(n) | Effect::AddBankroll(n) => write!(f, "{name} {n:+}"),
+            Effect::MulBankroll(n) => write!(f, "bank ×{n}"),
+            Effect::SetAcc(n) => write!(f, "acc = {n}"),
+            Effect::Clone(n) | Effect::Again(n) => write!(f, "{name} ×{n}"),
+            Effect::Draw(n) | Effect::Skip(n) => write!(f, "{name} {n}"),
+            Effect::Pull(n) => write!(f, "{name} #{n}"),
+            Effect::Mutate | Effect::SwapNext | Effect::Reverse => f.write_str(name),
+        }
+    }
+}
+
+impl Effect {
+    /// Every value [`Effect::name`] can return.
+    pub const NAMES: [&'static str; 12] = [
+        "score", "bank", "dbl", "draw", "acc", "clone", "again", "mutate", "skip", "swap", "rev",
+        "pull",
+    ];
+
+    /// Stable name of the effect kind, shared by achievement rules, relic filters and queue
+    /// events. Effects produced by a builtin use its long name (`bank`, `dbl`, `clone`, ...).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Effect::AddScore(_) => "score",
+            Effect::AddBankroll(_) => "bank",
+            Effect::MulBankroll(_) => "dbl",
+            Effect::Draw(_) => "draw",
+            Effect::SetAcc(_) => "acc",
+            Effect::Clone(_) => "clone",
+            Effect::Again(_) => "again",
+            Effect::Mutate => "mutate",
+            Effect::Skip(_) => "skip",
+            Effect::SwapNext => "swap",
+            Effect::Reverse => "rev",
+            Effect::Pull(_) => "pull",
+        }
+    }
+
+    /// Whether the effect rearranges the pending execution queue.
+    pub fn is_queue_op(&self) -> bool {
+        matches!(
+            self,
+            Effect::Skip(_) | Effect::SwapNext | Effect::Reverse | Effect::Pull(_)
+        )
+    }
 }
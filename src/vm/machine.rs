@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::kardlang::{BinOp, Call, Expr};
 
-use super::{Effect, Limits};
+use super::{Effect, Limits, VmRules};
 
 pub trait VmContext {
     fn get(&self, name: &str) -> Option<i64>;
@@ -28,17 +28,34 @@ pub enum VmError {
 
     #[error("execution aborted: exceeded max steps ({max_steps})")]
     StepLimitExceeded { max_steps: usize },
+
+    #[error("{0}() is disabled on this level")]
+    Disabled(String),
 }
 
 #[derive(Debug, Clone)]
 pub struct Machine {
     steps: usize,
     limits: Limits,
+    rules: VmRules,
 }
 
 impl Machine {
     pub fn new(limits: Limits) -> Self {
-        Self { steps: 0, limits }
+        Self::with_rules(limits, VmRules::default())
+    }
+
+    pub fn with_rules(limits: Limits, rules: VmRules) -> Self {
+        Self {
+            steps: 0,
+            limits,
+            rules,
+        }
+    }
+
+    /// Calls evaluated so far (including any that failed).
+    pub fn steps(&self) -> usize {
+        self.steps
     }
 
     pub fn eval_call<C: VmContext>(
@@ -90,6 +107,7 @@ impl Machine {
             }
             "mutate" | "m" => {
                 expect_arity(call, 0)?;
+                self.check_mutate(call)?;
                 Ok(vec![Effect::Mutate])
             }
             "jam" | "j" => {
@@ -103,7 +121,8 @@ impl Machine {
             "cash" | "v" => {
                 let n = expect_one_int(call, ctx)?;
                 let spend = n.checked_neg().ok_or(VmError::Overflow)?;
-                Ok(vec![Effect::AddScore(n), Effect::AddBankroll(spend)])
+                let score = if self.rules.cash_halved { n / 2 } else { n };
+                Ok(vec![Effect::AddScore(score), Effect::AddBankroll(spend)])
             }
             "hedge" | "h" => {
                 let n = expect_one_int(call, ctx)?;
@@ -123,11 +142,40 @@ impl Machine {
             }
             "wild" | "w" => {
                 let n = expect_one_int(call, ctx)?;
+                self.check_mutate(call)?;
                 Ok(vec![Effect::Mutate, Effect::Again(n)])
             }
+            "skip" | "k" => {
+                let n = expect_one_int(call, ctx)?;
+                Ok(vec![Effect::Skip(n)])
+            }
+            "swap" | "z" => {
+                expect_arity(call, 0)?;
+                Ok(vec![Effect::SwapNext])
+            }
+            "rev" | "r" => {
+                expect_arity(call, 0)?;
+                Ok(vec![Effect::Reverse])
+            }
+            "pull" | "p" => {
+                let n = expect_one_int(call, ctx)?;
+                Ok(vec![Effect::Pull(n)])
+            }
             other => Err(VmError::UnknownFunction(other.to_string())),
         }
     }
+
+    fn check_mutate(&self, call: &Call) -> Result<(), VmError> {
+        if self.rules.mutate_disabled {
+            return Err(VmError::Disabled(call.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Best-effort values of a call's arguments, for tracing; `None` where evaluation fails.
+pub fn eval_args<C: VmContext>(call: &Call, ctx: &C) -> Vec<Option<i64>> {
+    call.args.iter().map(|a| eval_expr(a, ctx).ok()).collect()
 }
 
 fn expect_arity(call: &Call, expected: usize) -> Result<(), VmError> {
@@ -254,6 +302,26 @@ mod tests {
         assert_eq!(dbl, vec![Effect::MulBankroll(2)]);
     }
 
+    #[test]
+    fn queue_builtins_map_to_queue_effects() {
+        let mut vm = Machine::new(Limits::default());
+        let ctx = TestCtx::from_pairs(&[("len_queue", 3)]);
+
+        let cases = [
+            ("k(11)", Effect::Skip(2)),
+            ("skip(1)", Effect::Skip(1)),
+            ("z()", Effect::SwapNext),
+            ("r()", Effect::Reverse),
+            ("pull(len_queue)", Effect::Pull(3)),
+        ];
+        for (src, effect) in cases {
+            let out = vm.eval_call(&parse_single_call(src), &ctx).unwrap();
+            assert!(effect.is_queue_op());
+            assert_eq!(out, vec![effect], "{src}");
+        }
+        assert!(vm.eval_call(&parse_single_call("r(1)"), &ctx).is_err());
+    }
+
     #[test]
     fn hedge_switches_between_score_and_bank_modes() {
         let mut vm = Machine::new(Limits::default());
@@ -283,4 +351,21 @@ mod tests {
         let wild = vm.eval_call(&parse_single_call("w(1)"), &ctx).unwrap();
         assert_eq!(wild, vec![Effect::Mutate, Effect::Again(1)]);
     }
+
+    #[test]
+    fn rules_disable_mutation_and_halve_cash() {
+        let rules = VmRules {
+            mutate_disabled: true,
+            cash_halved: true,
+        };
+        let mut vm = Machine::with_rules(Limits::default(), rules);
+        let ctx = TestCtx::from_pairs(&[]);
+
+        for source in ["m()", "w(1)"] {
+            let err = vm.eval_call(&parse_single_call(source), &ctx).unwrap_err();
+            assert!(matches!(err, VmError::Disabled(_)));
+        }
+        let cash = vm.eval_call(&parse_single_call("v(111)"), &ctx).unwrap();
+        assert_eq!(cash, vec![Effect::AddScore(1), Effect::AddBankroll(-3)]);
+    }
 }
@@ -1,7 +1,9 @@
 mod effects;
 mod limits;
 mod machine;
+mod rules;
 
 pub use effects::Effect;
 pub use limits::Limits;
-pub use machine::{Machine, VmContext, VmError};
+pub use machine::{Machine, VmContext, VmError, eval_args};
+pub use rules::VmRules;
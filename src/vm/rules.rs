@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// Rule tweaks a [`Machine`](super::Machine) applies to builtins (used by boss levels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VmRules {
+    /// Builtins that mutate (`mutate`, `wild`) fail with `VmError::Disabled`.
+    pub mutate_disabled: bool,
+    /// Score from `cash` is halved (rounded down); its bankroll cost is unchanged.
+    pub cash_halved: bool,
+}